@@ -1,5 +1,6 @@
-use std::{env, path::PathBuf};
+use std::{env, num::NonZeroUsize, path::PathBuf};
 
+use cargo_metadata::camino::Utf8PathBuf;
 use clap::{ColorChoice, Parser as _};
 use clap_verbosity_flag::{InfoLevel, Verbosity};
 
@@ -22,7 +23,7 @@ pub(crate) fn parse() -> Args {
 }
 
 /// Synchronize a package README and additional configured Markdown files with package metadata and crate documentation.
-#[derive(Debug, Clone, Default, clap::Parser)]
+#[derive(Debug, Clone, clap::Parser)]
 #[command(
     name = "cargo-sync-rdme",
     bin_name = "cargo sync-rdme",
@@ -41,6 +42,9 @@ pub(crate) struct Args {
     pub(crate) mode: ModeArgs,
     #[command(flatten)]
     pub(crate) fix: FixArgs,
+    /// Number of packages to synchronize in parallel.
+    #[arg(long, short = 'j', value_name = "N", default_value = "1")]
+    pub(crate) jobs: NonZeroUsize,
     #[command(flatten, next_help_heading = "Package Selection")]
     pub(crate) package: PackageSelection,
     #[command(flatten, next_help_heading = "Feature Selection")]
@@ -90,6 +94,15 @@ pub(crate) struct RustdocToolchainArgs {
     /// Install the Rust toolchain specified by `--toolchain` if it is not already installed.
     #[arg(long)]
     pub(crate) install_toolchain: bool,
+    /// Cache rustdoc JSON output in the target directory and reuse it while the sources, features and toolchain are unchanged.
+    #[arg(long)]
+    pub(crate) cache: bool,
+    /// Read pre-built rustdoc JSON instead of running rustdoc. If a directory is given, `<crate name>.json` in it is read for each package.
+    #[arg(long, value_name = "PATH")]
+    pub(crate) rustdoc_json: Option<Utf8PathBuf>,
+    /// Read crate documentation from the source of the crate root instead of running rustdoc, so that a nightly toolchain is not needed. Intra-doc links are only resolved by `rustdoc.mappings`.
+    #[arg(long, conflicts_with = "rustdoc_json")]
+    pub(crate) docs_from_source: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -98,20 +111,47 @@ pub(crate) enum Mode {
     Fix,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// Synchronize target files from crate documentation.
+    Forward,
+    /// Synchronize crate documentation from the package README.
+    Reverse,
+}
+
 #[derive(Debug, Clone, Default, clap::Args)]
 pub(crate) struct ModeArgs {
     /// Check whether target files are up to date.
     #[arg(long)]
     check: bool,
+    /// Synchronize crate documentation in the crate root from the `rustdoc` marker region of the package README.
+    #[arg(long)]
+    reverse: bool,
+    /// Write copies of target files with marker comments removed to the paths configured in `strip-markers-output` or to stdout, instead of updating target files.
+    #[arg(long, conflicts_with = "reverse")]
+    strip_markers: bool,
 }
 
 impl ModeArgs {
     pub(crate) fn mode(&self) -> Mode {
         if self.check { Mode::Check } else { Mode::Fix }
     }
+
+    pub(crate) fn strip_markers(&self) -> bool {
+        self.strip_markers
+    }
+
+    pub(crate) fn direction(&self) -> Direction {
+        if self.reverse {
+            Direction::Reverse
+        } else {
+            Direction::Forward
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, clap::Args)]
+#[expect(clippy::struct_excessive_bools)]
 pub(crate) struct FixArgs {
     /// Synchronize target files even if no VCS was detected.
     #[arg(long)]
@@ -122,6 +162,9 @@ pub(crate) struct FixArgs {
     /// Synchronize target files even if one has staged changes.
     #[arg(long)]
     pub(crate) allow_staged: bool,
+    /// Overwrite marker regions even if they were edited by hand since the last synchronization.
+    #[arg(long)]
+    pub(crate) force: bool,
 }
 
 #[cfg(test)]
@@ -33,6 +33,9 @@ pub(crate) fn command_for_build_doc(args: &RustdocToolchainArgs) -> Command {
     let RustdocToolchainArgs {
         toolchain,
         install_toolchain,
+        cache: _,
+        rustdoc_json: _,
+        docs_from_source: _,
     } = args;
 
     let Some(toolchain) = toolchain.as_ref() else {
@@ -213,7 +216,13 @@ pub(crate) enum ToolchainError {
     },
 }
 
-pub(crate) fn toolchain(args: Option<&RustdocToolchainArgs>) -> Result<Toolchain, ToolchainError> {
+/// Returns the toolchain and the output of `cargo --version --verbose`.
+///
+/// The output identifies the exact build of the toolchain, including the
+/// commit hash of nightly toolchains.
+pub(crate) fn toolchain(
+    args: Option<&RustdocToolchainArgs>,
+) -> Result<(Toolchain, String), ToolchainError> {
     let mut cmd = if let Some(args) = args {
         command_for_build_doc(args)
     } else {
@@ -246,7 +255,7 @@ pub(crate) fn toolchain(args: Option<&RustdocToolchainArgs>) -> Result<Toolchain
             stderr: output.stderr,
         })?;
     let Ok(toolchain) = Toolchain::from_str(release_line.trim());
-    Ok(toolchain)
+    Ok((toolchain, stdout))
 }
 
 #[cfg(test)]
@@ -173,3 +173,37 @@ where
     let map = deserializer.deserialize_any(StringOrMap(PhantomData))?;
     Ok(map)
 }
+
+/// Deserializes a map into a list of entries, preserving the order of the keys.
+pub(super) fn ordered_map<'de, V, D>(deserializer: D) -> Result<Vec<(String, V)>, D::Error>
+where
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    struct OrderedMap<V>(PhantomData<V>);
+
+    impl<'de, V> Visitor<'de> for OrderedMap<V>
+    where
+        V: Deserialize<'de>,
+    {
+        type Value = Vec<(String, V)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a map")
+        }
+
+        fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+        where
+            M: serde::de::MapAccess<'de>,
+        {
+            let mut entries = vec![];
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+            Ok(entries)
+        }
+    }
+
+    let map = deserializer.deserialize_map(OrderedMap(PhantomData))?;
+    Ok(map)
+}
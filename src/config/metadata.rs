@@ -1,9 +1,11 @@
 use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
 
+use cargo_metadata::camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 use serde::{
     Deserialize,
     de::{Error as _, Visitor},
 };
+use toml::Spanned;
 use void::Void;
 
 use crate::parse;
@@ -26,6 +28,114 @@ pub(crate) struct CargoSyncRdme {
     pub(crate) badge: Badge,
     #[serde(default)]
     pub(crate) rustdoc: Rustdoc,
+    #[serde(default)]
+    pub(crate) install: Install,
+    #[serde(default)]
+    pub(crate) changelog: Changelog,
+    #[serde(default)]
+    pub(crate) replacements: Vec<Replacement>,
+    #[serde(default)]
+    pub(crate) templates: HashMap<String, Spanned<String>>,
+    /// Marker style of each target file, keyed by the path relative to the package root.
+    #[serde(default)]
+    pub(crate) marker_style: HashMap<String, MarkerStyle>,
+    /// Format of each target file, keyed by the path relative to the package root.
+    #[serde(default)]
+    pub(crate) target_format: HashMap<String, TargetFormat>,
+    /// Output path of each target file with the markers stripped, keyed by the
+    /// path relative to the package root.
+    #[serde(default)]
+    pub(crate) strip_markers_output: HashMap<String, String>,
+    /// Whether to record the hashes of the generated contents in the start markers.
+    #[serde(default)]
+    pub(crate) content_hash: bool,
+}
+
+impl CargoSyncRdme {
+    /// Returns the marker style of the target file at the package-relative `path`.
+    ///
+    /// Marker styles are only configurable for Markdown files. Markers in the
+    /// other formats are written as the comments of the format.
+    pub(crate) fn marker_style(&self, path: &Utf8Path) -> MarkerStyle {
+        match self.target_format(path) {
+            TargetFormat::Markdown => lookup(&self.marker_style, path)
+                .copied()
+                .unwrap_or_default(),
+            TargetFormat::Asciidoc => MarkerStyle::Asciidoc,
+            TargetFormat::Rst => MarkerStyle::Rst,
+        }
+    }
+
+    /// Returns the format of the target file at the package-relative `path`.
+    ///
+    /// If the format is not configured, it is inferred from the file extension.
+    pub(crate) fn target_format(&self, path: &Utf8Path) -> TargetFormat {
+        lookup(&self.target_format, path)
+            .copied()
+            .unwrap_or_else(|| TargetFormat::from_path(path))
+    }
+
+    /// Returns the package-relative output path of the target file at the
+    /// package-relative `path` with the markers stripped.
+    pub(crate) fn strip_markers_output(&self, path: &Utf8Path) -> Option<&Utf8Path> {
+        lookup(&self.strip_markers_output, path).map(Utf8Path::new)
+    }
+}
+
+fn lookup<'a, T>(map: &'a HashMap<String, T>, path: &Utf8Path) -> Option<&'a T> {
+    let normalize = |path: &Utf8Path| -> Utf8PathBuf {
+        path.components()
+            .filter(|component| *component != Utf8Component::CurDir)
+            .collect()
+    };
+    let path = normalize(path);
+    map.iter()
+        .find(|(target, _value)| normalize(Utf8Path::new(target)) == path)
+        .map(|(_target, value)| value)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) enum MarkerStyle {
+    /// HTML comments: `<!-- cargo-sync-rdme ... -->`.
+    #[default]
+    Html,
+    /// Link reference definitions: `[//]: # (cargo-sync-rdme ...)`.
+    LinkReference,
+    /// MDX comments: `{/* cargo-sync-rdme ... */}`.
+    Mdx,
+    /// Asciidoc comment lines: `// cargo-sync-rdme ...`.
+    ///
+    /// Not configurable. Used for Asciidoc target files.
+    #[serde(skip)]
+    Asciidoc,
+    /// reStructuredText comments: `.. cargo-sync-rdme ...`.
+    ///
+    /// Not configurable. Used for reStructuredText target files.
+    #[serde(skip)]
+    Rst,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) enum TargetFormat {
+    /// Markdown with GitHub Flavored Markdown extensions.
+    #[default]
+    Markdown,
+    /// Asciidoc.
+    Asciidoc,
+    /// reStructuredText.
+    Rst,
+}
+
+impl TargetFormat {
+    fn from_path(path: &Utf8Path) -> Self {
+        match path.extension() {
+            Some("adoc" | "asciidoc" | "asc") => Self::Asciidoc,
+            Some("rst") => Self::Rst,
+            _ => Self::Markdown,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -67,6 +177,7 @@ pub(crate) enum BadgeItem {
     RustVersion,
     GithubActions(GithubActions),
     Codecov(Codecov),
+    Custom(Custom),
 }
 
 #[derive(Debug, Clone)]
@@ -78,6 +189,7 @@ enum BadgeKind {
     RustVersion,
     GithubActions,
     Codecov,
+    Custom,
 }
 
 impl FromStr for BadgeKind {
@@ -92,6 +204,7 @@ impl FromStr for BadgeKind {
             "rust-version" => Self::RustVersion,
             "github-actions" => Self::GithubActions,
             "codecov" => Self::Codecov,
+            "custom" => Self::Custom,
             _ => {
                 if s.starts_with("maintenance-") {
                     Self::Maintenance
@@ -107,6 +220,8 @@ impl FromStr for BadgeKind {
                     Self::GithubActions
                 } else if s.starts_with("codecov-") {
                     Self::Codecov
+                } else if s.starts_with("custom-") {
+                    Self::Custom
                 } else {
                     return Err(());
                 }
@@ -126,6 +241,7 @@ impl BadgeKind {
             "rust-version",
             "github-actions",
             "codecov",
+            "custom",
             "maintenance-*",
             "license-*",
             "crates-io-*",
@@ -133,6 +249,7 @@ impl BadgeKind {
             "rust-version-*",
             "github-actions-*",
             "codecov-*",
+            "custom-*",
         ]
     }
 }
@@ -201,6 +318,9 @@ where
                             data.push(BadgeItem::Codecov(codecov));
                         }
                     }
+                    BadgeKind::Custom => {
+                        data.push(BadgeItem::Custom(map.next_value::<Custom>()?));
+                    }
                 }
             }
             Ok(data.into())
@@ -310,6 +430,15 @@ pub(crate) struct Codecov {
     pub(crate) component: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Custom {
+    pub(crate) name: Spanned<String>,
+    #[serde(default)]
+    pub(crate) link: Option<Spanned<String>>,
+    pub(crate) image: Spanned<String>,
+}
+
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub(crate) struct Rustdoc {
@@ -317,4 +446,50 @@ pub(crate) struct Rustdoc {
     pub(crate) html_root_url: Option<String>,
     #[serde(default)]
     pub(crate) mappings: HashMap<String, String>,
+    #[serde(default)]
+    pub(crate) json: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Install {
+    #[serde(default = "default_true")]
+    pub(crate) default_features: bool,
+    #[serde(default)]
+    pub(crate) features: Vec<String>,
+}
+
+impl Default for Install {
+    fn default() -> Self {
+        Self {
+            default_features: true,
+            features: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Changelog {
+    #[serde(default)]
+    pub(crate) path: Option<String>,
+}
+
+impl Changelog {
+    const DEFAULT_PATH: &str = "CHANGELOG.md";
+
+    pub(crate) fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or(Self::DEFAULT_PATH)
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Replacement {
+    pub(crate) search: Spanned<String>,
+    pub(crate) replace: Spanned<String>,
 }
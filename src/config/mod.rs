@@ -56,6 +56,9 @@ pub(crate) struct Manifest {
     pub(crate) package: Option<Spanned<package::Package>>,
     #[serde(default)]
     pub(crate) badges: Option<Spanned<badges::Badges>>,
+    /// Entries of the `[features]` table in the order of the manifest.
+    #[serde(default, deserialize_with = "de::ordered_map")]
+    pub(crate) features: Vec<(String, Spanned<Vec<String>>)>,
 }
 
 impl WithSource<Manifest> {
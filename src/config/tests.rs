@@ -3,8 +3,11 @@ use std::assert_matches;
 use indoc::{formatdoc, indoc};
 use similar_asserts::assert_eq;
 
+use cargo_metadata::camino::Utf8Path;
+
 use crate::config::metadata::{
-    Badge, BadgeItem, Codecov, GithubActions, GithubActionsWorkflow, License, Rustdoc,
+    Badge, BadgeItem, Codecov, Custom, GithubActions, GithubActionsWorkflow, License, MarkerStyle,
+    Rustdoc, TargetFormat,
 };
 
 use super::*;
@@ -349,6 +352,41 @@ fn test_codecov() {
     );
 }
 
+#[test]
+fn test_custom() {
+    let badges = get_default_badges(badge_manifest(indoc! {r#"
+        badges = {
+          custom = { name = "foo", image = "https://example.com/{{package_name}}.svg" },
+          custom-bar = { name = "bar", link = "https://example.com/", image = "bar.svg" },
+        }
+    "#}));
+    assert_matches!(
+        &*badges,
+        [
+            BadgeItem::Custom(Custom { name: name1, link: None, image: image1 }),
+            BadgeItem::Custom(Custom { name: name2, link: Some(link2), image: image2 }),
+        ] if name1.get_ref() == "foo"
+            && image1.get_ref() == "https://example.com/{{package_name}}.svg"
+            && name2.get_ref() == "bar"
+            && link2.get_ref() == "https://example.com/"
+            && image2.get_ref() == "bar.svg"
+    );
+
+    let err = badge_manifest_err(indoc! {r#"
+        badges = {
+          custom = { name = "foo" },
+        }
+    "#});
+    assert!(err.to_string().contains("missing field `image`"));
+
+    let err = badge_manifest_err(indoc! {r"
+        badges = {
+          custom = true,
+        }
+    "});
+    assert!(err.to_string().contains("expected struct Custom"));
+}
+
 #[test]
 fn test_rustdoc() {
     let rustdoc = get_rustdoc(rustdoc_manifest(indoc! {r#"
@@ -372,3 +410,180 @@ fn test_rustdoc() {
         Some("./docs/some-trait.md")
     );
 }
+
+#[test]
+fn test_features() {
+    let manifest: Manifest = toml::from_str(indoc! {r#"
+        [features]
+        default = ["std"]
+        std = []
+        alloc = []
+    "#})
+    .unwrap();
+    let features = manifest
+        .features
+        .iter()
+        .map(|(name, enables)| (name.as_str(), enables.get_ref().clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        features,
+        [
+            ("default", vec!["std".to_owned()]),
+            ("std", vec![]),
+            ("alloc", vec![]),
+        ]
+    );
+
+    let manifest: Manifest = toml::from_str("").unwrap();
+    assert_eq!(manifest.features, []);
+}
+
+#[test]
+fn test_install() {
+    let get_install = |config: &str| {
+        let manifest: Manifest = toml::from_str(&formatdoc! {r"
+            [package.metadata.cargo-sync-rdme.install]
+            {config}
+        "})
+        .unwrap();
+        manifest.config().install.clone()
+    };
+
+    let install = get_install("");
+    assert!(install.default_features);
+    assert_eq!(install.features, Vec::<String>::new());
+
+    let install = get_install(indoc! {r#"
+        default-features = false
+        features = ["std"]
+    "#});
+    assert!(!install.default_features);
+    assert_eq!(install.features, ["std"]);
+}
+
+#[test]
+fn test_replacements() {
+    let manifest: Manifest = toml::from_str(indoc! {r#"
+        [[package.metadata.cargo-sync-rdme.replacements]]
+        search = 'foo = "[0-9.]+"'
+        replace = 'foo = "{{major_minor}}"'
+
+        [[package.metadata.cargo-sync-rdme.replacements]]
+        search = "foo@[0-9.]+"
+        replace = "foo@{{version}}"
+    "#})
+    .unwrap();
+    let replacements = &manifest.config().replacements;
+    assert_eq!(replacements.len(), 2);
+    assert_eq!(replacements[0].search.get_ref(), r#"foo = "[0-9.]+""#);
+    assert_eq!(
+        replacements[0].replace.get_ref(),
+        r#"foo = "{{major_minor}}""#
+    );
+    assert_eq!(replacements[1].search.get_ref(), "foo@[0-9.]+");
+    assert_eq!(replacements[1].replace.get_ref(), "foo@{{version}}");
+
+    let manifest: Manifest = toml::from_str("").unwrap();
+    assert!(manifest.config().replacements.is_empty());
+}
+
+#[test]
+fn test_changelog() {
+    let manifest: Manifest = toml::from_str("").unwrap();
+    assert_eq!(manifest.config().changelog.path(), "CHANGELOG.md");
+
+    let manifest: Manifest = toml::from_str(indoc! {r#"
+        [package.metadata.cargo-sync-rdme.changelog]
+        path = "docs/CHANGES.md"
+    "#})
+    .unwrap();
+    assert_eq!(manifest.config().changelog.path(), "docs/CHANGES.md");
+}
+
+#[test]
+fn test_templates() {
+    let manifest: Manifest = toml::from_str(indoc! {r#"
+        [package.metadata.cargo-sync-rdme.templates]
+        footer = "Part of the [foo](https://example.com) project."
+        keywords = """
+        {{#each keywords}}
+        * {{this}}
+        {{/each}}
+        """
+    "#})
+    .unwrap();
+    let templates = &manifest.config().templates;
+    assert_eq!(templates.len(), 2);
+    assert_eq!(
+        templates["footer"].get_ref(),
+        "Part of the [foo](https://example.com) project."
+    );
+    assert_eq!(
+        templates["keywords"].get_ref(),
+        "{{#each keywords}}\n* {{this}}\n{{/each}}\n"
+    );
+
+    let manifest: Manifest = toml::from_str("").unwrap();
+    assert!(manifest.config().templates.is_empty());
+}
+
+#[test]
+fn test_marker_style() {
+    let manifest: Manifest = toml::from_str(indoc! {r#"
+        [package.metadata.cargo-sync-rdme.marker-style]
+        "./docs/index.mdx" = "mdx"
+        "docs/wiki.md" = "link-reference"
+    "#})
+    .unwrap();
+    let config = manifest.config();
+    assert_eq!(
+        config.marker_style(Utf8Path::new("docs/index.mdx")),
+        MarkerStyle::Mdx
+    );
+    assert_eq!(
+        config.marker_style(Utf8Path::new("./docs/wiki.md")),
+        MarkerStyle::LinkReference
+    );
+    assert_eq!(
+        config.marker_style(Utf8Path::new("README.md")),
+        MarkerStyle::Html
+    );
+
+    let err = toml::from_str::<Manifest>(indoc! {r#"
+        [package.metadata.cargo-sync-rdme.marker-style]
+        "README.md" = "markdown"
+    "#})
+    .unwrap_err();
+    assert!(err.to_string().contains("unknown variant `markdown`"));
+}
+
+#[test]
+fn test_target_format() {
+    let manifest: Manifest = toml::from_str(indoc! {r#"
+        [package.metadata.cargo-sync-rdme.target-format]
+        "./docs/guide.txt" = "asciidoc"
+        "docs/index.md" = "rst"
+
+        [package.metadata.cargo-sync-rdme.marker-style]
+        "docs/index.md" = "mdx"
+        "README.adoc" = "link-reference"
+    "#})
+    .unwrap();
+    let config = manifest.config();
+    let format = |path| config.target_format(Utf8Path::new(path));
+    let style = |path| config.marker_style(Utf8Path::new(path));
+    assert_eq!(format("README.md"), TargetFormat::Markdown);
+    assert_eq!(format("README.adoc"), TargetFormat::Asciidoc);
+    assert_eq!(format("docs/index.rst"), TargetFormat::Rst);
+    assert_eq!(format("docs/guide.txt"), TargetFormat::Asciidoc);
+    assert_eq!(format("./docs/index.md"), TargetFormat::Rst);
+    assert_eq!(style("docs/index.md"), MarkerStyle::Rst);
+    assert_eq!(style("README.adoc"), MarkerStyle::Asciidoc);
+
+    let err = toml::from_str::<Manifest>(indoc! {r#"
+        [package.metadata.cargo-sync-rdme.marker-style]
+        "README.md" = "asciidoc"
+    "#})
+    .unwrap_err();
+    assert!(err.to_string().contains("unknown variant `asciidoc`"));
+}
@@ -1,8 +1,13 @@
-use std::{fmt, io};
+use std::{
+    fmt,
+    io::{self, Write as _},
+};
 
 use similar::{ChangeTag, TextDiff};
 use supports_color::Stream;
 
+use crate::output;
+
 #[derive(Debug)]
 struct Line(Option<usize>);
 
@@ -46,10 +51,7 @@ pub(crate) fn write_pretty_diff(stream: Stream, old: &str, new: &str) -> Result<
     let styling = DiffStyler::new(stream);
     let diff = TextDiff::from_lines(old, new);
 
-    let mut output: &mut dyn io::Write = match stream {
-        Stream::Stdout => &mut io::stdout().lock(),
-        Stream::Stderr => &mut io::stderr().lock(),
-    };
+    let mut output = io::BufWriter::new(output::writer(stream));
 
     for (idx, group) in diff.grouped_ops(3).iter().enumerate() {
         if idx > 0 {
@@ -86,5 +88,5 @@ pub(crate) fn write_pretty_diff(stream: Stream, old: &str, new: &str) -> Result<
             }
         }
     }
-    Ok(())
+    output.flush()
 }
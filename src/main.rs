@@ -21,12 +21,16 @@ use supports_color::Stream;
 use tracing::Level;
 use tracing_subscriber::{EnvFilter, filter::LevelFilter, fmt::writer::BoxMakeWriter};
 
-use crate::{args::Args, sync::SyncOptions};
+use crate::{
+    args::Args,
+    sync::{RustdocCache, SyncOptions},
+};
 
 mod args;
 mod cargo;
 mod config;
 mod diff;
+mod output;
 mod parse;
 mod sync;
 mod traits;
@@ -52,22 +56,27 @@ fn main() -> miette::Result<()> {
     set_miette_hook(use_color, output_stream);
     install_logger(args.verbosity, use_color, output_stream);
 
+    let workspace = cargo::metadata(&args.manifest)?;
+    let rustdoc_cache = RustdocCache::new(&workspace, args.toolchain.cache);
     let sync_options = SyncOptions {
         mode: args.mode.mode(),
+        strip_markers: args.mode.strip_markers(),
         verbosity: args.verbosity.into(),
         diff_stream: output_stream,
         fix: &args.fix,
         toolchain: &args.toolchain,
         feature: &args.feature,
+        rustdoc_cache: &rustdoc_cache,
     };
 
-    let workspace = cargo::metadata(&args.manifest)?;
-    for package in cargo::select_packages(&workspace, &args.package)? {
-        sync::sync_all(&workspace, package, &sync_options)
-            .map_err(|source| miette::Report::new_boxed(source))?;
-    }
-
-    Ok(())
+    let packages = cargo::select_packages(&workspace, &args.package)?;
+    sync::sync_packages(
+        &workspace,
+        &packages,
+        args.mode.direction(),
+        &sync_options,
+        args.jobs,
+    )
 }
 
 fn should_use_color(choice: ColorChoice, stream: Stream) -> bool {
@@ -113,10 +122,7 @@ fn install_logger(verbosity: Verbosity<InfoLevel>, use_color: bool, stream: Stre
             .with_default_directive(default_level.into())
             .from_env_lossy()
     };
-    let writer = match stream {
-        Stream::Stdout => BoxMakeWriter::new(io::stdout),
-        Stream::Stderr => BoxMakeWriter::new(io::stderr),
-    };
+    let writer = BoxMakeWriter::new(move || output::writer(stream));
 
     tracing_subscriber::fmt()
         .with_env_filter(env_filter)
@@ -0,0 +1,198 @@
+//! Output streams that can be captured per thread.
+//!
+//! Packages synchronized in parallel write their logs, diffs and stripped
+//! files into buffers, which are printed in the order of the packages.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    env,
+    io::{self, Read, Write as _},
+    num::NonZeroUsize,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use supports_color::Stream;
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Captured>> = const { RefCell::new(None) };
+}
+
+/// Returns a writer to `stream`, or to the capture buffer of the current thread.
+pub(crate) fn writer(stream: Stream) -> Writer {
+    Writer(stream)
+}
+
+#[derive(Debug)]
+pub(crate) struct Writer(Stream);
+
+impl io::Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let captured = CAPTURED.with_borrow_mut(|captured| {
+            let Some(captured) = captured else {
+                return false;
+            };
+            captured.push(self.0, buf);
+            true
+        });
+        if captured {
+            return Ok(buf.len());
+        }
+        match self.0 {
+            Stream::Stdout => io::stdout().write(buf),
+            Stream::Stderr => io::stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.0 {
+            Stream::Stdout => io::stdout().flush(),
+            Stream::Stderr => io::stderr().flush(),
+        }
+    }
+}
+
+/// Pipes the standard error of the child process, so that it can be printed by
+/// [`writer`] in the order of the packages instead of being interleaved with
+/// the output of the other packages.
+pub(crate) fn pipe_stderr(command: &mut Command, stream: Stream) -> &mut Command {
+    let colored = match stream {
+        Stream::Stdout => console::colors_enabled(),
+        Stream::Stderr => console::colors_enabled_stderr(),
+    };
+    // Cargo does not colorize the output written to a pipe by default.
+    if colored && env::var_os("CARGO_TERM_COLOR").is_none() {
+        command.env("CARGO_TERM_COLOR", "always");
+    }
+    command.stderr(Stdio::piped())
+}
+
+/// Reads `reader` to the end on another thread, so that the child process does
+/// not block on a full pipe while its other output is read.
+pub(crate) fn read_in_background<R>(mut reader: R) -> thread::JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut buf = vec![];
+        // The output read before an error is still printed.
+        let _ = reader.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// Writes the output of a child process to `stream`, or to the capture buffer
+/// of the current thread.
+pub(crate) fn write_child_output(stream: Stream, output: &[u8]) {
+    // Errors are ignored in the same way as `eprint!` ignores broken pipes.
+    let _ = writer(stream).write_all(output);
+}
+
+/// Output written to the streams while capturing, in the order of writes.
+#[derive(Debug, Default)]
+struct Captured {
+    chunks: Vec<(Stream, Vec<u8>)>,
+}
+
+impl Captured {
+    fn push(&mut self, stream: Stream, buf: &[u8]) {
+        match self.chunks.last_mut() {
+            Some((last, chunk)) if is_same_stream(*last, stream) => chunk.extend_from_slice(buf),
+            _ => self.chunks.push((stream, buf.to_vec())),
+        }
+    }
+
+    fn print(self) {
+        for (stream, chunk) in self.chunks {
+            // Errors are ignored in the same way as `eprint!` ignores broken pipes.
+            let _ = match stream {
+                Stream::Stdout => io::stdout().lock().write_all(&chunk),
+                Stream::Stderr => io::stderr().lock().write_all(&chunk),
+            };
+        }
+    }
+}
+
+fn is_same_stream(a: Stream, b: Stream) -> bool {
+    matches!(
+        (a, b),
+        (Stream::Stdout, Stream::Stdout) | (Stream::Stderr, Stream::Stderr)
+    )
+}
+
+fn capture<R>(f: impl FnOnce() -> R) -> (R, Captured) {
+    CAPTURED.set(Some(Captured::default()));
+    let result = f();
+    let captured = CAPTURED.take().unwrap_or_default();
+    (result, captured)
+}
+
+/// Calls `f` for each item on up to `jobs` threads, and returns the results in
+/// the order of `items`.
+///
+/// The output of each call is printed after the output of the calls for the
+/// preceding items, as soon as they are finished.
+pub(crate) fn run_ordered<T, R, F>(items: &[T], jobs: NonZeroUsize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if jobs.get() == 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..usize::min(jobs.get(), items.len()) {
+            let (tx, next, f) = (tx.clone(), &next, &f);
+            scope.spawn(move || {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(idx) else {
+                        break;
+                    };
+                    let (result, captured) = capture(|| f(item));
+                    if tx.send((idx, result, captured)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        let mut pending = BTreeMap::new();
+        let mut results = Vec::with_capacity(items.len());
+        for (idx, result, captured) in rx {
+            pending.insert(idx, (result, captured));
+            while let Some((result, captured)) = pending.remove(&results.len()) {
+                captured.print();
+                results.push(result);
+            }
+        }
+        results
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn run_ordered_returns_results_in_order() {
+        let items = (0..16).collect::<Vec<u64>>();
+        let results = run_ordered(&items, NonZeroUsize::new(4).unwrap(), |&i| {
+            thread::sleep(Duration::from_millis((16 - i) % 5));
+            i * 2
+        });
+        assert_eq!(results, (0..16).map(|i| i * 2).collect::<Vec<_>>());
+    }
+}
@@ -17,7 +17,10 @@ use crate::{
         badges::MaintenanceStatus,
         metadata::{self, Codecov},
     },
-    sync::ManifestFile,
+    sync::{
+        ManifestFile,
+        template::{self, RenderTemplateError, Variables},
+    },
 };
 
 type CreateResult<T> = Result<T, Box<CreateBadgeError>>;
@@ -102,6 +105,9 @@ impl BadgeLinkSet {
             metadata::BadgeItem::Codecov(codecov) => {
                 BadgeLink::codecov(codecov, manifest, package)?.into()
             }
+            metadata::BadgeItem::Custom(custom) => {
+                BadgeLink::custom(custom, manifest, package)?.into()
+            }
         })
     }
 }
@@ -158,6 +164,13 @@ enum CreateBadgeError {
     },
     #[snafu(display("`package.repository` must start with `https://github.com/`"))]
     InvalidGithubRepository,
+    #[snafu(display("failed to render custom badge"))]
+    RenderCustomBadge {
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[related]
+        errors: Vec<RenderTemplateError>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -474,6 +487,36 @@ impl BadgeLink {
         })
     }
 
+    fn custom(
+        custom: &metadata::Custom,
+        manifest: &ManifestFile,
+        package: &Package,
+    ) -> CreateResult<Self> {
+        let variables = Variables::from_package(package);
+        let mut errors = vec![];
+        let mut render = |value| {
+            let input = manifest.map(|_| value).spanned_str();
+            template::render(input, &variables).unwrap_or_else(|errs| {
+                errors.extend(errs);
+                String::new()
+            })
+        };
+
+        let alt = render(&custom.name);
+        let link = custom.link.as_ref().map(&mut render);
+        let image = render(&custom.image);
+
+        ensure!(
+            errors.is_empty(),
+            RenderCustomBadgeSnafu {
+                source_code: manifest.to_named_source(),
+                errors,
+            }
+        );
+
+        Ok(Self { alt, link, image })
+    }
+
     fn github_actions_from_directory(
         workspace: &Metadata,
     ) -> CreateResult<Vec<CreateResult<(String, String)>>> {
@@ -0,0 +1,334 @@
+use std::{fs, io, ops::Range, sync::Arc};
+
+use cargo_metadata::{
+    Metadata, Package,
+    camino::{Utf8Path, Utf8PathBuf},
+};
+use miette::{NamedSource, SourceSpan};
+use pulldown_cmark::{CowStr, Event, HeadingLevel, LinkType, Options, Parser, Tag, TagEnd};
+use snafu::{OptionExt as _, ResultExt as _, Snafu};
+
+use super::{
+    relative_link,
+    rustdoc::{heading, link},
+    toc::Slugger,
+};
+use crate::{
+    sync::{
+        ManifestFile, MarkdownFile,
+        marker::{ChangelogOptions, ChangelogSection, LinkStyle},
+    },
+    traits::PackageExt as _,
+};
+
+const UNRELEASED: &str = "Unreleased";
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+pub(in super::super) enum CreateChangelogError {
+    #[snafu(display("failed to read changelog: {path}"))]
+    #[diagnostic(help(
+        "set the path of the changelog with `package.metadata.cargo-sync-rdme.changelog.path`"
+    ))]
+    ReadChangelog {
+        path: Utf8PathBuf,
+        #[snafu(source)]
+        source: io::Error,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("section `{section}` not found in changelog: {path}"))]
+    #[diagnostic(help("sections must be level 2 headings, e.g. `## [1.2.3] - 2024-01-01`"))]
+    SectionNotFound {
+        section: String,
+        path: Utf8PathBuf,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+}
+
+pub(super) fn create(
+    markdown: &MarkdownFile<'_>,
+    manifest: &ManifestFile,
+    workspace: &Metadata,
+    package: &Package,
+    span: SourceSpan,
+    options: &ChangelogOptions,
+) -> Result<String, Box<CreateChangelogError>> {
+    let config = &manifest.value().config().changelog;
+    let relative_path = package
+        .workspace_relative_root_directory(workspace)
+        .join(config.path());
+    let text = fs::read_to_string(workspace.workspace_root.join(&relative_path)).with_context(
+        |_source| ReadChangelogSnafu {
+            path: relative_path.clone(),
+            source_code: markdown.to_named_source(),
+            span,
+        },
+    )?;
+
+    let link = options
+        .link()
+        .then(|| {
+            let markdown_dir = markdown.relative_path.parent().unwrap_or(Utf8Path::new(""));
+            relative_link(markdown_dir, &relative_path)
+        })
+        .flatten();
+    let output =
+        render_section(&text, options, link.as_deref()).with_context(|| SectionNotFoundSnafu {
+            section: options.section().to_string(),
+            path: relative_path.clone(),
+            source_code: markdown.to_named_source(),
+            span,
+        })?;
+    Ok(output)
+}
+
+#[derive(Debug)]
+struct Section {
+    /// Version in the title, such as `Unreleased` or `1.2.3`.
+    version: String,
+    anchor: String,
+    /// Range of the heading events.
+    heading: Range<usize>,
+    /// Range of the events of the whole section, including the heading.
+    events: Range<usize>,
+}
+
+/// Renders the section of the changelog selected by `options`.
+///
+/// If `link` is given, the section title is linked to the section in the changelog at `link`.
+fn render_section(text: &str, options: &ChangelogOptions, link: Option<&str>) -> Option<String> {
+    let options_ext = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(text, options_ext).collect::<Vec<_>>();
+    let sections = sections(&events);
+
+    let section = match options.section() {
+        ChangelogSection::Latest => sections
+            .iter()
+            .find(|section| !section.version.eq_ignore_ascii_case(UNRELEASED)),
+        ChangelogSection::Index(n) => sections.get(n - 1),
+        ChangelogSection::Version(version) => sections
+            .iter()
+            .find(|section| section.version.eq_ignore_ascii_case(version)),
+    }?;
+
+    let mut output_events = vec![];
+    let (heading_start, heading_end) = (section.heading.start, section.heading.end);
+    output_events.extend(events[section.events.start..=heading_start].iter().cloned());
+    let title = events[heading_start + 1..heading_end - 1].iter().cloned();
+    if let Some(link) = link {
+        // Links cannot be nested in the link to the changelog.
+        let title = title.filter(|event| {
+            !matches!(
+                event,
+                Event::Start(Tag::Link { .. }) | Event::End(TagEnd::Link)
+            )
+        });
+        output_events.push(Event::Start(Tag::Link {
+            link_type: LinkType::Inline,
+            dest_url: CowStr::from(format!("{link}#{}", section.anchor)),
+            title: CowStr::from(""),
+            id: CowStr::from(""),
+        }));
+        output_events.extend(title);
+        output_events.push(Event::End(TagEnd::Link));
+    } else {
+        output_events.extend(title);
+    }
+    output_events.extend(strip_html_comments(
+        &events[heading_end - 1..section.events.end],
+    ));
+
+    let output_events = heading::convert(output_events, options.heading_offset());
+    // Link reference definitions are not part of the section, so write links inline.
+    let output_events = link::convert(output_events, LinkStyle::Inline);
+
+    let mut buf = String::new();
+    pulldown_cmark_to_cmark::cmark(output_events, &mut buf).unwrap();
+    if !buf.is_empty() && !buf.ends_with('\n') {
+        buf.push('\n');
+    }
+    Some(buf)
+}
+
+/// Removes HTML blocks consisting only of comments, such as `<!-- next-url -->`.
+fn strip_html_comments<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
+    let is_comment = |event: &Event<'_>| match event {
+        Event::Html(html) => {
+            let html = html.trim();
+            html.is_empty() || (html.starts_with("<!--") && html.ends_with("-->"))
+        }
+        _ => false,
+    };
+
+    let mut output = vec![];
+    let mut idx = 0;
+    while idx < events.len() {
+        if let Event::Start(Tag::HtmlBlock) = &events[idx]
+            && let Some(len) = events[idx + 1..]
+                .iter()
+                .position(|event| matches!(event, Event::End(TagEnd::HtmlBlock)))
+            && events[idx + 1..idx + 1 + len].iter().all(is_comment)
+        {
+            idx += len + 2;
+            continue;
+        }
+        output.push(events[idx].clone());
+        idx += 1;
+    }
+    output
+}
+
+/// Splits `events` into sections delimited by level 2 headings.
+fn sections(events: &[Event<'_>]) -> Vec<Section> {
+    let mut slugger = Slugger::default();
+    let mut sections: Vec<Section> = vec![];
+    let mut idx = 0;
+    while idx < events.len() {
+        let Event::Start(Tag::Heading { level, .. }) = &events[idx] else {
+            idx += 1;
+            continue;
+        };
+        let level = *level;
+        let start = idx;
+        let end = start
+            + events[start..]
+                .iter()
+                .position(|event| matches!(event, Event::End(TagEnd::Heading(_))))
+                .unwrap()
+            + 1;
+        let title = events[start..end]
+            .iter()
+            .filter_map(|event| match event {
+                Event::Text(text) | Event::Code(text) => Some(&**text),
+                _ => None,
+            })
+            .collect::<String>();
+        let anchor = slugger.slug(&title);
+
+        if level <= HeadingLevel::H2
+            && let Some(last) = sections.last_mut()
+            && last.events.end == events.len()
+        {
+            last.events.end = start;
+        }
+        if level == HeadingLevel::H2 {
+            let version = title
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .trim_matches(|c| c == '[' || c == ']')
+                .to_owned();
+            sections.push(Section {
+                version,
+                anchor,
+                heading: start..end,
+                events: start..events.len(),
+            });
+        }
+        idx = end;
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    static CHANGELOG: &str = indoc::indoc! {"
+        # Changelog
+
+        <!-- next-header -->
+
+        ## [Unreleased] - ReleaseDate
+
+        ### Added
+
+        * Add `foo`.
+
+        ## [1.1.0] - 2024-02-01
+
+        ### Fixed
+
+        * Fix [`bar`].
+
+        ## [1.0.0] - 2024-01-01
+
+        * Initial release.
+
+        <!-- next-url -->
+        [Unreleased]: https://example.com/compare/v1.1.0...HEAD
+        [1.1.0]: https://example.com/compare/v1.0.0...v1.1.0
+        [1.0.0]: https://example.com/commits/v1.0.0
+        [`bar`]: https://example.com/bar
+    "};
+
+    fn render(section: ChangelogSection, heading_offset: u8, link: Option<&str>) -> Option<String> {
+        let options = ChangelogOptions {
+            section: Some(section),
+            heading_offset: Some(heading_offset),
+            link: None,
+        };
+        render_section(CHANGELOG, &options, link)
+    }
+
+    #[test]
+    fn render_section_selects_section() {
+        assert_eq!(
+            render(ChangelogSection::Latest, 1, None).unwrap(),
+            indoc::indoc! {"
+                ### [1.1.0](https://example.com/compare/v1.0.0...v1.1.0) - 2024-02-01
+
+                #### Fixed
+
+                * Fix [`bar`](https://example.com/bar).
+            "}
+        );
+        assert_eq!(
+            render(ChangelogSection::Index(1), 0, None).unwrap(),
+            indoc::indoc! {"
+                ## [Unreleased](https://example.com/compare/v1.1.0...HEAD) - ReleaseDate
+
+                ### Added
+
+                * Add `foo`.
+            "}
+        );
+        assert_eq!(
+            render(ChangelogSection::Version("1.0.0".into()), 1, None).unwrap(),
+            indoc::indoc! {"
+                ### [1.0.0](https://example.com/commits/v1.0.0) - 2024-01-01
+
+                * Initial release.
+            "}
+        );
+        assert_eq!(render(ChangelogSection::Index(4), 1, None), None);
+        assert_eq!(
+            render(ChangelogSection::Version("2.0.0".into()), 1, None),
+            None
+        );
+    }
+
+    #[test]
+    fn render_section_links_title() {
+        assert_eq!(
+            render(ChangelogSection::Latest, 1, Some("CHANGELOG.md")).unwrap(),
+            indoc::indoc! {"
+                ### [1.1.0 - 2024-02-01](CHANGELOG.md#110---2024-02-01)
+
+                #### Fixed
+
+                * Fix [`bar`](https://example.com/bar).
+            "}
+        );
+    }
+}
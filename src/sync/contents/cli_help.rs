@@ -0,0 +1,207 @@
+use std::{
+    ffi::OsString,
+    io,
+    process::{ExitStatus, Stdio},
+    sync::{Arc, LazyLock},
+};
+
+use cargo_metadata::{Package, PackageName};
+use miette::{NamedSource, SourceSpan};
+use regex::Regex;
+use snafu::{ResultExt as _, Snafu, ensure};
+use tracing::Level;
+
+use crate::{
+    cargo, output,
+    parse::Spanned,
+    sync::{
+        MarkdownFile, SyncOptions,
+        contents::include,
+        marker::{CliHelpLang, CliHelpOptions},
+    },
+    traits::CommandExt as _,
+};
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+pub(in super::super) enum CreateCliHelpError {
+    #[snafu(display("package {package_name} does not have any binary targets"))]
+    NoBinTarget {
+        package_name: PackageName,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("package {package_name} has multiple binary targets: {}", bins.join(", ")))]
+    #[diagnostic(help("specify the binary target, e.g. `cli-help:{}`", bins[0]))]
+    AmbiguousBinTarget {
+        package_name: PackageName,
+        bins: Vec<String>,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("binary target `{bin}` not found in package {package_name}"))]
+    BinTargetNotFound {
+        bin: Arc<str>,
+        package_name: PackageName,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("failed to run binary target: {}", commandline.display()))]
+    RunBinTarget {
+        commandline: OsString,
+        #[snafu(source)]
+        source: io::Error,
+    },
+    #[snafu(display("binary target exited with status `{status}`: {}", commandline.display()))]
+    BinTargetFailed {
+        commandline: OsString,
+        status: ExitStatus,
+    },
+}
+
+pub(super) fn create(
+    markdown: &MarkdownFile<'_>,
+    package: &Package,
+    options: &SyncOptions<'_>,
+    span: SourceSpan,
+    bin: Option<&Spanned<Arc<str>>>,
+    cli_help_options: &CliHelpOptions,
+) -> Result<String, Box<CreateCliHelpError>> {
+    let bin = find_bin_target(markdown, package, span, bin)?;
+    let args = cli_help_options.args();
+
+    let mut command = cargo::command();
+    match options.verbosity {
+        Some(Level::TRACE) => _ = command.arg("-v"),
+        Some(Level::DEBUG) => {}
+        _ => _ = command.arg("-q"),
+    }
+    command
+        .args(["run", "--manifest-path", package.manifest_path.as_str()])
+        .args(["--package", &package.name, "--bin", bin])
+        .args(cargo::feature_args(options.feature))
+        .arg("--")
+        .args(&args)
+        // Ask the binary not to colorize the output.
+        .env("NO_COLOR", "1")
+        .env("CLICOLOR", "0")
+        .stdin(Stdio::null());
+    output::pipe_stderr(&mut command, options.diff_stream);
+
+    let commandline = command.commandline();
+    tracing::debug!("executing binary target: {}", commandline.display());
+    let output = command.output().context(RunBinTargetSnafu {
+        commandline: &commandline,
+    })?;
+    output::write_child_output(options.diff_stream, &output.stderr);
+    ensure!(
+        output.status.success(),
+        BinTargetFailedSnafu {
+            commandline: &commandline,
+            status: output.status,
+        }
+    );
+
+    let mut text = normalize(&String::from_utf8_lossy(&output.stdout));
+    let lang = cli_help_options.lang();
+    if lang == CliHelpLang::Console {
+        let prompt = std::iter::once(bin)
+            .chain(args)
+            .collect::<Vec<_>>()
+            .join(" ");
+        text = format!("$ {prompt}\n{text}");
+    }
+    Ok(include::code_block(&text, lang.as_str()))
+}
+
+fn find_bin_target<'a>(
+    markdown: &MarkdownFile<'_>,
+    package: &'a Package,
+    span: SourceSpan,
+    bin: Option<&'a Spanned<Arc<str>>>,
+) -> Result<&'a str, Box<CreateCliHelpError>> {
+    let mut bins = package
+        .targets
+        .iter()
+        .filter(|target| target.is_bin())
+        .map(|target| target.name.as_str());
+
+    if let Some(bin) = bin {
+        ensure!(
+            bins.any(|name| name == &*bin.value),
+            BinTargetNotFoundSnafu {
+                bin: Arc::clone(&bin.value),
+                package_name: package.name.clone(),
+                source_code: markdown.to_named_source(),
+                span: bin.source_span(),
+            }
+        );
+        return Ok(&bin.value);
+    }
+
+    let bins = bins.collect::<Vec<_>>();
+    match bins.as_slice() {
+        [bin] => Ok(bin),
+        [] => Err(NoBinTargetSnafu {
+            package_name: package.name.clone(),
+            source_code: markdown.to_named_source(),
+            span,
+        }
+        .build()
+        .into()),
+        _ => Err(AmbiguousBinTargetSnafu {
+            package_name: package.name.clone(),
+            bins: bins.iter().map(|&bin| bin.to_owned()).collect::<Vec<_>>(),
+            source_code: markdown.to_named_source(),
+            span,
+        }
+        .build()
+        .into()),
+    }
+}
+
+/// Strips ANSI escape sequences and normalizes line endings and whitespace.
+fn normalize(output: &str) -> String {
+    static ANSI_ESCAPE: LazyLock<Regex> = LazyLock::new(|| {
+        // CSI sequences (e.g. colors) and OSC sequences (e.g. hyperlinks)
+        Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)").unwrap()
+    });
+
+    let output = ANSI_ESCAPE.replace_all(output, "");
+    let mut text = String::new();
+    for line in output.lines() {
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    let len = text.trim_end().len();
+    text.truncate(len);
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn normalize_strips_ansi_escapes() {
+        assert_eq!(normalize(""), "");
+        assert_eq!(normalize("\n\n"), "");
+        assert_eq!(
+            normalize(
+                "\x1b[1m\x1b[4mUsage:\x1b[0m foo [OPTIONS]  \r\n\r\n\
+                 \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\\r\n\n"
+            ),
+            "Usage: foo [OPTIONS]\n\nlink\n"
+        );
+    }
+}
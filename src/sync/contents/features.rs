@@ -0,0 +1,147 @@
+use std::{collections::BTreeSet, fmt::Write as _};
+
+use cargo_metadata::Package;
+
+use super::Escape;
+use crate::sync::ManifestFile;
+
+const DEFAULT_FEATURE: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FeatureRow<'a> {
+    name: &'a str,
+    enables: &'a [String],
+    is_default: bool,
+    description: String,
+}
+
+pub(super) fn create(manifest: &ManifestFile, package: &Package) -> String {
+    let rows = feature_rows(manifest, package);
+    if rows.is_empty() {
+        return String::new();
+    }
+
+    let mut output = String::new();
+    output.push_str("| Feature | Default | Enables | Description |\n");
+    output.push_str("| ------- | :-----: | ------- | ----------- |\n");
+    for row in rows {
+        let enables = row
+            .enables
+            .iter()
+            .map(|feature| format!("`{feature}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            &mut output,
+            "| `{}` | {} | {} | {} |",
+            row.name,
+            if row.is_default { "✓" } else { "" },
+            enables,
+            Escape(&row.description, &['|']),
+        )
+        .unwrap();
+    }
+    output
+}
+
+/// Lists the features of the package.
+///
+/// Features declared in the manifest come first in the order of declaration,
+/// followed by features implicitly defined by optional dependencies.
+fn feature_rows<'a>(manifest: &'a ManifestFile, package: &'a Package) -> Vec<FeatureRow<'a>> {
+    let default_features = default_features(package);
+    let declared = &manifest.value().features;
+
+    let declared_names = declared.iter().map(|(name, _)| name.as_str());
+    let implicit_names = package
+        .features
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !declared.iter().any(|(declared, _)| declared == name));
+
+    declared_names
+        .chain(implicit_names)
+        .filter(|name| *name != DEFAULT_FEATURE)
+        .filter_map(|name| {
+            let enables = package.features.get(name)?;
+            let description = declared
+                .iter()
+                .find(|(declared, _)| declared == name)
+                .map(|(_, value)| doc_comment(manifest.text(), value.span().start))
+                .unwrap_or_default();
+            Some(FeatureRow {
+                name,
+                enables,
+                is_default: default_features.contains(name),
+                description,
+            })
+        })
+        .collect()
+}
+
+/// Returns the features enabled by the `default` feature, directly or transitively.
+fn default_features(package: &Package) -> BTreeSet<&str> {
+    let mut defaults = BTreeSet::new();
+    let mut stack = vec![DEFAULT_FEATURE];
+    while let Some(name) = stack.pop() {
+        let Some(enables) = package.features.get(name) else {
+            continue;
+        };
+        for feature in enables {
+            // `dep:name` enables a dependency, not a feature.
+            // `name/feature` and `name?/feature` also enable the feature `name` if it exists.
+            if feature.starts_with("dep:") {
+                continue;
+            }
+            let (name, _) = feature.split_once('/').unwrap_or((feature, ""));
+            let name = name.strip_suffix('?').unwrap_or(name);
+            if package.features.contains_key(name) && defaults.insert(name) {
+                stack.push(name);
+            }
+        }
+    }
+    defaults
+}
+
+/// Collects `##` comments on the lines preceding the line containing `offset`.
+fn doc_comment(text: &str, offset: usize) -> String {
+    let line_start = text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let mut lines = text[..line_start]
+        .lines()
+        .rev()
+        .map_while(|line| line.trim_start().strip_prefix("##"))
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    lines.reverse();
+    lines.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn doc_comment_collects_preceding_comments() {
+        let text = indoc::indoc! {r#"
+            [features]
+            ## Enables `foo`.
+            ##
+            ## Requires `std`.
+            foo = ["std"]
+            # Not a doc comment.
+            bar = []
+            ## The standard library.
+              std = []
+        "#};
+        let offset = |key: &str| text.find(&format!("{key} =")).unwrap();
+        assert_eq!(
+            doc_comment(text, offset("foo")),
+            "Enables `foo`. Requires `std`."
+        );
+        assert_eq!(doc_comment(text, offset("bar")), "");
+        assert_eq!(doc_comment(text, offset("std")), "The standard library.");
+    }
+}
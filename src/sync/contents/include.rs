@@ -0,0 +1,173 @@
+use std::{fs, io, sync::Arc};
+
+use cargo_metadata::{
+    Metadata, Package,
+    camino::{Utf8Path, Utf8PathBuf},
+};
+use miette::{NamedSource, SourceSpan};
+use snafu::{OptionExt as _, ResultExt as _, Snafu};
+
+use crate::{
+    parse::Spanned,
+    sync::{
+        MarkdownFile,
+        marker::{IncludeBase, IncludeFormat, IncludeOptions},
+    },
+    traits::PackageExt as _,
+};
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+pub(in super::super) enum CreateIncludeError {
+    #[snafu(display("failed to read included file: {path}"))]
+    ReadIncludedFile {
+        path: Utf8PathBuf,
+        #[snafu(source)]
+        source: io::Error,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("included file is outside of the workspace: {path}"))]
+    #[diagnostic(help("only files in the workspace root directory can be included"))]
+    IncludedFileOutsideWorkspace {
+        path: Utf8PathBuf,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+}
+
+pub(super) fn create(
+    markdown: &MarkdownFile<'_>,
+    workspace: &Metadata,
+    package: &Package,
+    path: &Spanned<Arc<str>>,
+    options: &IncludeOptions,
+) -> Result<String, CreateIncludeError> {
+    let base_dir = match options.relative_to() {
+        IncludeBase::File => markdown.relative_path.parent().unwrap_or(Utf8Path::new("")),
+        IncludeBase::Package => package.workspace_relative_root_directory(workspace),
+    };
+    let relative_path = base_dir.join(&*path.value);
+    let read_error = || ReadIncludedFileSnafu {
+        path: relative_path.clone(),
+        source_code: markdown.to_named_source(),
+        span: path.source_span(),
+    };
+    let full_path = workspace_file(&workspace.workspace_root, &relative_path)
+        .with_context(|_source| read_error())?
+        .with_context(|| IncludedFileOutsideWorkspaceSnafu {
+            path: relative_path.clone(),
+            source_code: markdown.to_named_source(),
+            span: path.source_span(),
+        })?;
+    let text = fs::read_to_string(full_path).with_context(|_source| read_error())?;
+
+    let text = match options.format() {
+        IncludeFormat::Markdown | IncludeFormat::Native => text,
+        IncludeFormat::Code => {
+            let lang = options
+                .lang
+                .as_deref()
+                .or_else(|| relative_path.extension().map(lang_from_extension))
+                .unwrap_or_default();
+            code_block(&text, lang)
+        }
+    };
+
+    if text.is_empty() || text.ends_with('\n') {
+        Ok(text)
+    } else {
+        Ok(text + "\n")
+    }
+}
+
+/// Returns the canonical path of `relative_path` in the workspace.
+///
+/// Returns `None` if the path points outside of the workspace root directory,
+/// e.g. through `..` components, an absolute path, or a symbolic link.
+pub(super) fn workspace_file(
+    workspace_root: &Utf8Path,
+    relative_path: &Utf8Path,
+) -> io::Result<Option<Utf8PathBuf>> {
+    let root = workspace_root.canonicalize_utf8()?;
+    let path = workspace_root.join(relative_path).canonicalize_utf8()?;
+    Ok(path.starts_with(&root).then_some(path))
+}
+
+pub(super) fn lang_from_extension(ext: &str) -> &str {
+    match ext {
+        "rs" => "rust",
+        "py" => "python",
+        "rb" => "ruby",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" => "typescript",
+        "yml" => "yaml",
+        "md" => "markdown",
+        "ps1" => "powershell",
+        _ => ext,
+    }
+}
+
+/// Wraps `text` in a fenced code block.
+///
+/// The fence is made longer than any backtick sequence in `text`.
+pub(super) fn code_block(text: &str, lang: &str) -> String {
+    let longest_backticks = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(usize::max(3, longest_backticks + 1));
+    let newline = if text.is_empty() || text.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    format!("{fence}{lang}\n{text}{newline}{fence}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn workspace_file_rejects_paths_outside_of_workspace() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = Utf8PathBuf::try_from(tempdir.path().to_owned()).unwrap();
+        let root = dir.join("workspace");
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/intro.md"), "").unwrap();
+        fs::write(dir.join("secret.txt"), "").unwrap();
+
+        let path = workspace_file(&root, "docs/../docs/intro.md".into()).unwrap();
+        assert_eq!(
+            path,
+            Some(root.canonicalize_utf8().unwrap().join("docs/intro.md"))
+        );
+        assert_eq!(workspace_file(&root, "../secret.txt".into()).unwrap(), None);
+        assert_eq!(
+            workspace_file(&root, &dir.join("secret.txt")).unwrap(),
+            None
+        );
+        workspace_file(&root, "missing.md".into()).unwrap_err();
+    }
+
+    #[test]
+    fn code_block_uses_long_enough_fence() {
+        assert_eq!(code_block("", ""), "```\n```\n");
+        assert_eq!(
+            code_block("[package]\n", "toml"),
+            "```toml\n[package]\n```\n"
+        );
+        assert_eq!(code_block("echo foo", "sh"), "```sh\necho foo\n```\n");
+        assert_eq!(
+            code_block("````\nfoo\n````\n", "markdown"),
+            "`````markdown\n````\nfoo\n````\n`````\n"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::Write as _;
+
+use cargo_metadata::{Package, Target};
+
+use crate::{
+    config::metadata::Install,
+    sync::{ManifestFile, contents::include, marker::InstallStyle},
+};
+
+pub(super) fn create(
+    manifest: &ManifestFile,
+    package: &Package,
+    style: Option<InstallStyle>,
+) -> String {
+    let config = &manifest.value().config().install;
+    let style = style.unwrap_or_else(|| default_style(package));
+    render(style, &package.name, &package.version.to_string(), config)
+}
+
+fn render(style: InstallStyle, name: &str, version: &str, config: &Install) -> String {
+    match style {
+        InstallStyle::Toml => {
+            let mut text = String::from("[dependencies]\n");
+            if config.default_features && config.features.is_empty() {
+                writeln!(&mut text, "{name} = \"{version}\"").unwrap();
+            } else {
+                write!(&mut text, "{name} = {{ version = \"{version}\"").unwrap();
+                if !config.default_features {
+                    text.push_str(", default-features = false");
+                }
+                if !config.features.is_empty() {
+                    let features = config
+                        .features
+                        .iter()
+                        .map(|feature| format!("\"{feature}\""))
+                        .collect::<Vec<_>>();
+                    write!(&mut text, ", features = [{}]", features.join(", ")).unwrap();
+                }
+                text.push_str(" }\n");
+            }
+            include::code_block(&text, "toml")
+        }
+        InstallStyle::Add | InstallStyle::Install => {
+            let subcommand = if style == InstallStyle::Add {
+                "add"
+            } else {
+                "install"
+            };
+            let mut text = format!("cargo {subcommand} {name}");
+            if !config.default_features {
+                text.push_str(" --no-default-features");
+            }
+            if !config.features.is_empty() {
+                write!(&mut text, " --features {}", config.features.join(",")).unwrap();
+            }
+            text.push('\n');
+            include::code_block(&text, "console")
+        }
+        InstallStyle::Binstall => {
+            include::code_block(&format!("cargo binstall {name}\n"), "console")
+        }
+    }
+}
+
+/// Returns `cargo install` for packages that only have binary targets, and a
+/// `[dependencies]` table otherwise.
+fn default_style(package: &Package) -> InstallStyle {
+    let has_lib = package
+        .targets
+        .iter()
+        .any(|target| target.is_lib() || target.is_rlib() || target.is_proc_macro());
+    let has_bin = package.targets.iter().any(Target::is_bin);
+    if !has_lib && has_bin {
+        InstallStyle::Install
+    } else {
+        InstallStyle::Toml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn render_snippets() {
+        let config = Install::default();
+        let render = |style| render(style, "foo", "1.2.3", &config);
+        assert_eq!(
+            render(InstallStyle::Toml),
+            "```toml\n[dependencies]\nfoo = \"1.2.3\"\n```\n"
+        );
+        assert_eq!(
+            render(InstallStyle::Add),
+            "```console\ncargo add foo\n```\n"
+        );
+        assert_eq!(
+            render(InstallStyle::Install),
+            "```console\ncargo install foo\n```\n"
+        );
+        assert_eq!(
+            render(InstallStyle::Binstall),
+            "```console\ncargo binstall foo\n```\n"
+        );
+    }
+
+    #[test]
+    fn render_snippets_with_features() {
+        let config = Install {
+            default_features: false,
+            features: vec!["std".to_owned(), "serde".to_owned()],
+        };
+        let render = |style| render(style, "foo", "1.2.3", &config);
+        assert_eq!(
+            render(InstallStyle::Toml),
+            "```toml\n[dependencies]\nfoo = { version = \"1.2.3\", default-features = false, features = [\"std\", \"serde\"] }\n```\n"
+        );
+        assert_eq!(
+            render(InstallStyle::Add),
+            "```console\ncargo add foo --no-default-features --features std,serde\n```\n"
+        );
+    }
+}
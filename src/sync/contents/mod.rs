@@ -1,18 +1,38 @@
-use std::fmt;
+use std::{collections::HashMap, fmt, sync::Arc};
 
-use cargo_metadata::{Metadata, Package};
-use snafu::{Snafu, ensure};
+use cargo_metadata::{
+    Metadata, Package,
+    camino::{Utf8Path, Utf8PathBuf},
+};
+use miette::{NamedSource, SourceSpan};
+use snafu::{OptionExt as _, Snafu, ensure};
 
-use crate::{parse::Spanned, sync::SyncOptions};
+use crate::{config::metadata::TargetFormat, parse::Spanned, sync::SyncOptions};
 
-use super::{ManifestFile, marker::ResolvedReplaceSpecifier};
+use super::{
+    ManifestFile, MarkdownFile,
+    marker::{IncludeFormat, Markers, ResolvedReplaceSpecifier, RustdocOptions},
+};
 
 mod badge;
+mod changelog;
+mod cli_help;
+mod features;
+mod include;
+mod install;
+mod render;
 mod rustdoc;
+mod snippet;
+mod template;
 mod title;
+mod toc;
+mod workspace_members;
+
+pub(crate) use self::rustdoc::RustdocCache;
 
 pub(super) fn create_all(
-    specifiers: Vec<Spanned<ResolvedReplaceSpecifier>>,
+    markers: Markers,
+    markdown: &MarkdownFile<'_>,
     manifest: &ManifestFile,
     workspace: &Metadata,
     package: &Package,
@@ -20,8 +40,16 @@ pub(super) fn create_all(
 ) -> Result<Vec<Contents>, CreateAllContentsError> {
     let mut contents = vec![];
     let mut errors = vec![];
-    for specifier in specifiers {
-        let res = create_content(specifier, manifest, workspace, package, options);
+    for specifier in markers.specifiers {
+        let res = create_content(
+            specifier,
+            markdown,
+            manifest,
+            &markers.package_manifests,
+            workspace,
+            package,
+            options,
+        );
         match res {
             Ok(c) => contents.push(c),
             Err(err) => errors.push(err),
@@ -33,6 +61,42 @@ pub(super) fn create_all(
     Ok(contents)
 }
 
+/// Creates the contents of `toc` markers from the synced text.
+///
+/// Returns `true` if `contents` has any `toc` marker.
+/// A table of contents lists headings generated by other markers, so it must be
+/// created after the other contents are replaced.
+/// In the other formats than Markdown, tables of contents are created by the
+/// directives of the formats instead.
+pub(super) fn create_toc(contents: &mut [Contents], text: &str, format: TargetFormat) -> bool {
+    if format != TargetFormat::Markdown {
+        return false;
+    }
+    let mut found = false;
+    for contents in contents {
+        if let ResolvedReplaceSpecifier::Toc { options } = &contents.specifier.value {
+            contents.text = toc::create(text, options);
+            found = true;
+        }
+    }
+    found
+}
+
+/// Creates crate-level documentation from the contents of a `rustdoc` marker region.
+pub(super) fn create_crate_docs(
+    text: &str,
+    manifest: &ManifestFile,
+    workspace: &Metadata,
+    package: &Package,
+    options: &SyncOptions<'_>,
+    rustdoc_options: &RustdocOptions,
+) -> Result<String, CreateAllContentsError> {
+    rustdoc::create_crate_docs(text, manifest, workspace, package, options, rustdoc_options)
+        .map_err(|err| CreateAllContentsError {
+            errors: vec![CreateContentsError::from(Box::new(err))],
+        })
+}
+
 #[derive(Debug, Snafu, miette::Diagnostic)]
 #[snafu(display("failed to create replacement contents"))]
 pub(crate) struct CreateAllContentsError {
@@ -42,6 +106,14 @@ pub(crate) struct CreateAllContentsError {
 
 #[derive(Debug, Snafu, miette::Diagnostic)]
 pub(super) enum CreateContentsError {
+    #[snafu(display("package `{package}` is not a member of the workspace"))]
+    PackageNotFound {
+        package: String,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
     #[snafu(transparent)]
     #[diagnostic(transparent)]
     CreateBadge {
@@ -54,7 +126,49 @@ pub(super) enum CreateContentsError {
     CreateRustdoc {
         #[snafu(source)]
         #[diagnostic_source]
-        source: rustdoc::CreateRustdocError,
+        source: Box<rustdoc::CreateRustdocError>,
+    },
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    CreateInclude {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: Box<include::CreateIncludeError>,
+    },
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    CreateChangelog {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: Box<changelog::CreateChangelogError>,
+    },
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    CreateWorkspaceMembers {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: Box<workspace_members::CreateWorkspaceMembersError>,
+    },
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    CreateCliHelp {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: Box<cli_help::CreateCliHelpError>,
+    },
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    CreateTemplate {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: Box<template::CreateTemplateError>,
+    },
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    CreateSnippet {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: Box<snippet::CreateSnippetError>,
     },
 }
 
@@ -66,24 +180,124 @@ pub(super) struct Contents {
 
 fn create_content(
     specifier: Spanned<ResolvedReplaceSpecifier>,
+    markdown: &MarkdownFile<'_>,
     manifest: &ManifestFile,
+    package_manifests: &HashMap<String, ManifestFile>,
     workspace: &Metadata,
     package: &Package,
     options: &SyncOptions<'_>,
 ) -> Result<Contents, CreateContentsError> {
+    // Markers qualified with `@<package>` are created for the named workspace member,
+    // whose manifest is read by the marker resolver.
+    let (manifest, package) = match specifier.value.package() {
+        Some(name) => {
+            let member = workspace
+                .workspace_packages()
+                .into_iter()
+                .find(|member| member.name.as_str() == &*name.value);
+            let member_manifest = package_manifests.get(&*name.value);
+            member_manifest
+                .zip(member)
+                .with_context(|| PackageNotFoundSnafu {
+                    package: &*name.value,
+                    source_code: markdown.to_named_source(),
+                    span: name.source_span(),
+                })?
+        }
+        None => (manifest, package),
+    };
+
     let text = match &specifier.value {
-        ResolvedReplaceSpecifier::Title => title::create(package),
-        ResolvedReplaceSpecifier::Badge { group: _, badges } => {
-            badge::create_all(badges, manifest, workspace, package)?
+        ResolvedReplaceSpecifier::Title { package: _ } => title::create(package),
+        ResolvedReplaceSpecifier::Features => features::create(manifest, package),
+        ResolvedReplaceSpecifier::Install { style } => install::create(manifest, package, *style),
+        ResolvedReplaceSpecifier::Badge {
+            package: _,
+            group: _,
+            badges,
+        } => badge::create_all(badges, manifest, workspace, package)?,
+        ResolvedReplaceSpecifier::Rustdoc {
+            package: _,
+            item,
+            options: rustdoc_options,
+        } => rustdoc::create(
+            markdown,
+            manifest,
+            workspace,
+            package,
+            options,
+            item.as_ref(),
+            rustdoc_options,
+        )
+        .map_err(Box::new)?,
+        ResolvedReplaceSpecifier::ApiSummary {
+            options: summary_options,
+        } => rustdoc::create_api_summary(manifest, workspace, package, options, summary_options)
+            .map_err(Box::new)?,
+        ResolvedReplaceSpecifier::Include {
+            path,
+            options: include_options,
+        } => include::create(markdown, workspace, package, path, include_options)
+            .map_err(Box::new)?,
+        ResolvedReplaceSpecifier::Snippet { path, anchor, lang } => {
+            snippet::create(markdown, workspace, package, path, anchor, lang.as_deref())
+                .map_err(Box::new)?
+        }
+        ResolvedReplaceSpecifier::Changelog {
+            options: changelog_options,
+        } => changelog::create(
+            markdown,
+            manifest,
+            workspace,
+            package,
+            specifier.source_span(),
+            changelog_options,
+        )?,
+        ResolvedReplaceSpecifier::WorkspaceMembers {
+            options: workspace_members_options,
+        } => workspace_members::create(markdown, workspace, workspace_members_options)
+            .map_err(Box::new)?,
+        ResolvedReplaceSpecifier::Template { name } => {
+            template::create(manifest, package, name).map_err(Box::new)?
+        }
+        ResolvedReplaceSpecifier::CliHelp {
+            bin,
+            options: cli_help_options,
+        } => cli_help::create(
+            markdown,
+            package,
+            options,
+            specifier.source_span(),
+            bin.as_ref(),
+            cli_help_options,
+        )?,
+        ResolvedReplaceSpecifier::Toc { options } => {
+            // Created by `create_toc` after the other contents are replaced.
+            let text = toc::create_directive(markdown.format, options);
+            return Ok(Contents { specifier, text });
         }
-        ResolvedReplaceSpecifier::Rustdoc => rustdoc::create(manifest, package, options)?,
     };
 
     assert!(text.is_empty() || text.ends_with('\n'));
+    let text = if is_native(&specifier.value) {
+        text
+    } else {
+        render::render(text, markdown.format)
+    };
 
     Ok(Contents { specifier, text })
 }
 
+/// Returns `true` if the contents are written in the format of the target file
+/// instead of being generated as Markdown.
+fn is_native(specifier: &ResolvedReplaceSpecifier) -> bool {
+    matches!(
+        specifier,
+        ResolvedReplaceSpecifier::Include { options, .. }
+            if options.format() == IncludeFormat::Native
+    )
+}
+
 impl Contents {
     pub(super) fn specifier(&self) -> &Spanned<ResolvedReplaceSpecifier> {
         &self.specifier
@@ -94,6 +308,31 @@ impl Contents {
     }
 }
 
+/// Returns a relative link from the directory `from` to the path `to`.
+///
+/// Both paths must be relative to the workspace root.
+fn relative_link(from: &Utf8Path, to: &Utf8Path) -> Option<String> {
+    if from.is_absolute() || to.is_absolute() {
+        return None;
+    }
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut link = Utf8PathBuf::new();
+    for _ in from.components().skip(common) {
+        link.push("..");
+    }
+    for component in to.components().skip(common) {
+        link.push(component);
+    }
+    if link.as_str().is_empty() {
+        return Some(".".to_owned());
+    }
+    Some(link.as_str().replace('\\', "/"))
+}
+
 struct Escape<'s>(&'s str, &'s [char]);
 
 impl fmt::Display for Escape<'_> {
@@ -135,4 +374,15 @@ mod tests {
         );
         assert_eq!(Escape(r"*", &need_escape).to_string(), r"\*");
     }
+
+    #[test]
+    fn relative_link_between_directories() {
+        let link = |from: &str, to: &str| relative_link(Utf8Path::new(from), Utf8Path::new(to));
+        assert_eq!(link("", "").as_deref(), Some("."));
+        assert_eq!(link("", "crates/foo").as_deref(), Some("crates/foo"));
+        assert_eq!(link("docs", "").as_deref(), Some(".."));
+        assert_eq!(link("docs", "crates/foo").as_deref(), Some("../crates/foo"));
+        assert_eq!(link("crates/bar", "crates/foo").as_deref(), Some("../foo"));
+        assert_eq!(link("crates/foo", "crates/foo").as_deref(), Some("."));
+    }
 }
@@ -0,0 +1,426 @@
+use std::{collections::HashMap, fmt::Write as _};
+
+use pulldown_cmark::{CodeBlockKind, Event, LinkType, Tag};
+
+use super::{Block, Node};
+
+/// Renders the Markdown syntax tree as Asciidoc.
+pub(super) fn render(nodes: &[Node<'_>]) -> String {
+    let mut renderer = Renderer::default();
+    // Footnotes are written inline in AsciiDoc, so the definitions are needed
+    // before the references.
+    for node in nodes {
+        if let Node::Element(Tag::FootnoteDefinition(label), children) = node {
+            let text = renderer.blocks(children, 0).replace('\n', " ");
+            renderer.footnotes.insert(label.to_string(), text);
+        }
+    }
+    let text = renderer.blocks(nodes, 0);
+    if text.is_empty() { text } else { text + "\n" }
+}
+
+#[derive(Debug, Default)]
+struct Renderer {
+    footnotes: HashMap<String, String>,
+}
+
+impl Renderer {
+    /// Renders the blocks separated by blank lines.
+    ///
+    /// `depth` is the nesting level of lists.
+    fn blocks(&self, nodes: &[Node<'_>], depth: usize) -> String {
+        super::blocks(nodes)
+            .into_iter()
+            .filter_map(|block| self.block(block, depth))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn block(&self, block: Block<'_, '_>, depth: usize) -> Option<String> {
+        let node = match block {
+            Block::Inline(nodes) => return Some(self.inline(nodes)),
+            Block::Block(node) => node,
+        };
+        let (tag, children) = match node {
+            Node::Element(tag, children) => (tag, children),
+            Node::Leaf(Event::Rule) => return Some("'''".to_owned()),
+            Node::Leaf(_) => return None,
+        };
+        let text = match tag {
+            Tag::Paragraph => self.inline(children),
+            Tag::Heading { level, .. } => {
+                let text = self.inline(children).replace('\n', " ");
+                format!("{} {text}", "=".repeat(*level as usize))
+            }
+            Tag::BlockQuote(_) => format!("____\n{}\n____", self.blocks(children, depth)),
+            Tag::CodeBlock(kind) => {
+                let code = code_text(children);
+                let delimiter = "-".repeat(usize::max(4, super::longest_line_of(&code, '-') + 1));
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split([',', ' ', '\t'])
+                        .next()
+                        .filter(|lang| !lang.is_empty()),
+                    CodeBlockKind::Indented => None,
+                };
+                let attributes = match lang {
+                    Some(lang) => format!("[source,{lang}]\n"),
+                    None => String::new(),
+                };
+                format!("{attributes}{delimiter}\n{code}{delimiter}")
+            }
+            Tag::HtmlBlock => format!("++++\n{}++++", code_text(children)),
+            Tag::List(start) => {
+                let marker = match start {
+                    Some(_) => ".",
+                    None => "*",
+                }
+                .repeat(depth + 1);
+                let items = children
+                    .iter()
+                    .map(|item| match item {
+                        Node::Element(Tag::Item, children) => self.item(children, &marker, depth),
+                        node => self.blocks(std::slice::from_ref(node), depth),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                match start {
+                    Some(start) if *start != 1 => format!("[start={start}]\n{items}"),
+                    _ => items,
+                }
+            }
+            Tag::Table(_) => self.table(children),
+            Tag::FootnoteDefinition(_) => return None,
+            _ => self.blocks(children, depth),
+        };
+        Some(text)
+    }
+
+    fn item(&self, children: &[Node<'_>], marker: &str, depth: usize) -> String {
+        let mut text = format!("{marker} ");
+        let blocks = super::blocks(children);
+        if blocks.is_empty() {
+            text.push_str("{empty}");
+        }
+        for (idx, block) in blocks.into_iter().enumerate() {
+            let is_list = matches!(block, Block::Block(Node::Element(Tag::List(_), _)));
+            let Some(block) = self.block(block, depth + 1) else {
+                continue;
+            };
+            match idx {
+                0 => {}
+                // Nested lists are attached to the item by their markers.
+                _ if is_list => text.push('\n'),
+                _ => text.push_str("\n+\n"),
+            }
+            text.push_str(&block);
+        }
+        text
+    }
+
+    fn table(&self, children: &[Node<'_>]) -> String {
+        let mut text = "[%header]\n|===\n".to_owned();
+        for (idx, row) in children.iter().enumerate() {
+            let Node::Element(Tag::TableHead | Tag::TableRow, cells) = row else {
+                continue;
+            };
+            if idx == 1 {
+                text.push('\n');
+            }
+            let cells = cells
+                .iter()
+                .filter_map(|cell| match cell {
+                    Node::Element(Tag::TableCell, children) => {
+                        let cell = self.inline(children).replace('|', r"\|");
+                        Some(format!("| {cell}").trim_end().to_owned())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            text.push_str(&cells.join(" "));
+            text.push('\n');
+        }
+        text.push_str("|===");
+        text
+    }
+
+    fn inline(&self, nodes: &[Node<'_>]) -> String {
+        let mut text = String::new();
+        for node in nodes {
+            match node {
+                Node::Leaf(event) => match event {
+                    Event::Text(s) => text.push_str(&escape(s)),
+                    Event::Code(s) => write!(text, "`+{s}+`").unwrap(),
+                    Event::InlineHtml(s) | Event::Html(s) => write!(text, "+++{s}+++").unwrap(),
+                    Event::SoftBreak => text.push('\n'),
+                    Event::HardBreak => text.push_str(" +\n"),
+                    Event::TaskListMarker(checked) => {
+                        text.push_str(if *checked { "[x] " } else { "[ ] " });
+                    }
+                    Event::FootnoteReference(label) => match self.footnotes.get(label.as_ref()) {
+                        Some(footnote) => {
+                            write!(text, "footnote:[{}]", footnote.replace(']', r"\]")).unwrap();
+                        }
+                        None => write!(text, "footnote:{label}[]").unwrap(),
+                    },
+                    _ => {}
+                },
+                Node::Element(tag, children) => match tag {
+                    Tag::Emphasis => write!(text, "__{}__", self.inline(children)).unwrap(),
+                    Tag::Strong => write!(text, "**{}**", self.inline(children)).unwrap(),
+                    Tag::Strikethrough => {
+                        write!(text, "[.line-through]#{}#", self.inline(children)).unwrap();
+                    }
+                    Tag::Link {
+                        link_type: LinkType::Autolink | LinkType::Email,
+                        ..
+                    } => text.push_str(&self.inline(children)),
+                    Tag::Link { dest_url, .. } => {
+                        if let [
+                            Node::Element(
+                                Tag::Image {
+                                    dest_url: image, ..
+                                },
+                                alt,
+                            ),
+                        ] = children.as_slice()
+                        {
+                            text.push_str(&image_macro(image, &super::plain_text(alt), dest_url));
+                        } else {
+                            let label = attribute_value(&self.inline(children));
+                            let dest_url = dest_url.replace(' ', "%20");
+                            write!(text, "link:{dest_url}[{label}]").unwrap();
+                        }
+                    }
+                    Tag::Image { dest_url, .. } => {
+                        text.push_str(&image_macro(dest_url, &super::plain_text(children), ""));
+                    }
+                    _ => text.push_str(&self.inline(children)),
+                },
+            }
+        }
+        text
+    }
+}
+
+/// Escapes the characters of `text` that would be interpreted as Asciidoc markup.
+///
+/// Asciidoctor keeps a backslash in front of a character that does not complete
+/// any markup, so the characters are replaced with attribute and character
+/// references instead, which are substituted after inline formatting.
+fn escape(text: &str) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let is_word_char = |idx: Option<usize>| {
+        idx.and_then(|idx| chars.get(idx))
+            .is_some_and(|c| c.is_alphanumeric())
+    };
+    let mut escaped = String::new();
+    for (idx, &c) in chars.iter().enumerate() {
+        // Constrained formatting marks are not recognized inside words.
+        let in_word = is_word_char(idx.checked_sub(1)) && is_word_char(Some(idx + 1));
+        let reference = match c {
+            '*' | '_' | '#' | '`' if in_word => None,
+            '*' => Some("{asterisk}"),
+            '`' => Some("{backtick}"),
+            '^' => Some("{caret}"),
+            '~' => Some("{tilde}"),
+            '+' => Some("{plus}"),
+            '\\' => Some("{backslash}"),
+            '_' => Some("&#95;"),
+            '#' => Some("&#35;"),
+            '{' => Some("&#123;"),
+            _ => None,
+        };
+        match reference {
+            Some(reference) => escaped.push_str(reference),
+            None => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn code_text(nodes: &[Node<'_>]) -> String {
+    let mut code = String::new();
+    for node in nodes {
+        if let Node::Leaf(Event::Text(s) | Event::Html(s)) = node {
+            code.push_str(s);
+        }
+    }
+    if !code.is_empty() && !code.ends_with('\n') {
+        code.push('\n');
+    }
+    code
+}
+
+fn image_macro(image: &str, alt: &str, link: &str) -> String {
+    let alt = attribute_value(alt);
+    let image = image.replace(' ', "%20");
+    if link.is_empty() {
+        format!("image:{image}[{alt}]")
+    } else {
+        format!("image:{image}[{alt},link=\"{link}\"]")
+    }
+}
+
+/// Quotes the positional attribute of a macro if necessary.
+fn attribute_value(value: &str) -> String {
+    if value.contains([',', '=', '"']) {
+        format!("\"{}\"", value.replace('"', r#"\""#))
+    } else {
+        value.replace(']', r"\]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    fn render_str(markdown: &str) -> String {
+        render(&super::super::parse(markdown))
+    }
+
+    #[test]
+    fn render_blocks() {
+        let markdown = indoc! {"
+            # Title
+
+            Some *emphasis*, **strong**, ~~deleted~~ and `code`.
+            Next line.
+
+            ## Section
+
+            > Quoted
+            > text.
+
+            ```rust,ignore
+            fn main() {}
+            ```
+
+            ---
+
+            <div>HTML</div>
+        "};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {"
+                = Title
+
+                Some __emphasis__, **strong**, [.line-through]#deleted# and `+code+`.
+                Next line.
+
+                == Section
+
+                ____
+                Quoted
+                text.
+                ____
+
+                [source,rust]
+                ----
+                fn main() {}
+                ----
+
+                '''
+
+                ++++
+                <div>HTML</div>
+                ++++
+            "}
+        );
+    }
+
+    #[test]
+    fn render_escapes_text() {
+        let markdown = indoc! {r"
+            # The {version} of snake_case
+
+            2 \* 3 + 4 = *ten*, C# and `a*b`.
+            \_not emphasis\_ ~ ^sup^ \\
+        "};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {r"
+                = The &#123;version} of snake_case
+
+                2 {asterisk} 3 {plus} 4 = __ten__, C&#35; and `+a*b+`.
+                &#95;not emphasis&#95; {tilde} {caret}sup{caret} {backslash}
+            "}
+        );
+    }
+
+    #[test]
+    fn render_lists() {
+        let markdown = indoc! {"
+            * foo
+              * bar
+            * baz
+
+              more
+
+            3. one
+            4. two
+        "};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {"
+                * foo
+                ** bar
+                * baz
+                +
+                more
+
+                [start=3]
+                . one
+                . two
+            "}
+        );
+    }
+
+    #[test]
+    fn render_links_and_images() {
+        let markdown = indoc! {r#"
+            [![License: MIT](https://img.shields.io/badge/license-MIT-blue.svg)](#license)
+            ![Image](https://example.com/a.png)
+            [`Foo`](https://docs.rs/foo "struct foo::Foo") and <https://example.com>.
+            [a, b](https://example.com/a,b)
+        "#};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {r##"
+                image:https://img.shields.io/badge/license-MIT-blue.svg[License: MIT,link="#license"]
+                image:https://example.com/a.png[Image]
+                link:https://docs.rs/foo[`+Foo+`] and https://example.com.
+                link:https://example.com/a,b["a, b"]
+            "##}
+        );
+    }
+
+    #[test]
+    fn render_table_and_footnote() {
+        let markdown = indoc! {"
+            | Name | Value |
+            |------|-------|
+            | a    | 1 \\| 2 |
+
+            Text[^note].
+
+            [^note]: The note.
+        "};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {r"
+                [%header]
+                |===
+                | Name | Value
+
+                | a | 1 \| 2
+                |===
+
+                Textfootnote:[The note.].
+            "}
+        );
+    }
+}
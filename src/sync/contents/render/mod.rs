@@ -0,0 +1,130 @@
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+use crate::config::metadata::TargetFormat;
+
+mod asciidoc;
+mod rst;
+
+/// Converts the Markdown contents into the target format.
+pub(super) fn render(markdown: String, format: TargetFormat) -> String {
+    match format {
+        TargetFormat::Markdown => markdown,
+        TargetFormat::Asciidoc => asciidoc::render(&parse(&markdown)),
+        TargetFormat::Rst => rst::render(&parse(&markdown)),
+    }
+}
+
+/// Markdown syntax tree built from pulldown-cmark events.
+#[derive(Debug)]
+enum Node<'a> {
+    /// Contents between `Event::Start` and `Event::End`.
+    Element(Tag<'a>, Vec<Node<'a>>),
+    /// Events other than `Event::Start` and `Event::End`.
+    Leaf(Event<'a>),
+}
+
+fn parse(markdown: &str) -> Vec<Node<'_>> {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+    let mut stack = vec![(None, vec![])];
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(tag) => stack.push((Some(tag), vec![])),
+            Event::End(_) => {
+                let (tag, children) = stack.pop().unwrap();
+                let node = Node::Element(tag.unwrap(), children);
+                stack.last_mut().unwrap().1.push(node);
+            }
+            event => stack.last_mut().unwrap().1.push(Node::Leaf(event)),
+        }
+    }
+    let (_tag, nodes) = stack.pop().unwrap();
+    nodes
+}
+
+/// Block-level contents of a container.
+#[derive(Debug, Clone, Copy)]
+enum Block<'n, 'a> {
+    /// Consecutive inline nodes, such as the contents of a tight list item.
+    Inline(&'n [Node<'a>]),
+    Block(&'n Node<'a>),
+}
+
+fn blocks<'n, 'a>(nodes: &'n [Node<'a>]) -> Vec<Block<'n, 'a>> {
+    let mut blocks = vec![];
+    let mut inline_start = 0;
+    for (idx, node) in nodes.iter().enumerate() {
+        if !is_block(node) {
+            continue;
+        }
+        if inline_start < idx {
+            blocks.push(Block::Inline(&nodes[inline_start..idx]));
+        }
+        blocks.push(Block::Block(node));
+        inline_start = idx + 1;
+    }
+    if inline_start < nodes.len() {
+        blocks.push(Block::Inline(&nodes[inline_start..]));
+    }
+    blocks
+}
+
+fn is_block(node: &Node<'_>) -> bool {
+    match node {
+        Node::Element(tag, _) => matches!(
+            tag,
+            Tag::Paragraph
+                | Tag::Heading { .. }
+                | Tag::BlockQuote(_)
+                | Tag::CodeBlock(_)
+                | Tag::HtmlBlock
+                | Tag::List(_)
+                | Tag::Item
+                | Tag::FootnoteDefinition(_)
+                | Tag::Table(_)
+                | Tag::TableHead
+                | Tag::TableRow
+                | Tag::TableCell
+        ),
+        Node::Leaf(event) => matches!(event, Event::Rule),
+    }
+}
+
+/// Returns the plain text of the inline nodes.
+fn plain_text(nodes: &[Node<'_>]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Node::Element(_tag, children) => text.push_str(&plain_text(children)),
+            Node::Leaf(Event::Text(s) | Event::Code(s)) => text.push_str(s),
+            Node::Leaf(Event::SoftBreak | Event::HardBreak) => text.push(' '),
+            Node::Leaf(_) => {}
+        }
+    }
+    text
+}
+
+/// Returns the longest line of `text` consisting of only `c`.
+fn longest_line_of(text: &str, c: char) -> usize {
+    text.lines()
+        .filter(|line| !line.is_empty() && line.chars().all(|ch| ch == c))
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Indents the non-empty lines of `text`.
+fn indent(text: &str, first: &str, rest: &str) -> String {
+    let mut output = String::new();
+    for (idx, line) in text.split_inclusive('\n').enumerate() {
+        if line.trim().is_empty() {
+            output.push_str(line.trim_start_matches(' '));
+            continue;
+        }
+        output.push_str(if idx == 0 { first } else { rest });
+        output.push_str(line);
+    }
+    output
+}
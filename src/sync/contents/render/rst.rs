@@ -0,0 +1,459 @@
+use std::fmt::Write as _;
+
+use pulldown_cmark::{CodeBlockKind, Event, LinkType, Tag};
+
+use super::{Block, Node};
+
+/// Characters used to underline headings of levels 1 to 6.
+const HEADING_UNDERLINES: [char; 6] = ['=', '-', '~', '^', '"', '\''];
+
+/// Renders the Markdown syntax tree as reStructuredText.
+pub(super) fn render(nodes: &[Node<'_>]) -> String {
+    let mut renderer = Renderer::default();
+    let text = renderer.blocks(nodes, true);
+    if text.is_empty() { text } else { text + "\n" }
+}
+
+#[derive(Debug, Default)]
+struct Renderer {
+    /// Names and definitions of the substitutions used for images.
+    substitutions: Vec<(String, String)>,
+    /// Substitution definitions to be written after the current top-level block.
+    pending: Vec<String>,
+}
+
+impl Renderer {
+    /// Renders the blocks separated by blank lines.
+    ///
+    /// Substitution definitions are written after each block if `top_level`
+    /// is `true`, because they cannot be written inside other blocks.
+    fn blocks(&mut self, nodes: &[Node<'_>], top_level: bool) -> String {
+        let mut blocks = vec![];
+        for block in super::blocks(nodes) {
+            blocks.extend(self.block(block));
+            if top_level && !self.pending.is_empty() {
+                blocks.push(self.pending.join("\n"));
+                self.pending.clear();
+            }
+        }
+        blocks.join("\n\n")
+    }
+
+    fn block(&mut self, block: Block<'_, '_>) -> Option<String> {
+        let node = match block {
+            Block::Inline(nodes) => return Some(self.inline(nodes, false)),
+            Block::Block(node) => node,
+        };
+        let (tag, children) = match node {
+            Node::Element(tag, children) => (tag, children),
+            Node::Leaf(Event::Rule) => return Some("----".to_owned()),
+            Node::Leaf(_) => return None,
+        };
+        let text = match tag {
+            Tag::Paragraph => self.inline(children, false),
+            Tag::Heading { level, .. } => {
+                let text = self.inline(children, false).replace('\n', " ");
+                let width = text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum();
+                let underline = HEADING_UNDERLINES[*level as usize - 1];
+                format!("{text}\n{}", underline.to_string().repeat(width))
+            }
+            Tag::BlockQuote(_) => {
+                // An empty comment prevents the block quote from being a part of
+                // the preceding block.
+                let text = self.blocks(children, false);
+                format!("..\n\n{}", super::indent(&text, "   ", "   "))
+            }
+            Tag::CodeBlock(kind) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split([',', ' ', '\t'])
+                        .next()
+                        .filter(|lang| !lang.is_empty()),
+                    CodeBlockKind::Indented => None,
+                };
+                let directive = match lang {
+                    Some(lang) => format!(".. code-block:: {lang}"),
+                    None => "::".to_owned(),
+                };
+                directive_with_contents(&directive, &code_text(children))
+            }
+            Tag::HtmlBlock => directive_with_contents(".. raw:: html", &code_text(children)),
+            Tag::List(start) => {
+                let items = children
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, item)| {
+                        let marker = match start {
+                            Some(start) => format!("{}. ", start + idx as u64),
+                            None => "* ".to_owned(),
+                        };
+                        let text = match item {
+                            Node::Element(Tag::Item, children) => self.blocks(children, false),
+                            node => self.blocks(std::slice::from_ref(node), false),
+                        };
+                        super::indent(&text, &marker, &" ".repeat(marker.len()))
+                    })
+                    .collect::<Vec<_>>();
+                // Items containing multiple blocks must be separated by blank lines.
+                let separator = if items.iter().any(|item| item.contains("\n\n")) {
+                    "\n\n"
+                } else {
+                    "\n"
+                };
+                items.join(separator)
+            }
+            Tag::Table(_) => self.table(children),
+            Tag::FootnoteDefinition(label) => {
+                let text = self.blocks(children, false);
+                super::indent(&text, &format!(".. [#{}] ", footnote_label(label)), "   ")
+            }
+            _ => self.blocks(children, false),
+        };
+        Some(text.trim_end().to_owned())
+    }
+
+    fn table(&mut self, children: &[Node<'_>]) -> String {
+        let mut text = ".. list-table::\n   :header-rows: 1\n\n".to_owned();
+        for row in children {
+            let Node::Element(Tag::TableHead | Tag::TableRow, cells) = row else {
+                continue;
+            };
+            for (idx, cell) in cells.iter().enumerate() {
+                let Node::Element(Tag::TableCell, children) = cell else {
+                    continue;
+                };
+                let marker = if idx == 0 { "   * - " } else { "     - " };
+                let cell = self.inline(children, false);
+                text.push_str(format!("{marker}{cell}").trim_end());
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Renders the inline nodes.
+    ///
+    /// Inline markup cannot be nested in reStructuredText, so the contents are
+    /// rendered as plain text if `plain` is `true`.
+    fn inline(&mut self, nodes: &[Node<'_>], plain: bool) -> String {
+        let mut text = Inline::default();
+        for node in nodes {
+            match node {
+                Node::Leaf(event) => match event {
+                    Event::Text(s) => text.push_text(&escape(s)),
+                    Event::Code(s) if plain => text.push_text(&escape(s)),
+                    Event::Code(s) => text.push_markup(&format!("``{s}``")),
+                    Event::SoftBreak | Event::HardBreak => text.push_text("\n"),
+                    Event::TaskListMarker(checked) => {
+                        text.push_text(if *checked { "[x] " } else { "[ ] " });
+                    }
+                    Event::FootnoteReference(label) => {
+                        text.push_markup(&format!("[#{}]_", footnote_label(label)));
+                    }
+                    // Raw inline HTML cannot be written without defining a role.
+                    _ => {}
+                },
+                Node::Element(tag, children) => match tag {
+                    Tag::Emphasis | Tag::Strong if plain => {
+                        text.push_text(&self.inline(children, true));
+                    }
+                    Tag::Emphasis => {
+                        text.push_markup(&format!("*{}*", self.inline(children, true)));
+                    }
+                    Tag::Strong => {
+                        text.push_markup(&format!("**{}**", self.inline(children, true)));
+                    }
+                    // Standalone URLs and email addresses are recognized as links.
+                    Tag::Link {
+                        link_type: LinkType::Autolink | LinkType::Email,
+                        dest_url,
+                        ..
+                    } => text.push_markup(dest_url),
+                    Tag::Link { dest_url, .. } => match children.as_slice() {
+                        [
+                            Node::Element(
+                                Tag::Image {
+                                    dest_url: image, ..
+                                },
+                                alt,
+                            ),
+                        ] if !plain => {
+                            let alt = super::plain_text(alt);
+                            text.push_markup(&self.substitution(image, &alt, dest_url));
+                        }
+                        _ if plain => text.push_text(&self.inline(children, true)),
+                        _ => {
+                            let label = self.inline(children, true).replace('<', r"\<");
+                            let separator = if label.is_empty() { "" } else { " " };
+                            text.push_markup(&format!("`{label}{separator}<{dest_url}>`__"));
+                        }
+                    },
+                    Tag::Image { dest_url, .. } => {
+                        let alt = super::plain_text(children);
+                        if plain {
+                            text.push_text(&escape(&alt));
+                        } else {
+                            text.push_markup(&self.substitution(dest_url, &alt, ""));
+                        }
+                    }
+                    _ => text.push_text(&self.inline(children, plain)),
+                },
+            }
+        }
+        text.text
+    }
+
+    /// Defines a substitution for the image and returns its reference.
+    fn substitution(&mut self, image: &str, alt: &str, link: &str) -> String {
+        let mut definition = format!("image:: {image}");
+        if !alt.is_empty() {
+            write!(definition, "\n   :alt: {alt}").unwrap();
+        }
+        if !link.is_empty() {
+            write!(definition, "\n   :target: {link}").unwrap();
+        }
+
+        let base = alt.replace(['|', '\n'], " ").trim().to_owned();
+        let base = if base.is_empty() {
+            "image".to_owned()
+        } else {
+            base
+        };
+        let mut name = base.clone();
+        for count in 2.. {
+            // Substitution names are case-insensitive.
+            let existing = self
+                .substitutions
+                .iter()
+                .find(|(existing, _)| existing.eq_ignore_ascii_case(&name));
+            match existing {
+                Some((_, existing)) if *existing == definition => return format!("|{name}|"),
+                Some(_) => name = format!("{base} ({count})"),
+                None => break,
+            }
+        }
+        self.pending.push(format!(".. |{name}| {definition}"));
+        self.substitutions.push((name.clone(), definition));
+        format!("|{name}|")
+    }
+}
+
+/// Inline text with inline markup separated from the surrounding text.
+#[derive(Debug, Default)]
+struct Inline {
+    text: String,
+    after_markup: bool,
+}
+
+impl Inline {
+    fn push_text(&mut self, text: &str) {
+        // Inline markup must be followed by whitespace or punctuation.
+        if self.after_markup && text.starts_with(|c: char| c.is_alphanumeric()) {
+            self.text.push_str(r"\ ");
+        }
+        self.after_markup = false;
+        self.text.push_str(text);
+    }
+
+    fn push_markup(&mut self, markup: &str) {
+        // Inline markup must be preceded by whitespace or punctuation.
+        if self
+            .text
+            .ends_with(|c: char| !c.is_whitespace() && !"-:/'\"<([{".contains(c))
+        {
+            self.text.push_str(r"\ ");
+        }
+        self.text.push_str(markup);
+        self.after_markup = true;
+    }
+}
+
+fn directive_with_contents(directive: &str, contents: &str) -> String {
+    format!("{directive}\n\n{}", super::indent(contents, "   ", "   "))
+}
+
+fn code_text(nodes: &[Node<'_>]) -> String {
+    let mut code = String::new();
+    for node in nodes {
+        if let Node::Leaf(Event::Text(s) | Event::Html(s)) = node {
+            code.push_str(s);
+        }
+    }
+    code
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '`' | '|' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn footnote_label(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    fn render_str(markdown: &str) -> String {
+        render(&super::super::parse(markdown))
+    }
+
+    #[test]
+    fn render_blocks() {
+        let markdown = indoc! {"
+            # Title
+
+            Some *emphasis*, **strong**, ~~deleted~~ and `code`s.
+            Escaped \\*stars\\*: snake_case.
+
+            ## Section
+
+            > Quoted
+            > text.
+
+            ```rust,ignore
+            fn main() {}
+            ```
+
+            ```
+            plain
+            ```
+
+            ---
+
+            <div>HTML</div>
+        "};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {r"
+                Title
+                =====
+
+                Some *emphasis*, **strong**, deleted and ``code``\ s.
+                Escaped \*stars\*: snake\_case.
+
+                Section
+                -------
+
+                ..
+
+                   Quoted
+                   text.
+
+                .. code-block:: rust
+
+                   fn main() {}
+
+                ::
+
+                   plain
+
+                ----
+
+                .. raw:: html
+
+                   <div>HTML</div>
+            "}
+        );
+    }
+
+    #[test]
+    fn render_lists() {
+        let markdown = indoc! {"
+            * foo
+            * bar
+
+            3. one
+
+               more
+            4. two
+               * nested
+        "};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {"
+                * foo
+                * bar
+
+                3. one
+
+                   more
+
+                4. two
+
+                   * nested
+            "}
+        );
+    }
+
+    #[test]
+    fn render_links_and_images() {
+        let markdown = indoc! {r#"
+            [![License](https://img.shields.io/badge/license-MIT-blue.svg)](#license)
+            [![License](https://img.shields.io/badge/license-MIT-blue.svg)](#license)
+            ![License](https://example.com/license.png)
+            [**Foo**](https://docs.rs/foo "struct foo::Foo") and <https://example.com/a_b>.
+
+            Next paragraph.
+        "#};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {r"
+                |License|
+                |License|
+                |License (2)|
+                `Foo <https://docs.rs/foo>`__ and https://example.com/a_b.
+
+                .. |License| image:: https://img.shields.io/badge/license-MIT-blue.svg
+                   :alt: License
+                   :target: #license
+                .. |License (2)| image:: https://example.com/license.png
+                   :alt: License
+
+                Next paragraph.
+            "}
+        );
+    }
+
+    #[test]
+    fn render_table_and_footnote() {
+        let markdown = indoc! {"
+            | Name | Value |
+            |------|-------|
+            | a    |       |
+
+            Text[^note].
+
+            [^note]: The note.
+        "};
+        assert_eq!(
+            render_str(markdown),
+            indoc! {"
+                .. list-table::
+                   :header-rows: 1
+
+                   * - Name
+                     - Value
+                   * - a
+                     -
+
+                Text\\ [#note]_.
+
+                .. [#note] The note.
+            "}
+        );
+    }
+}
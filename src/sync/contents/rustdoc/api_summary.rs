@@ -0,0 +1,204 @@
+use std::fmt::Write as _;
+
+use pulldown_cmark::{CowStr, Event, Tag, TagEnd};
+use rustdoc_types::{Item, ItemEnum};
+
+use crate::sync::{
+    contents::rustdoc::{
+        document::{IntraLinkResolver, RustdocDocument},
+        intra_link::LinkMappingConfig,
+        link, main_body_opts,
+    },
+    marker::{ApiSummaryOptions, LinkStyle},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Group {
+    Modules,
+    Structs,
+    Enums,
+    Traits,
+    Functions,
+    Macros,
+}
+
+impl Group {
+    fn of(item: &Item) -> Option<Self> {
+        match &item.inner {
+            ItemEnum::Module(_) => Some(Self::Modules),
+            ItemEnum::Struct(_) => Some(Self::Structs),
+            ItemEnum::Enum(_) => Some(Self::Enums),
+            ItemEnum::Trait(_) => Some(Self::Traits),
+            ItemEnum::Function(_) => Some(Self::Functions),
+            ItemEnum::Macro(_) | ItemEnum::ProcMacro(_) => Some(Self::Macros),
+            _ => None,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Modules => "Modules",
+            Self::Structs => "Structs",
+            Self::Enums => "Enums",
+            Self::Traits => "Traits",
+            Self::Functions => "Functions",
+            Self::Macros => "Macros",
+        }
+    }
+}
+
+pub(super) fn render(
+    doc: &RustdocDocument,
+    resolver: &IntraLinkResolver<'_>,
+    mapping_config: &LinkMappingConfig<'_>,
+    options: &ApiSummaryOptions,
+) -> String {
+    let mut items = doc
+        .public_items()
+        .into_iter()
+        .filter_map(|(path, item)| Some((Group::of(item)?, path.join("::"), item)))
+        .collect::<Vec<_>>();
+    items.sort_by(|(g1, p1, _), (g2, p2, _)| g1.cmp(g2).then_with(|| p1.cmp(p2)));
+
+    let heading = "#".repeat(options.heading_level().into());
+    let mut output = String::new();
+    let mut current_group = None;
+    for (group, path, item) in items {
+        if current_group != Some(group) {
+            if current_group.is_some() {
+                output.push('\n');
+            }
+            writeln!(&mut output, "{heading} {}\n", group.title()).unwrap();
+            current_group = Some(group);
+        }
+
+        let name = match &item.inner {
+            ItemEnum::Macro(_) => format!("{path}!"),
+            _ => path,
+        };
+        match resolver.resolve_link(item.id) {
+            Some(target) => write!(&mut output, "* [`{name}`]({})", target.build_url()).unwrap(),
+            None => write!(&mut output, "* `{name}`").unwrap(),
+        }
+        if let Some(summary) = summary(resolver, mapping_config, item) {
+            write!(&mut output, ": {summary}").unwrap();
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Returns the first sentence of the documentation of `item`, written in Markdown.
+fn summary(
+    resolver: &IntraLinkResolver<'_>,
+    mapping_config: &LinkMappingConfig<'_>,
+    item: &Item,
+) -> Option<String> {
+    let mapper = mapping_config.build_mapper(resolver, item)?;
+    let events = first_sentence(mapper.build_parser(main_body_opts()));
+    if events.is_empty() {
+        return None;
+    }
+
+    let events = [Event::Start(Tag::Paragraph)]
+        .into_iter()
+        .chain(events)
+        .chain([Event::End(TagEnd::Paragraph)]);
+    let mut buf = String::new();
+    pulldown_cmark_to_cmark::cmark(link::convert(events, LinkStyle::Inline), &mut buf).unwrap();
+    Some(buf.trim().to_owned())
+}
+
+/// Takes the inline events of the first sentence in the first paragraph.
+fn first_sentence<'a>(events: impl IntoIterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut events = events
+        .into_iter()
+        .skip_while(|event| !matches!(event, Event::Start(Tag::Paragraph)))
+        .skip(1);
+
+    let mut output = vec![];
+    let mut depth = 0_usize;
+    for event in events.by_ref() {
+        match &event {
+            Event::End(TagEnd::Paragraph) if depth == 0 => break,
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth -= 1,
+            Event::SoftBreak | Event::HardBreak => {
+                if depth == 0
+                    && let Some(Event::Text(text)) = output.last()
+                    && ends_sentence(text)
+                {
+                    break;
+                }
+                output.push(Event::Text(" ".into()));
+                continue;
+            }
+            Event::Text(text) if depth == 0 => {
+                if let Some(end) = sentence_end(text) {
+                    output.push(Event::Text(CowStr::from(text[..end].to_owned())));
+                    break;
+                }
+            }
+            _ => {}
+        }
+        output.push(event);
+    }
+    output
+}
+
+/// Returns the byte offset just after the end of the first sentence in `text`.
+///
+/// A sentence ends with `.`, `!`, or `?` followed by whitespace.
+fn sentence_end(text: &str) -> Option<usize> {
+    text.char_indices()
+        .zip(text.chars().skip(1))
+        .filter(|(_, next)| next.is_whitespace())
+        .map(|((idx, c), _)| idx + c.len_utf8())
+        .find(|end| ends_sentence(&text[..*end]))
+}
+
+/// Returns `true` if `text` ends with the end of a sentence.
+fn ends_sentence(text: &str) -> bool {
+    const ABBREVIATIONS: &[&str] = &["e.g.", "i.e.", "etc."];
+
+    text.ends_with(['.', '!', '?']) && !ABBREVIATIONS.iter().any(|abbr| text.ends_with(abbr))
+}
+
+#[cfg(test)]
+mod tests {
+    use pulldown_cmark::Parser;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    fn first_sentence_str(input: &str) -> String {
+        let events = [Event::Start(Tag::Paragraph)]
+            .into_iter()
+            .chain(first_sentence(Parser::new(input)))
+            .chain([Event::End(TagEnd::Paragraph)]);
+        let mut buf = String::new();
+        pulldown_cmark_to_cmark::cmark(events, &mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn first_sentence_of_first_paragraph() {
+        assert_eq!(first_sentence_str("Foo bar."), "Foo bar.");
+        assert_eq!(
+            first_sentence_str("Returns `a.b`, e.g. `1.0`. More text."),
+            "Returns `a.b`, e.g. `1.0`."
+        );
+        assert_eq!(
+            first_sentence_str("A [link. with dot](https://example.com) here!\nNext line."),
+            "A [link. with dot](https://example.com) here!"
+        );
+        assert_eq!(
+            first_sentence_str("Wrapped\nsentence.\n\nSecond paragraph."),
+            "Wrapped sentence."
+        );
+        assert_eq!(
+            first_sentence_str("# Heading\n\nFirst paragraph."),
+            "First paragraph."
+        );
+    }
+}
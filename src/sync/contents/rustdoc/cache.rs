@@ -0,0 +1,510 @@
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Write as _,
+    fs, io,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use cargo_metadata::{
+    DependencyKind, Metadata, Package, PackageId,
+    camino::{Utf8Component, Utf8Path, Utf8PathBuf},
+};
+use sha1::{Digest as _, Sha1};
+use snafu::ResultExt as _;
+
+use crate::{
+    cargo::{self, Toolchain},
+    sync::{
+        ManifestFile, SyncOptions,
+        contents::rustdoc::{
+            CreateResult, DetermineToolchainSnafu, document::RustdocDocument, format,
+        },
+    },
+    traits::PackageExt as _,
+};
+
+/// Toolchains and rustdoc output shared across markers, target files and
+/// packages for the whole invocation.
+///
+/// Features and the toolchain are fixed for an invocation, so documents are
+/// keyed by the package.
+#[derive(Debug, Default)]
+pub(crate) struct RustdocCache {
+    /// Directory of the on-disk cache of rustdoc JSON output, if enabled.
+    dir: Option<Utf8PathBuf>,
+    toolchains: Mutex<Option<Arc<Toolchains>>>,
+    documents: Mutex<HashMap<PackageId, Arc<DocumentSlot>>>,
+}
+
+/// Document of a package, locked while it is loaded so that rustdoc is run once
+/// even if the package is requested by parallel tasks.
+type DocumentSlot = Mutex<Option<Arc<RustdocDocument>>>;
+
+#[derive(Debug)]
+pub(super) struct Toolchains {
+    /// Toolchain running Cargo.
+    pub(super) expected: Toolchain,
+    /// Toolchain running rustdoc, specified by `--toolchain`.
+    pub(super) rustdoc: Toolchain,
+    /// Output of `cargo --version --verbose` of the rustdoc toolchain.
+    rustdoc_version: String,
+}
+
+impl RustdocCache {
+    /// Creates a cache. If `on_disk` is `true`, rustdoc JSON output is also
+    /// cached under the target directory of the workspace across invocations.
+    pub(crate) fn new(workspace: &Metadata, on_disk: bool) -> Self {
+        let dir = on_disk.then(|| {
+            workspace
+                .target_directory
+                .join("cargo-sync-rdme")
+                .join("rustdoc")
+        });
+        Self {
+            dir,
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn toolchains(&self, options: &SyncOptions<'_>) -> CreateResult<Arc<Toolchains>> {
+        let mut toolchains = self
+            .toolchains
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(toolchains) = &*toolchains {
+            return Ok(Arc::clone(toolchains));
+        }
+        let (expected, _version) = cargo::toolchain(None).context(DetermineToolchainSnafu)?;
+        let (rustdoc, rustdoc_version) =
+            cargo::toolchain(Some(options.toolchain)).context(DetermineToolchainSnafu)?;
+        let new = Arc::new(Toolchains {
+            expected,
+            rustdoc,
+            rustdoc_version,
+        });
+        *toolchains = Some(Arc::clone(&new));
+        Ok(new)
+    }
+
+    pub(super) fn document(
+        &self,
+        workspace: &Metadata,
+        manifest: &ManifestFile,
+        package: &Package,
+        options: &SyncOptions<'_>,
+    ) -> CreateResult<Arc<RustdocDocument>> {
+        let slot = Arc::clone(
+            self.documents
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(package.id.clone())
+                .or_default(),
+        );
+        // Only the tasks waiting for the same package are blocked while rustdoc is running.
+        let mut slot = slot.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(doc) = &*slot {
+            return Ok(Arc::clone(doc));
+        }
+
+        let doc = Arc::new(self.load_document(workspace, manifest, package, options)?);
+        *slot = Some(Arc::clone(&doc));
+        Ok(doc)
+    }
+
+    fn load_document(
+        &self,
+        workspace: &Metadata,
+        manifest: &ManifestFile,
+        package: &Package,
+        options: &SyncOptions<'_>,
+    ) -> CreateResult<RustdocDocument> {
+        if let Some(path) = super::prebuilt_json_path(manifest, package, options) {
+            tracing::info!("using pre-built rustdoc output: {path}");
+            let doc = read_document(&path)?;
+            super::ensure_crate_name(&doc, &path, package)?;
+            return Ok(doc);
+        }
+
+        let Some(dir) = &self.dir else {
+            let output_file = super::run_rustdoc(package, options)?;
+            return read_document(&output_file);
+        };
+
+        let toolchains = self.toolchains(options)?;
+        let cache_dir = dir.join(package.name.as_str());
+        let sources = SourcesHash::new(workspace, package, options, &toolchains)
+            .inspect_err(|err| {
+                tracing::warn!("failed to compute the fingerprint of package sources: {err}");
+            })
+            .ok();
+        let cache_file = |sources: &SourcesHash<'_>| match sources.fingerprint() {
+            Ok(fingerprint) => Some(cache_dir.join(format!("{fingerprint}.json"))),
+            Err(err) => {
+                tracing::warn!("failed to compute the fingerprint of package sources: {err}");
+                None
+            }
+        };
+
+        if let Some(cache_file) = sources.as_ref().and_then(cache_file)
+            && cache_file.is_file()
+        {
+            tracing::info!("using cached rustdoc output: {cache_file}");
+            match read_document(&cache_file) {
+                Ok(doc) => return Ok(doc),
+                Err(err) => tracing::warn!("ignoring broken rustdoc cache: {cache_file}: {err}"),
+            }
+        }
+
+        let output_file = super::run_rustdoc(package, options)?;
+        let doc = read_document(&output_file)?;
+        // Cargo may create or update `Cargo.lock` and the build script output
+        // while building, so only they are read again.
+        if let Some(cache_file) = sources.as_ref().and_then(cache_file)
+            && let Err(err) = store(&cache_dir, &cache_file, &output_file)
+        {
+            tracing::warn!("failed to cache rustdoc output: {cache_file}: {err}");
+        }
+        Ok(doc)
+    }
+}
+
+fn read_document(path: &Utf8Path) -> CreateResult<RustdocDocument> {
+    Ok(RustdocDocument::new(format::read(path)?))
+}
+
+/// Copies the rustdoc output into the cache, replacing outdated entries of the package.
+fn store(cache_dir: &Utf8Path, cache_file: &Utf8Path, output_file: &Utf8Path) -> io::Result<()> {
+    if cache_dir.is_dir() {
+        for entry in cache_dir.read_dir_utf8()? {
+            fs::remove_file(entry?.path())?;
+        }
+    }
+    fs::create_dir_all(cache_dir)?;
+    fs::copy(output_file, cache_file)?;
+    Ok(())
+}
+
+/// Environment variables that change the rustdoc output through the flags
+/// passed by Cargo.
+const FLAG_VARIABLES: &[&str] = &[
+    "RUSTFLAGS",
+    "RUSTDOCFLAGS",
+    "CARGO_ENCODED_RUSTFLAGS",
+    "CARGO_ENCODED_RUSTDOCFLAGS",
+    "CARGO_BUILD_RUSTFLAGS",
+    "CARGO_BUILD_RUSTDOCFLAGS",
+    "CARGO_BUILD_TARGET",
+];
+
+/// Hash of the inputs of rustdoc that are not written by building the package.
+///
+/// Covers the version of this tool, the package, the features, the rustdoc
+/// toolchain, the flag environment variables, the Cargo configuration files,
+/// and the files in the roots of the package and its path dependencies except
+/// the target files of this tool.
+struct SourcesHash<'a> {
+    hasher: Sha1,
+    workspace: &'a Metadata,
+    /// Names and root directories of the package and its path dependencies.
+    packages: Vec<(String, Utf8PathBuf)>,
+}
+
+impl<'a> SourcesHash<'a> {
+    fn new(
+        workspace: &'a Metadata,
+        package: &Package,
+        options: &SyncOptions<'_>,
+        toolchains: &Toolchains,
+    ) -> io::Result<Self> {
+        let mut hasher = Sha1::new();
+        update(&mut hasher, env!("CARGO_PKG_VERSION").as_bytes());
+        update(&mut hasher, package.id.repr.as_bytes());
+        for arg in cargo::feature_args(options.feature) {
+            update(&mut hasher, arg.as_bytes());
+        }
+        update(&mut hasher, toolchains.rustdoc_version.as_bytes());
+        hash_variables(&mut hasher, FLAG_VARIABLES.iter().copied());
+
+        let packages = path_dependencies(workspace, package);
+        let mut files = config_files(workspace);
+        let mut skipped = target_files(workspace, &packages);
+        // Hashed by `fingerprint`, as it may be created by the build.
+        skipped.push(workspace.workspace_root.join("Cargo.lock"));
+        for (_name, dir) in &packages {
+            walk(dir, &workspace.target_directory, &skipped, &mut files)?;
+        }
+        hash_files(&mut hasher, workspace, files)?;
+
+        Ok(Self {
+            hasher,
+            workspace,
+            packages,
+        })
+    }
+
+    /// Returns the fingerprint of the inputs of rustdoc, adding `Cargo.lock`
+    /// and the inputs declared by the build scripts to the sources.
+    fn fingerprint(&self) -> io::Result<String> {
+        let mut hasher = self.hasher.clone();
+        let mut files = vec![];
+        let lock_file = self.workspace.workspace_root.join("Cargo.lock");
+        if lock_file.is_file() {
+            files.push(lock_file);
+        }
+        let mut variables = vec![];
+        build_script_inputs(self.workspace, &self.packages, &mut files, &mut variables)?;
+        hash_variables(&mut hasher, variables.iter().map(String::as_str));
+        hash_files(&mut hasher, self.workspace, files)?;
+
+        let mut fingerprint = String::new();
+        for byte in hasher.finalize() {
+            write!(fingerprint, "{byte:02x}").unwrap();
+        }
+        Ok(fingerprint)
+    }
+}
+
+fn update(hasher: &mut Sha1, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+fn hash_variables<'v>(hasher: &mut Sha1, variables: impl Iterator<Item = &'v str>) {
+    let mut variables = variables.collect::<Vec<_>>();
+    variables.sort_unstable();
+    variables.dedup();
+    for name in variables {
+        update(hasher, name.as_bytes());
+        match env::var_os(name) {
+            Some(value) => update(hasher, value.as_encoded_bytes()),
+            // Distinguish unset variables from empty ones.
+            None => update(hasher, &[0xff]),
+        }
+    }
+}
+
+fn hash_files(
+    hasher: &mut Sha1,
+    workspace: &Metadata,
+    mut files: Vec<Utf8PathBuf>,
+) -> io::Result<()> {
+    files.sort();
+    files.dedup();
+    for path in files {
+        let relative = path
+            .strip_prefix(&workspace.workspace_root)
+            .unwrap_or(&path);
+        update(hasher, relative.as_str().as_bytes());
+        update(hasher, &fs::read(&path)?);
+    }
+    Ok(())
+}
+
+/// Returns the Cargo configuration files that may set the flags of rustdoc.
+fn config_files(workspace: &Metadata) -> Vec<Utf8PathBuf> {
+    let mut files = vec![];
+    for dir in workspace.workspace_root.ancestors() {
+        for name in [".cargo/config.toml", ".cargo/config"] {
+            let config = dir.join(name);
+            if config.is_file() {
+                files.push(config);
+            }
+        }
+    }
+    if let Some(home) = env::var_os("CARGO_HOME")
+        && let Ok(home) = Utf8PathBuf::from_path_buf(home.into())
+        && home.join("config.toml").is_file()
+    {
+        files.push(home.join("config.toml"));
+    }
+    files
+}
+
+/// Returns the names and root directories of `package` and its path
+/// dependencies, following the path dependencies outside of the workspace
+/// through their manifests.
+///
+/// Dev-dependencies are skipped, as they are not used by rustdoc.
+fn path_dependencies(workspace: &Metadata, package: &Package) -> Vec<(String, Utf8PathBuf)> {
+    let members = workspace.workspace_packages();
+    let mut packages = vec![];
+    let mut pending = vec![package.root_directory().to_owned()];
+    while let Some(dir) = pending.pop() {
+        if packages.iter().any(|(_name, known)| *known == dir) {
+            continue;
+        }
+        if let Some(member) = members.iter().find(|member| member.root_directory() == dir) {
+            pending.extend(
+                member
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| dependency.kind != DependencyKind::Development)
+                    .filter_map(|dependency| dependency.path.clone()),
+            );
+            packages.push((member.name.to_string(), dir));
+            continue;
+        }
+        let manifest = fs::read_to_string(dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|text| text.parse::<toml::Table>().ok());
+        let name = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get("package")?.get("name")?.as_str())
+            .unwrap_or_default()
+            .to_owned();
+        if let Some(manifest) = &manifest {
+            pending.extend(manifest_dependency_paths(manifest).map(|path| dir.join(path)));
+        }
+        packages.push((name, dir));
+    }
+    packages
+}
+
+/// Returns the `path` of the dependencies declared in the manifest, including
+/// the platform-specific ones.
+fn manifest_dependency_paths(manifest: &toml::Table) -> impl Iterator<Item = &str> {
+    let tables = [manifest].into_iter().chain(
+        manifest
+            .get("target")
+            .and_then(toml::Value::as_table)
+            .into_iter()
+            .flat_map(toml::Table::values)
+            .filter_map(toml::Value::as_table),
+    );
+    tables
+        .flat_map(|table| {
+            ["dependencies", "build-dependencies"]
+                .into_iter()
+                .filter_map(|key| table.get(key).and_then(toml::Value::as_table))
+        })
+        .flat_map(toml::Table::values)
+        .filter_map(|dependency| dependency.get("path").and_then(toml::Value::as_str))
+}
+
+/// Returns the target files of the workspace members in `packages`.
+///
+/// They are written by this tool, so hashing them would invalidate the cache on
+/// every synchronization.
+fn target_files(workspace: &Metadata, packages: &[(String, Utf8PathBuf)]) -> Vec<Utf8PathBuf> {
+    let mut files = vec![];
+    for member in workspace.workspace_packages() {
+        let root = member.root_directory();
+        if !packages.iter().any(|(_name, dir)| dir == root) {
+            continue;
+        }
+        let extra_targets = member
+            .metadata
+            .pointer("/cargo-sync-rdme/extra-targets")
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(serde_json::Value::as_str)
+            .map(Utf8Path::new);
+        for path in member.readme.as_deref().into_iter().chain(extra_targets) {
+            files.push(normalize(&root.join(path)));
+        }
+    }
+    files
+}
+
+/// Removes the `.` components, as the paths in the manifest may start with `./`.
+fn normalize(path: &Utf8Path) -> Utf8PathBuf {
+    path.components()
+        .filter(|component| *component != Utf8Component::CurDir)
+        .collect()
+}
+
+/// Appends the files and environment variables declared by the
+/// `rerun-if-changed` and `rerun-if-env-changed` instructions of the build
+/// scripts of `packages`.
+///
+/// The instructions are read from the output of the last build, and the output
+/// itself is also appended, as it sets the environment and `cfg`s of the
+/// package.
+fn build_script_inputs(
+    workspace: &Metadata,
+    packages: &[(String, Utf8PathBuf)],
+    files: &mut Vec<Utf8PathBuf>,
+    variables: &mut Vec<String>,
+) -> io::Result<()> {
+    let target_dir = &workspace.target_directory;
+    let mut build_dirs = vec![target_dir.join("debug/build")];
+    if target_dir.is_dir() {
+        for entry in target_dir.read_dir_utf8()? {
+            build_dirs.push(entry?.path().join("debug/build"));
+        }
+    }
+
+    for build_dir in build_dirs {
+        if !build_dir.is_dir() {
+            continue;
+        }
+        for entry in build_dir.read_dir_utf8()? {
+            let entry = entry?;
+            // Build directories are named `<package name>-<hash>`.
+            let Some((_name, root)) = entry
+                .file_name()
+                .rsplit_once('-')
+                .and_then(|(name, _hash)| packages.iter().find(|(known, _dir)| known == name))
+            else {
+                continue;
+            };
+            let output = entry.path().join("output");
+            let Ok(text) = fs::read_to_string(&output) else {
+                continue;
+            };
+            for line in text.lines() {
+                let Some(instruction) = line
+                    .strip_prefix("cargo::")
+                    .or_else(|| line.strip_prefix("cargo:"))
+                else {
+                    continue;
+                };
+                if let Some(path) = instruction.strip_prefix("rerun-if-changed=") {
+                    let path = root.join(path);
+                    if path.is_dir() {
+                        walk(&path, target_dir, &[], files)?;
+                    } else if path.is_file() {
+                        files.push(path);
+                    }
+                } else if let Some(name) = instruction.strip_prefix("rerun-if-env-changed=") {
+                    variables.push(name.to_owned());
+                }
+            }
+            files.push(output);
+        }
+    }
+    Ok(())
+}
+
+/// Appends the files under `dir` to `files`, except the hidden ones, the
+/// `skipped` ones, the target directories, and the other packages.
+fn walk(
+    dir: &Utf8Path,
+    target_dir: &Utf8Path,
+    skipped: &[Utf8PathBuf],
+    files: &mut Vec<Utf8PathBuf>,
+) -> io::Result<()> {
+    // Cargo marks target directories, including those of path dependencies,
+    // with `CACHEDIR.TAG`.
+    if dir == target_dir || !dir.is_dir() || dir.join("CACHEDIR.TAG").is_file() {
+        return Ok(());
+    }
+    for entry in dir.read_dir_utf8()? {
+        let entry = entry?;
+        if entry.file_name().starts_with('.') {
+            continue;
+        }
+        let path = entry.into_path();
+        if path.is_dir() {
+            // Nested packages are hashed only if they are path dependencies.
+            if !path.join("Cargo.toml").is_file() {
+                walk(&path, target_dir, skipped, files)?;
+            }
+        } else if !skipped.contains(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::collections::{HashMap, hash_map};
+
+use pulldown_cmark::{Event, Tag};
+use rustdoc_types::Item;
+
+use crate::sync::contents::rustdoc::document::{IntraLinkResolver, RustdocDocument};
+
+/// Maps documentation URLs back to intra-doc link destinations.
+#[derive(Debug, Default)]
+pub(super) struct LinkUnmapper {
+    links: HashMap<String, UnmappedLink>,
+}
+
+#[derive(Debug)]
+struct UnmappedLink {
+    path: String,
+    title: String,
+}
+
+impl LinkUnmapper {
+    /// Collects the URLs that the forward synchronization may generate.
+    ///
+    /// If multiple paths have the same URL, `rustdoc.mappings` take precedence
+    /// over the links in the current crate documentation, which take precedence
+    /// over the paths of public items.
+    pub(super) fn new(
+        doc: &RustdocDocument,
+        resolver: &IntraLinkResolver<'_>,
+        root: &Item,
+        mappings: &HashMap<String, String>,
+    ) -> Self {
+        let mut unmapper = Self::default();
+
+        let mut mappings = mappings.iter().collect::<Vec<_>>();
+        mappings.sort();
+        for (name, url) in mappings {
+            unmapper.insert(url.clone(), name, String::new());
+        }
+
+        let mut links = root.links.iter().collect::<Vec<_>>();
+        links.sort();
+        for (name, id) in links {
+            if let Some(target) = resolver.resolve_link(*id) {
+                unmapper.insert(target.build_url(), name, target.build_title());
+            }
+        }
+
+        for (path, item) in doc.public_items() {
+            if let Some(target) = resolver.resolve_link(item.id) {
+                unmapper.insert(target.build_url(), &path.join("::"), target.build_title());
+            }
+        }
+
+        unmapper
+    }
+
+    fn insert(&mut self, url: String, path: &str, title: String) {
+        if let hash_map::Entry::Vacant(entry) = self.links.entry(url) {
+            // rustdoc ignores backticks in intra-doc link destinations.
+            let path = path.replace('`', "");
+            entry.insert(UnmappedLink { path, title });
+        }
+    }
+
+    /// Rewrites links to known documentation URLs into intra-doc links.
+    ///
+    /// Titles generated by the forward synchronization are removed.
+    pub(super) fn convert<'a, 'b>(
+        &'b self,
+        events: impl IntoIterator<Item = Event<'a>> + 'b,
+    ) -> impl Iterator<Item = Event<'a>> + 'b {
+        events.into_iter().map(move |mut event| {
+            if let Event::Start(Tag::Link {
+                dest_url, title, ..
+            }) = &mut event
+                && let Some(link) = self.links.get(dest_url.as_ref())
+            {
+                if title.as_ref() == link.title {
+                    *title = "".into();
+                }
+                *dest_url = link.path.clone().into();
+            }
+            event
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    fn convert_str(unmapper: &LinkUnmapper, input: &str) -> String {
+        let events: Vec<_> = pulldown_cmark::Parser::new(input).collect();
+        let mut output = String::new();
+        pulldown_cmark_to_cmark::cmark(unmapper.convert(events), &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn convert_links() {
+        let mut unmapper = LinkUnmapper::default();
+        unmapper.insert(
+            "https://docs.rs/foo/0.1.0/foo/struct.Foo.html".into(),
+            "`Foo`",
+            "struct foo::Foo".into(),
+        );
+        unmapper.insert(
+            "https://docs.rs/foo/0.1.0/foo/fn.bar.html".into(),
+            "crate::bar",
+            "fn foo::bar".into(),
+        );
+        unmapper.insert(
+            "https://docs.rs/foo/0.1.0/foo/struct.Foo.html".into(),
+            "Foo",
+            "struct foo::Foo".into(),
+        );
+
+        let input = indoc! {r#"
+            [`Foo`] [bar](https://docs.rs/foo/0.1.0/foo/fn.bar.html) [other] [titled][baz]
+
+            [`Foo`]: https://docs.rs/foo/0.1.0/foo/struct.Foo.html "struct foo::Foo"
+            [other]: https://example.com/other "Other"
+            [baz]: https://docs.rs/foo/0.1.0/foo/fn.bar.html "Custom title"
+        "#};
+
+        assert_eq!(
+            convert_str(&unmapper, input),
+            indoc! {r#"
+                [`Foo`] [bar](crate::bar) [other] [titled][baz]
+
+                [`Foo`]: Foo
+                [other]: https://example.com/other "Other"
+                [baz]: crate::bar "Custom title""#}
+        );
+    }
+}
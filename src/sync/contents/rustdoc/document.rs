@@ -1,13 +1,13 @@
 use std::{
     borrow::Cow,
     cmp,
-    collections::{HashMap, hash_map},
+    collections::{HashMap, HashSet, VecDeque, hash_map},
     fmt::Display,
     iter,
     rc::Rc,
 };
 
-use rustdoc_types::{Crate, Id, Item, ItemEnum, ItemKind, ItemSummary};
+use rustdoc_types::{Crate, Id, Item, ItemEnum, ItemKind, ItemSummary, Visibility};
 
 use crate::cargo::{Channel, Toolchain};
 
@@ -34,6 +34,113 @@ impl RustdocDocument {
     pub(super) fn root_item(&self) -> Option<&Item> {
         self.doc.index.get(&self.doc.root)
     }
+
+    /// Finds an item of the local crate by its path (e.g. `foo::Bar`).
+    ///
+    /// The path may start with `crate` or the crate name. Items re-exported
+    /// with `pub use` are found by their re-exported path.
+    pub(super) fn find_item(&self, path: &str) -> Option<&Item> {
+        let root = self.root_item()?;
+        let mut segments = path.split("::").collect::<Vec<_>>();
+        if segments
+            .first()
+            .is_some_and(|first| *first == "crate" || Some(*first) == root.name.as_deref())
+        {
+            segments.remove(0);
+        }
+        if segments.is_empty() {
+            return Some(root);
+        }
+
+        let found = self
+            .doc
+            .paths
+            .iter()
+            .filter(|(id, summary)| {
+                summary.crate_id == LOCAL_CRATE_ID
+                    && summary.path.get(1..).is_some_and(|path| path == segments)
+                    && self.doc.index.contains_key(id)
+            })
+            .min_by_key(|(id, _summary)| **id);
+        if let Some((id, _summary)) = found {
+            return self.doc.index.get(id);
+        }
+
+        segments
+            .iter()
+            .try_fold(root, |item, segment| self.find_child(item, segment))
+    }
+
+    /// Returns the public items of the local crate with their paths relative to the crate root.
+    ///
+    /// Items re-exported with `pub use` are listed by their re-exported path.
+    /// If an item is reachable by multiple paths, the shortest one is used.
+    /// rustdoc is run with `--document-private-items`, so items that are not
+    /// reachable from the crate root through public modules are skipped.
+    pub(super) fn public_items(&self) -> Vec<(Vec<&str>, &Item)> {
+        let Some(root) = self.root_item() else {
+            return vec![];
+        };
+
+        let mut items = vec![];
+        let mut visited = HashSet::from([root.id]);
+        let mut queue = VecDeque::from([(vec![], root)]);
+        while let Some((path, module)) = queue.pop_front() {
+            let ItemEnum::Module(module) = &module.inner else {
+                continue;
+            };
+            for id in &module.items {
+                let Some(item) = self.doc.index.get(id) else {
+                    continue;
+                };
+                if item.visibility != Visibility::Public {
+                    continue;
+                }
+                let (name, item) = match &item.inner {
+                    ItemEnum::Use(use_) if !use_.is_glob => {
+                        let Some(target) = use_.id.as_ref().and_then(|id| self.doc.index.get(id))
+                        else {
+                            continue;
+                        };
+                        (use_.name.as_str(), target)
+                    }
+                    ItemEnum::Use(_) => continue,
+                    _ => {
+                        let Some(name) = item.name.as_deref() else {
+                            continue;
+                        };
+                        (name, item)
+                    }
+                };
+                if !visited.insert(item.id) {
+                    continue;
+                }
+                let mut path = path.clone();
+                path.push(name);
+                if matches!(item.inner, ItemEnum::Module(_)) {
+                    queue.push_back((path.clone(), item));
+                }
+                items.push((path, item));
+            }
+        }
+        items
+    }
+
+    fn find_child(&self, module: &Item, name: &str) -> Option<&Item> {
+        let ItemEnum::Module(module) = &module.inner else {
+            return None;
+        };
+        module.items.iter().find_map(|id| {
+            let item = self.doc.index.get(id)?;
+            match &item.inner {
+                ItemEnum::Use(use_) if !use_.is_glob && use_.name == name => {
+                    self.doc.index.get(use_.id.as_ref()?)
+                }
+                _ if item.name.as_deref() == Some(name) => Some(item),
+                _ => None,
+            }
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
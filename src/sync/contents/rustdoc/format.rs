@@ -0,0 +1,233 @@
+//! Reading rustdoc JSON of the supported format versions.
+//!
+//! [`rustdoc_types`] only reads the latest format version. JSON of older
+//! versions is upgraded by the adapters below before it is deserialized, so
+//! that a range of nightly toolchains can be used.
+
+use cargo_metadata::camino::Utf8Path;
+use rustdoc_types::{Crate, FORMAT_VERSION};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use snafu::{ResultExt as _, ensure};
+
+use crate::{
+    sync::contents::rustdoc::{
+        AdaptFormatVersionSnafu, CreateResult, FormatVersionTooNewSnafu, FormatVersionTooOldSnafu,
+    },
+    with_source::WithSource,
+};
+
+/// The oldest supported format version.
+///
+/// Format version 44 added `Crate::target`, which cannot be derived from older
+/// JSON, and format version 45 made the columns of `Span` one-indexed.
+pub(super) const MIN_FORMAT_VERSION: u32 = 45;
+
+/// The newest supported format version.
+pub(super) const MAX_FORMAT_VERSION: u32 = FORMAT_VERSION;
+
+/// Upgrades JSON of format versions before `version` to `version`.
+///
+/// Format versions without an adapter only added fields or variants that are
+/// optional when deserializing.
+struct Adapter {
+    version: u32,
+    upgrade: fn(&mut Value),
+}
+
+const ADAPTERS: &[Adapter] = &[
+    // Format version 54 changed `Item::attrs` from strings to `Attribute`.
+    Adapter {
+        version: 54,
+        upgrade: attrs_to_attributes,
+    },
+    // Format version 57 added `ExternalCrate::path`.
+    Adapter {
+        version: 57,
+        upgrade: add_external_crate_path,
+    },
+];
+
+#[derive(Debug, Deserialize)]
+struct Header {
+    format_version: u32,
+}
+
+/// Reads rustdoc JSON, checking its format version before deserializing the
+/// whole document.
+pub(super) fn read(path: &Utf8Path) -> CreateResult<Crate> {
+    let header = WithSource::open("rustdoc output", path)?;
+    let version = match trailing_format_version(header.text()) {
+        Some(version) => version,
+        None => header.parse_json::<Header>()?.value().format_version,
+    };
+    ensure!(
+        version >= MIN_FORMAT_VERSION,
+        FormatVersionTooOldSnafu {
+            path,
+            found: version,
+            min: MIN_FORMAT_VERSION,
+            max: MAX_FORMAT_VERSION,
+        }
+    );
+    ensure!(
+        version <= MAX_FORMAT_VERSION,
+        FormatVersionTooNewSnafu {
+            path,
+            found: version,
+            min: MIN_FORMAT_VERSION,
+            max: MAX_FORMAT_VERSION,
+        }
+    );
+
+    if !needs_upgrade(version) {
+        return Ok(header.parse_json::<Crate>()?.into_value());
+    }
+    tracing::debug!("upgrading rustdoc JSON from format version {version}: {path}");
+    let value = header.parse_json::<Value>()?.into_value();
+    upgrade(value, version).context(AdaptFormatVersionSnafu { path, version })
+}
+
+/// Returns the format version if it is the last field of the document, as
+/// written by rustdoc, without parsing the rest of the document.
+fn trailing_format_version(text: &str) -> Option<u32> {
+    let rest = text.trim_end().strip_suffix('}')?.trim_end();
+    let (rest, digits) = rest.split_at(rest.trim_end_matches(|c: char| c.is_ascii_digit()).len());
+    rest.trim_end()
+        .strip_suffix(':')?
+        .trim_end()
+        .strip_suffix(r#""format_version""#)?;
+    digits.parse().ok()
+}
+
+fn needs_upgrade(version: u32) -> bool {
+    ADAPTERS.iter().any(|adapter| version < adapter.version)
+}
+
+fn upgrade(mut value: Value, version: u32) -> Result<Crate, serde_json::Error> {
+    for adapter in ADAPTERS.iter().filter(|adapter| version < adapter.version) {
+        (adapter.upgrade)(&mut value);
+    }
+    serde_json::from_value(value)
+}
+
+fn object_values_mut<'a>(value: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+    value
+        .get_mut(key)
+        .and_then(Value::as_object_mut)
+        .into_iter()
+        .flat_map(Map::values_mut)
+}
+
+fn attrs_to_attributes(value: &mut Value) {
+    for item in object_values_mut(value, "index") {
+        let Some(attrs) = item.get_mut("attrs").and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for attr in attrs {
+            if attr.is_string() {
+                *attr = Value::Object(Map::from_iter([("other".to_owned(), attr.take())]));
+            }
+        }
+    }
+}
+
+fn add_external_crate_path(value: &mut Value) {
+    for krate in object_values_mut(value, "external_crates") {
+        if let Some(krate) = krate.as_object_mut() {
+            krate
+                .entry("path")
+                .or_insert_with(|| Value::String(String::new()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdoc_types::Attribute;
+    use serde_json::json;
+
+    use super::*;
+
+    fn crate_json(version: u32, attrs: &Value, external_crate: &Value) -> Value {
+        json!({
+            "root": 0,
+            "crate_version": null,
+            "includes_private": false,
+            "index": {
+                "0": {
+                    "id": 0,
+                    "crate_id": 0,
+                    "name": "foo",
+                    "span": null,
+                    "visibility": "public",
+                    "docs": "Crate docs.",
+                    "links": {},
+                    "attrs": attrs,
+                    "deprecation": null,
+                    "inner": { "module": { "is_crate": true, "items": [], "is_stripped": false } },
+                },
+            },
+            "paths": {},
+            "external_crates": { "1": external_crate },
+            "target": { "triple": "x86_64-unknown-linux-gnu", "target_features": [] },
+            "format_version": version,
+        })
+    }
+
+    #[test]
+    fn upgrade_string_attrs() {
+        let value = crate_json(
+            53,
+            &json!(["#[non_exhaustive]"]),
+            &json!({ "name": "std", "html_root_url": null }),
+        );
+        let krate = upgrade(value, 53).unwrap();
+        let root = &krate.index[&krate.root];
+        assert_eq!(
+            root.attrs,
+            [Attribute::Other("#[non_exhaustive]".to_owned())]
+        );
+        assert_eq!(krate.external_crates[&1].path.as_os_str(), "");
+    }
+
+    #[test]
+    fn upgrade_external_crate_path() {
+        let value = crate_json(
+            56,
+            &json!([{ "other": "#[doc(hidden)]" }]),
+            &json!({ "name": "std", "html_root_url": null }),
+        );
+        let krate = upgrade(value, 56).unwrap();
+        assert_eq!(krate.external_crates[&1].name, "std");
+    }
+
+    #[test]
+    fn read_trailing_format_version() {
+        assert_eq!(
+            trailing_format_version(r#"{"root":0,"format_version":57}"#),
+            Some(57)
+        );
+        assert_eq!(
+            trailing_format_version("{\n  \"root\": 0,\n  \"format_version\": 45\n}\n"),
+            Some(45)
+        );
+        assert_eq!(
+            trailing_format_version(r#"{"format_version":57,"root":0}"#),
+            None
+        );
+        assert_eq!(
+            trailing_format_version(r#"{"root":{"format_version":57}}"#),
+            None
+        );
+        assert_eq!(trailing_format_version(r#"{"format_version":}"#), None);
+    }
+
+    #[test]
+    fn latest_version_is_read_without_upgrade() {
+        assert!(needs_upgrade(MIN_FORMAT_VERSION));
+        assert!(needs_upgrade(56));
+        assert!(!needs_upgrade(57));
+        assert!(!needs_upgrade(MAX_FORMAT_VERSION));
+    }
+}
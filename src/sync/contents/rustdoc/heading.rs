@@ -1,22 +1,70 @@
-use pulldown_cmark::{Event, Tag, TagEnd};
+use pulldown_cmark::{Event, HeadingLevel, Tag, TagEnd};
 
-pub(super) fn convert<'a, 'b>(
+pub(in super::super) fn convert<'a, 'b>(
     events: impl IntoIterator<Item = Event<'a>> + 'b,
+    offset: u8,
 ) -> impl Iterator<Item = Event<'a>> + 'b {
-    use pulldown_cmark::HeadingLevel::{H1, H2, H3, H4, H5, H6};
-    events.into_iter().map(|mut event| {
+    events.into_iter().map(move |mut event| {
         match &mut event {
             Event::Start(Tag::Heading { level, .. }) | Event::End(TagEnd::Heading(level)) => {
-                *level = match level {
-                    H1 => H2,
-                    H2 => H3,
-                    H3 => H4,
-                    H4 => H5,
-                    H5 | H6 => H6,
-                }
+                *level = demote(*level, offset);
             }
             _ => {}
         }
         event
     })
 }
+
+/// Inverse of [`convert`]: promotes headings by `offset` levels.
+pub(in super::super) fn revert<'a, 'b>(
+    events: impl IntoIterator<Item = Event<'a>> + 'b,
+    offset: u8,
+) -> impl Iterator<Item = Event<'a>> + 'b {
+    events.into_iter().map(move |mut event| {
+        match &mut event {
+            Event::Start(Tag::Heading { level, .. }) | Event::End(TagEnd::Heading(level)) => {
+                *level = promote(*level, offset);
+            }
+            _ => {}
+        }
+        event
+    })
+}
+
+fn demote(level: HeadingLevel, offset: u8) -> HeadingLevel {
+    let level = (level as usize).saturating_add(offset.into());
+    HeadingLevel::try_from(level).unwrap_or(HeadingLevel::H6)
+}
+
+fn promote(level: HeadingLevel, offset: u8) -> HeadingLevel {
+    let level = (level as usize).saturating_sub(offset.into());
+    HeadingLevel::try_from(level).unwrap_or(HeadingLevel::H1)
+}
+
+#[cfg(test)]
+mod tests {
+    use pulldown_cmark::HeadingLevel::{H1, H2, H3, H5, H6};
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn demote() {
+        assert_eq!(super::demote(H1, 0), H1);
+        assert_eq!(super::demote(H1, 1), H2);
+        assert_eq!(super::demote(H2, 1), H3);
+        assert_eq!(super::demote(H5, 1), H6);
+        assert_eq!(super::demote(H6, 1), H6);
+        assert_eq!(super::demote(H1, 2), H3);
+        assert_eq!(super::demote(H3, 5), H6);
+    }
+
+    #[test]
+    fn promote() {
+        assert_eq!(super::promote(H1, 0), H1);
+        assert_eq!(super::promote(H2, 1), H1);
+        assert_eq!(super::promote(H3, 1), H2);
+        assert_eq!(super::promote(H6, 1), H5);
+        assert_eq!(super::promote(H1, 1), H1);
+        assert_eq!(super::promote(H3, 2), H1);
+        assert_eq!(super::promote(H3, 5), H1);
+    }
+}
@@ -31,6 +31,84 @@ impl<'map> LinkMappingConfig<'map> {
             .collect();
         Some(LinkMapper { docs, url_map })
     }
+
+    /// Builds a mapper for documentation read without rustdoc JSON, where
+    /// intra-doc links are only resolved by the mappings.
+    pub(super) fn build_source_mapper<'doc>(
+        &self,
+        docs: &'doc str,
+        options: Options,
+    ) -> LinkMapper<'doc, 'map> {
+        let mut url_map = HashMap::new();
+        let mut unresolved = vec![];
+        for name in intra_link_candidates(docs, options) {
+            let resolved = self
+                .mappings
+                .get(name)
+                .map(|url| ResolvedLink::Mapped(url.as_str().into()));
+            if resolved.is_none() && !unresolved.contains(&name) {
+                unresolved.push(name);
+            }
+            url_map.insert(name, resolved);
+        }
+        if !unresolved.is_empty() {
+            tracing::warn!(
+                "intra-doc links cannot be resolved without rustdoc JSON, add them to `rustdoc.mappings`: {}",
+                unresolved.join(", ")
+            );
+        }
+        LinkMapper { docs, url_map }
+    }
+}
+
+/// Returns the link destinations in `docs` that rustdoc would treat as
+/// intra-doc links.
+fn intra_link_candidates(docs: &str, options: Options) -> Vec<&str> {
+    let mut references = vec![];
+    let mut destinations = vec![];
+    let mut callback = |link: BrokenLink<'_>| {
+        references.push(link.reference.into_string());
+        None
+    };
+    let mut parser = Parser::new_with_broken_link_callback(docs, options, Some(&mut callback));
+    for event in parser.by_ref() {
+        if let Event::Start(Tag::Link {
+            link_type: LinkType::Inline,
+            dest_url,
+            ..
+        }) = event
+        {
+            destinations.push(dest_url.into_string());
+        }
+    }
+    destinations.extend(
+        parser
+            .reference_definitions()
+            .iter()
+            .map(|(_label, def)| def.dest.to_string()),
+    );
+    drop(parser);
+
+    references
+        .into_iter()
+        .chain(destinations)
+        .filter(|name| is_path_like(name))
+        // Keys of the link map borrow from the documentation, as with rustdoc JSON.
+        .filter_map(|name| docs.find(&name).map(|idx| &docs[idx..idx + name.len()]))
+        .collect()
+}
+
+/// Returns whether `link` looks like a Rust path, such as `` `crate::Foo` ``,
+/// `fn@foo` or `foo!()`.
+fn is_path_like(link: &str) -> bool {
+    let link = link.trim_matches('`');
+    let link = link.split_once('@').map_or(link, |(_ns, path)| path);
+    let link = link.trim_end_matches("()").trim_end_matches('!');
+    !link.is_empty()
+        && !link.starts_with(|c: char| c.is_ascii_digit())
+        && link
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '<' | '>' | ','))
 }
 
 #[derive(Debug)]
@@ -0,0 +1,61 @@
+use pulldown_cmark::{Event, LinkType, Tag};
+
+use crate::sync::marker::LinkStyle;
+
+pub(in super::super) fn convert<'a, 'b>(
+    events: impl IntoIterator<Item = Event<'a>> + 'b,
+    style: LinkStyle,
+) -> impl Iterator<Item = Event<'a>> + 'b {
+    events.into_iter().map(move |mut event| {
+        if let (LinkStyle::Inline, Event::Start(Tag::Link { link_type, .. })) = (style, &mut event)
+            && matches!(
+                link_type,
+                LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut
+            )
+        {
+            *link_type = LinkType::Inline;
+        }
+        event
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    fn convert_str(input: &str, style: LinkStyle) -> String {
+        let events: Vec<_> = pulldown_cmark::Parser::new(input).collect();
+        let mut output = String::new();
+        pulldown_cmark_to_cmark::cmark(convert(events, style), &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn convert_links() {
+        let input = indoc! {r#"
+            [foo] [bar][] [text][baz] [inline](https://example.com/inline) <https://example.com/auto>
+
+            [foo]: https://example.com/foo
+            [bar]: https://example.com/bar "Bar"
+            [baz]: https://example.com/baz
+        "#};
+
+        assert_eq!(
+            convert_str(input, LinkStyle::Inline),
+            indoc! {r#"
+            [foo](https://example.com/foo) [bar](https://example.com/bar "Bar") [text](https://example.com/baz) [inline](https://example.com/inline) <https://example.com/auto>"#}
+        );
+        assert_eq!(
+            convert_str(input, LinkStyle::Reference),
+            indoc! {r#"
+                [foo] [bar][] [text][baz] [inline](https://example.com/inline) <https://example.com/auto>
+
+                [foo]: https://example.com/foo
+                [bar]: https://example.com/bar "Bar"
+                [baz]: https://example.com/baz"#}
+        );
+    }
+}
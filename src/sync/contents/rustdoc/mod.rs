@@ -2,30 +2,46 @@ use std::{
     ffi::OsString,
     io::{self, BufReader},
     process::{ExitStatus, Stdio},
+    sync::Arc,
 };
 
-use cargo_metadata::{Message, Package, PackageName, camino::Utf8PathBuf};
-use pulldown_cmark::Options;
+use cargo_metadata::{
+    Message, Metadata, Package, PackageName, Target,
+    camino::{Utf8Path, Utf8PathBuf},
+};
+use miette::{NamedSource, SourceSpan};
+use pulldown_cmark::{Event, Options, Parser};
 use snafu::{OptionExt as _, ResultExt as _, Snafu, ensure};
 use tracing::Level;
 
 use crate::{
-    cargo,
+    cargo, output,
+    parse::Spanned,
     sync::{
-        ManifestFile, SyncOptions,
+        ManifestFile, MarkdownFile, SyncOptions,
         contents::rustdoc::{
+            crate_docs::LinkUnmapper,
             document::{BuildUrlOptions, RustdocDocument},
             intra_link::LinkMappingConfig,
         },
+        marker::{ApiSummaryOptions, RustdocOptions},
     },
-    traits::CommandExt as _,
-    with_source::{ReadFileError, WithSource},
+    traits::{CommandExt as _, PackageExt as _},
+    with_source::ReadFileError,
 };
 
+pub(crate) use self::cache::RustdocCache;
+
+mod api_summary;
+mod cache;
 mod code_block;
+mod crate_docs;
 mod document;
-mod heading;
+mod format;
+pub(super) mod heading;
 mod intra_link;
+pub(super) mod link;
+mod source;
 
 type CreateResult<T> = Result<T, CreateRustdocError>;
 
@@ -61,6 +77,79 @@ pub(in super::super) enum CreateRustdocError {
         commandline: OsString,
         files: Vec<Utf8PathBuf>,
     },
+    #[snafu(display("rustdoc JSON format version {found} is older than supported: {path}"))]
+    #[diagnostic(help(
+        "supported format versions are {min} to {max}; the nightly toolchain is too old, update it with `rustup update nightly`"
+    ))]
+    FormatVersionTooOld {
+        path: Utf8PathBuf,
+        found: u32,
+        min: u32,
+        max: u32,
+    },
+    #[snafu(display("rustdoc JSON format version {found} is newer than supported: {path}"))]
+    #[diagnostic(help(
+        "supported format versions are {min} to {max}; the nightly toolchain is too new, use an older one such as `--toolchain nightly-<date>` or update cargo-sync-rdme"
+    ))]
+    FormatVersionTooNew {
+        path: Utf8PathBuf,
+        found: u32,
+        min: u32,
+        max: u32,
+    },
+    #[snafu(display("failed to read rustdoc JSON of format version {version}: {path}"))]
+    AdaptFormatVersion {
+        path: Utf8PathBuf,
+        version: u32,
+        #[snafu(source)]
+        source: serde_json::Error,
+    },
+    #[snafu(display(
+        "rustdoc JSON of crate `{found}` does not match package {package_name} (expected crate `{expected}`): {path}"
+    ))]
+    CrateNameMismatch {
+        path: Utf8PathBuf,
+        found: String,
+        expected: String,
+        package_name: PackageName,
+    },
+    #[snafu(display("{what} requires rustdoc JSON, which is not read with `--docs-from-source`"))]
+    RustdocJsonRequired { what: &'static str },
+    #[snafu(display("package {package_name} does not have a library or binary target"))]
+    NoCrateTarget { package_name: PackageName },
+    #[snafu(display("failed to read crate root: {path}"))]
+    ReadCrateRoot {
+        path: Utf8PathBuf,
+        #[snafu(source)]
+        source: io::Error,
+    },
+    #[snafu(display("failed to parse crate root: {path}"))]
+    ParseCrateRoot {
+        path: Utf8PathBuf,
+        #[snafu(source(from(syn::Error, Box::new)))]
+        source: Box<syn::Error>,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label("{source}")]
+        label: SourceSpan,
+    },
+    #[snafu(display("unsupported `doc` attribute in crate root: {path}"))]
+    #[diagnostic(help(
+        "only string literals, `concat!`, `env!(\"CARGO_MANIFEST_DIR\")`, and `include_str!` of them are supported in `doc` attributes with `--docs-from-source`"
+    ))]
+    UnsupportedDocAttribute {
+        path: Utf8PathBuf,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        label: SourceSpan,
+    },
+    #[snafu(display("failed to read file included in crate documentation: {path}"))]
+    ReadIncludedDoc {
+        path: Utf8PathBuf,
+        #[snafu(source)]
+        source: io::Error,
+    },
     #[snafu(transparent)]
     #[diagnostic(transparent)]
     ReadFileError {
@@ -72,6 +161,27 @@ pub(in super::super) enum CreateRustdocError {
     RootNotFound { package_name: PackageName },
     #[snafu(display("package {package_name} does not have crate-level documentation"))]
     RootDocNotFound { package_name: PackageName },
+    #[snafu(display("item `{path}` not found in package {package_name}"))]
+    #[diagnostic(help(
+        "specify the path of a public item relative to the crate root, e.g. `module::Type`"
+    ))]
+    ItemNotFound {
+        path: Arc<str>,
+        package_name: PackageName,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("item `{path}` in package {package_name} does not have documentation"))]
+    ItemDocNotFound {
+        path: Arc<str>,
+        package_name: PackageName,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
     #[snafu(display("failed to determine the Rust toolchain version"))]
     DetermineToolchain {
         #[snafu(source)]
@@ -81,47 +191,181 @@ pub(in super::super) enum CreateRustdocError {
 }
 
 pub(super) fn create(
+    markdown: &MarkdownFile<'_>,
     manifest: &ManifestFile,
+    workspace: &Metadata,
     package: &Package,
     options: &SyncOptions<'_>,
+    item: Option<&Spanned<Arc<str>>>,
+    rustdoc_options: &RustdocOptions,
 ) -> CreateResult<String> {
+    if options.toolchain.docs_from_source {
+        ensure!(
+            item.is_none(),
+            RustdocJsonRequiredSnafu {
+                what: "`rustdoc:<path>` marker"
+            }
+        );
+        return create_from_source(manifest, package, rustdoc_options);
+    }
+
     let config = manifest.value().config();
-    let local_html_root_url = config
-        .rustdoc
-        .html_root_url
-        .clone()
-        .unwrap_or_else(|| format!("https://docs.rs/{}/{}", package.name, package.version));
-    let expected_toolchain = cargo::toolchain(None).context(DetermineToolchainSnafu)?;
-    let rustdoc_toolchain =
-        cargo::toolchain(Some(options.toolchain)).context(DetermineToolchainSnafu)?;
-    let build_url_options = BuildUrlOptions {
-        local_html_root_url: &local_html_root_url,
-        expected_toolchain,
-        rustdoc_toolchain,
+    let local_html_root_url = local_html_root_url(manifest, package);
+    let build_url_options = build_url_options(&local_html_root_url, options)?;
+    let mapping_config = LinkMappingConfig {
+        mappings: &config.rustdoc.mappings,
     };
 
+    let doc = options
+        .rustdoc_cache
+        .document(workspace, manifest, package, options)?;
+    let root = doc.root_item().with_context(|| RootNotFoundSnafu {
+        package_name: package.name.clone(),
+    })?;
+
+    let resolver = doc.intra_link_resolver(&build_url_options);
+    let mapper = if let Some(item) = item {
+        let target = doc
+            .find_item(&item.value)
+            .with_context(|| ItemNotFoundSnafu {
+                path: Arc::clone(&item.value),
+                package_name: package.name.clone(),
+                source_code: markdown.to_named_source(),
+                span: item.source_span(),
+            })?;
+        mapping_config
+            .build_mapper(&resolver, target)
+            .with_context(|| ItemDocNotFoundSnafu {
+                path: Arc::clone(&item.value),
+                package_name: package.name.clone(),
+                source_code: markdown.to_named_source(),
+                span: item.source_span(),
+            })?
+    } else {
+        mapping_config
+            .build_mapper(&resolver, root)
+            .with_context(|| RootDocNotFoundSnafu {
+                package_name: package.name.clone(),
+            })?
+    };
+
+    Ok(render(
+        mapper.build_parser(main_body_opts()),
+        rustdoc_options,
+    ))
+}
+
+/// Creates crate documentation from the source of the crate root, resolving
+/// intra-doc links only by `rustdoc.mappings`.
+fn create_from_source(
+    manifest: &ManifestFile,
+    package: &Package,
+    rustdoc_options: &RustdocOptions,
+) -> CreateResult<String> {
+    let target = crate_target(package).with_context(|| NoCrateTargetSnafu {
+        package_name: package.name.clone(),
+    })?;
+    let docs = source::read_crate_docs(&target.src_path, package.root_directory())?;
+    let mapping_config = LinkMappingConfig {
+        mappings: &manifest.value().config().rustdoc.mappings,
+    };
+    let mapper = mapping_config.build_source_mapper(&docs, main_body_opts());
+    Ok(render(
+        mapper.build_parser(main_body_opts()),
+        rustdoc_options,
+    ))
+}
+
+fn render<'a>(events: impl Iterator<Item = Event<'a>>, rustdoc_options: &RustdocOptions) -> String {
+    let events = heading::convert(events, rustdoc_options.heading_offset());
+    let events = code_block::convert(events);
+    let events = link::convert(events, rustdoc_options.links());
+
+    let mut buf = String::new();
+    pulldown_cmark_to_cmark::cmark(events, &mut buf).unwrap();
+    if !buf.is_empty() && !buf.ends_with('\n') {
+        buf.push('\n');
+    }
+    buf
+}
+
+pub(super) fn create_api_summary(
+    manifest: &ManifestFile,
+    workspace: &Metadata,
+    package: &Package,
+    options: &SyncOptions<'_>,
+    summary_options: &ApiSummaryOptions,
+) -> CreateResult<String> {
+    ensure!(
+        !options.toolchain.docs_from_source,
+        RustdocJsonRequiredSnafu {
+            what: "`api-summary` marker"
+        }
+    );
+    let config = manifest.value().config();
+    let local_html_root_url = local_html_root_url(manifest, package);
+    let build_url_options = build_url_options(&local_html_root_url, options)?;
     let mapping_config = LinkMappingConfig {
         mappings: &config.rustdoc.mappings,
     };
 
-    let output_file = run_rustdoc(package, options)?;
+    let doc = options
+        .rustdoc_cache
+        .document(workspace, manifest, package, options)?;
+    ensure!(
+        doc.root_item().is_some(),
+        RootNotFoundSnafu {
+            package_name: package.name.clone(),
+        }
+    );
+
+    let resolver = doc.intra_link_resolver(&build_url_options);
+    Ok(api_summary::render(
+        &doc,
+        &resolver,
+        &mapping_config,
+        summary_options,
+    ))
+}
 
-    let doc = WithSource::from_json("rustdoc output", output_file)?.into_value();
-    let doc = RustdocDocument::new(doc);
+/// Converts the contents of a `rustdoc` marker region back into crate-level documentation.
+///
+/// This is the inverse of [`create`]: headings are promoted by the heading
+/// offset of the marker, and links to the documentation of items are rewritten
+/// into intra-doc links.
+pub(super) fn create_crate_docs(
+    text: &str,
+    manifest: &ManifestFile,
+    workspace: &Metadata,
+    package: &Package,
+    options: &SyncOptions<'_>,
+    rustdoc_options: &RustdocOptions,
+) -> CreateResult<String> {
+    ensure!(
+        !options.toolchain.docs_from_source,
+        RustdocJsonRequiredSnafu {
+            what: "reverse synchronization"
+        }
+    );
+
+    let config = manifest.value().config();
+    let local_html_root_url = local_html_root_url(manifest, package);
+    let build_url_options = build_url_options(&local_html_root_url, options)?;
+
+    let doc = options
+        .rustdoc_cache
+        .document(workspace, manifest, package, options)?;
     let root = doc.root_item().with_context(|| RootNotFoundSnafu {
         package_name: package.name.clone(),
     })?;
 
     let resolver = doc.intra_link_resolver(&build_url_options);
-    let mapper = mapping_config
-        .build_mapper(&resolver, root)
-        .with_context(|| RootDocNotFoundSnafu {
-            package_name: package.name.clone(),
-        })?;
+    let unmapper = LinkUnmapper::new(&doc, &resolver, root, &config.rustdoc.mappings);
 
-    let events = mapper.build_parser(main_body_opts());
-    let events = heading::convert(events);
-    let events = code_block::convert(events);
+    // Smart punctuation is not reverted, so keep the punctuation as written in the README.
+    let events = Parser::new_ext(text, main_body_opts() - Options::ENABLE_SMART_PUNCTUATION);
+    let events = heading::revert(events, rustdoc_options.heading_offset());
+    let events = unmapper.convert(events);
 
     let mut buf = String::new();
     pulldown_cmark_to_cmark::cmark(events, &mut buf).unwrap();
@@ -131,6 +375,84 @@ pub(super) fn create(
     Ok(buf)
 }
 
+fn local_html_root_url(manifest: &ManifestFile, package: &Package) -> String {
+    manifest
+        .value()
+        .config()
+        .rustdoc
+        .html_root_url
+        .clone()
+        .unwrap_or_else(|| format!("https://docs.rs/{}/{}", package.name, package.version))
+}
+
+fn build_url_options<'a>(
+    local_html_root_url: &'a str,
+    options: &SyncOptions<'_>,
+) -> CreateResult<BuildUrlOptions<'a>> {
+    let toolchains = options.rustdoc_cache.toolchains(options)?;
+    Ok(BuildUrlOptions {
+        local_html_root_url,
+        expected_toolchain: toolchains.expected.clone(),
+        rustdoc_toolchain: toolchains.rustdoc.clone(),
+    })
+}
+
+/// Returns the path of the pre-built rustdoc JSON of the package, if specified
+/// by `--rustdoc-json` or `rustdoc.json` in the package configuration.
+fn prebuilt_json_path(
+    manifest: &ManifestFile,
+    package: &Package,
+    options: &SyncOptions<'_>,
+) -> Option<Utf8PathBuf> {
+    if let Some(path) = &options.toolchain.rustdoc_json {
+        if path.is_dir() {
+            return Some(path.join(format!("{}.json", crate_name(package))));
+        }
+        return Some(path.clone());
+    }
+    let path = manifest.value().config().rustdoc.json.as_deref()?;
+    Some(package.root_directory().join(path))
+}
+
+/// Returns the target documented by `cargo rustdoc`, which is the library
+/// target, or the binary target if the package has no library.
+fn crate_target(package: &Package) -> Option<&Target> {
+    let targets = &package.targets;
+    targets
+        .iter()
+        .find(|target| target.is_lib() || target.is_rlib() || target.is_proc_macro())
+        .or_else(|| targets.iter().find(|target| target.is_bin()))
+}
+
+/// Returns the name of the crate documented by `cargo rustdoc`.
+fn crate_name(package: &Package) -> String {
+    crate_target(package)
+        .map_or(package.name.as_str(), |target| target.name.as_str())
+        .replace('-', "_")
+}
+
+fn ensure_crate_name(
+    doc: &RustdocDocument,
+    path: &Utf8Path,
+    package: &Package,
+) -> CreateResult<()> {
+    // A missing root item is reported when the document is used.
+    let Some(found) = doc.root_item().and_then(|root| root.name.as_deref()) else {
+        return Ok(());
+    };
+    let expected = crate_name(package);
+    ensure!(
+        found == expected,
+        CrateNameMismatchSnafu {
+            path,
+            found,
+            expected,
+            package_name: package.name.clone(),
+        }
+    );
+    Ok(())
+}
+
 fn run_rustdoc(package: &Package, options: &SyncOptions<'_>) -> CreateResult<Utf8PathBuf> {
     let mut command = cargo::command_for_build_doc(options.toolchain);
     match options.verbosity {
@@ -160,6 +482,7 @@ fn run_rustdoc(package: &Package, options: &SyncOptions<'_>) -> CreateResult<Utf
             "--document-private-items",
         ])
         .stdout(Stdio::piped());
+    output::pipe_stderr(&mut command, options.diff_stream);
 
     let commandline = command.commandline();
     tracing::debug!("executing rustdoc command: {}", commandline.display());
@@ -169,6 +492,7 @@ fn run_rustdoc(package: &Package, options: &SyncOptions<'_>) -> CreateResult<Utf
             commandline: &commandline,
         })?;
 
+    let stderr = output::read_in_background(child.stderr.take().unwrap());
     let stdout = BufReader::new(child.stdout.take().unwrap());
     let mut output_files = vec![];
     for message in Message::parse_stream(stdout) {
@@ -189,6 +513,7 @@ fn run_rustdoc(package: &Package, options: &SyncOptions<'_>) -> CreateResult<Utf
     let status = child.wait().context(WaitRustdocProcessSnafu {
         commandline: &commandline,
     })?;
+    output::write_child_output(options.diff_stream, &stderr.join().unwrap_or_default());
     ensure!(
         status.success(),
         NonZeroExitStatusSnafu {
@@ -0,0 +1,337 @@
+//! Crate documentation read from the source of the crate root.
+//!
+//! This is used instead of rustdoc JSON with `--docs-from-source`, so that
+//! crate documentation can be synchronized without a nightly toolchain.
+
+use std::{fs, sync::Arc};
+
+use cargo_metadata::camino::Utf8Path;
+use miette::{NamedSource, SourceSpan};
+use proc_macro2::LineColumn;
+use snafu::ResultExt as _;
+use syn::{
+    Attribute, Expr, ExprLit, Lit, LitStr, Meta, Token, punctuated::Punctuated,
+    spanned::Spanned as _,
+};
+
+use crate::sync::contents::rustdoc::{
+    CreateResult, ParseCrateRootSnafu, ReadCrateRootSnafu, ReadIncludedDocSnafu,
+    UnsupportedDocAttributeSnafu,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FragmentKind {
+    /// `//! ...` or `/*! ... */`.
+    SugaredDoc,
+    /// `#![doc = ...]`.
+    RawDoc,
+}
+
+#[derive(Debug)]
+struct Fragment {
+    kind: FragmentKind,
+    doc: String,
+    indent: usize,
+}
+
+/// Reads the crate-level documentation from the inner `doc` attributes and
+/// doc comments of the crate root.
+///
+/// `manifest_dir` is the value of `env!("CARGO_MANIFEST_DIR")`.
+pub(super) fn read_crate_docs(path: &Utf8Path, manifest_dir: &Utf8Path) -> CreateResult<String> {
+    let text: Arc<str> = fs::read_to_string(path)
+        .context(ReadCrateRootSnafu { path })?
+        .into();
+    let file = syn::parse_file(&text).with_context(|source| ParseCrateRootSnafu {
+        path,
+        source_code: NamedSource::new(path, Arc::clone(&text)),
+        label: span(&text, source.span()),
+    })?;
+
+    let mut fragments = vec![];
+    for attr in &file.attrs {
+        if let Some(fragment) = fragment(attr, path, manifest_dir, &text)? {
+            fragments.push(fragment);
+        }
+    }
+    unindent(&mut fragments);
+
+    let mut docs = String::new();
+    for fragment in &fragments {
+        push_fragment(&mut docs, fragment);
+    }
+    docs.pop();
+    Ok(docs)
+}
+
+fn fragment(
+    attr: &Attribute,
+    path: &Utf8Path,
+    manifest_dir: &Utf8Path,
+    text: &Arc<str>,
+) -> CreateResult<Option<Fragment>> {
+    // `#![doc(html_root_url = "...")]` and other list forms do not contain documentation.
+    let Meta::NameValue(meta) = &attr.meta else {
+        return Ok(None);
+    };
+    if !meta.path.is_ident("doc") {
+        return Ok(None);
+    }
+
+    let offset = offset(text, attr.span().start());
+    let sugared = text[offset..].starts_with("//!") || text[offset..].starts_with("/*!");
+    let unsupported = || {
+        UnsupportedDocAttributeSnafu {
+            path,
+            source_code: NamedSource::new(path, Arc::clone(text)),
+            label: span(text, attr.span()),
+        }
+        .build()
+    };
+
+    let doc = match &meta.value {
+        Expr::Macro(expr) if expr.mac.path.is_ident("include_str") => {
+            let Some(file) = expr
+                .mac
+                .parse_body::<Expr>()
+                .ok()
+                .and_then(|arg| eval_str(&arg, manifest_dir))
+            else {
+                return Err(unsupported());
+            };
+            let dir = path.parent().unwrap_or(path);
+            let included = dir.join(file);
+            fs::read_to_string(&included).context(ReadIncludedDocSnafu { path: included })?
+        }
+        value => eval_str(value, manifest_dir).ok_or_else(unsupported)?,
+    };
+
+    let (kind, doc) = if sugared {
+        let block = text[offset..].starts_with("/*!");
+        let doc = if block { beautify_block(&doc) } else { doc };
+        (FragmentKind::SugaredDoc, doc)
+    } else {
+        (FragmentKind::RawDoc, doc)
+    };
+    Ok(Some(Fragment {
+        kind,
+        doc,
+        indent: 0,
+    }))
+}
+
+/// Evaluates a string literal, `concat!` of them, or `env!("CARGO_MANIFEST_DIR")`.
+fn eval_str(expr: &Expr, manifest_dir: &Utf8Path) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Some(lit.value()),
+        Expr::Macro(expr) if expr.mac.path.is_ident("concat") => expr
+            .mac
+            .parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+            .ok()?
+            .iter()
+            .map(|arg| eval_str(arg, manifest_dir))
+            .collect(),
+        Expr::Macro(expr) if expr.mac.path.is_ident("env") => {
+            let name = expr.mac.parse_body::<LitStr>().ok()?;
+            (name.value() == "CARGO_MANIFEST_DIR").then(|| manifest_dir.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the byte offset of a line and column (in characters) in `text`.
+fn offset(text: &str, position: LineColumn) -> usize {
+    let line_start = text
+        .split_inclusive('\n')
+        .take(position.line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+    text[line_start..]
+        .char_indices()
+        .nth(position.column)
+        .map_or(text.len(), |(idx, _)| line_start + idx)
+}
+
+fn span(text: &str, span: proc_macro2::Span) -> SourceSpan {
+    let start = offset(text, span.start());
+    let end = offset(text, span.end()).max(start);
+    SourceSpan::from(start..end)
+}
+
+/// Strips the leading and trailing blank lines and the common ` * ` prefix of
+/// block doc comments, as rustdoc does.
+fn beautify_block(doc: &str) -> String {
+    let mut lines = doc.lines().collect::<Vec<_>>();
+    if lines.first().is_some_and(|line| line.trim().is_empty()) {
+        lines.remove(0);
+    }
+    if lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    let starred = !lines.is_empty() && lines.iter().all(|line| line.trim_start().starts_with('*'));
+    let lines = lines.into_iter().map(|line| {
+        if starred {
+            line.trim_start().strip_prefix('*').unwrap_or(line)
+        } else {
+            line
+        }
+    });
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+// Same algorithm as rustdoc uses to remove the common indentation of the fragments.
+// <https://github.com/rust-lang/rust/blob/153ecc4f74035b709bb3e1eb9546f1d934865042/compiler/rustc_resolve/src/rustdoc.rs#L89-L162>
+fn unindent(fragments: &mut [Fragment]) {
+    // Raw fragments are counted with one more space of indentation when they
+    // are mixed with sugared ones, which usually start with a space.
+    let add = usize::from(
+        fragments
+            .windows(2)
+            .any(|pair| pair[0].kind != pair[1].kind)
+            && fragments
+                .iter()
+                .any(|fragment| fragment.kind == FragmentKind::SugaredDoc),
+    );
+
+    let Some(min_indent) = fragments
+        .iter()
+        .map(|fragment| {
+            fragment
+                .doc
+                .lines()
+                .filter(|line| line.chars().any(|c| !c.is_whitespace()))
+                .map(|line| {
+                    let whitespace = line.chars().take_while(|c| *c == ' ' || *c == '\t').count();
+                    whitespace
+                        + if fragment.kind == FragmentKind::SugaredDoc {
+                            0
+                        } else {
+                            add
+                        }
+                })
+                .min()
+                .unwrap_or(usize::MAX)
+        })
+        .min()
+    else {
+        return;
+    };
+
+    for fragment in fragments {
+        if fragment.doc.is_empty() {
+            continue;
+        }
+        fragment.indent = if fragment.kind != FragmentKind::SugaredDoc && min_indent > 0 {
+            min_indent - add
+        } else {
+            min_indent
+        };
+    }
+}
+
+fn push_fragment(out: &mut String, fragment: &Fragment) {
+    if fragment.doc.is_empty() {
+        out.push('\n');
+        return;
+    }
+    for line in fragment.doc.lines() {
+        if line.chars().any(|c| !c.is_whitespace()) {
+            out.push_str(line.get(fragment.indent..).unwrap_or(line.trim_start()));
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cargo_metadata::camino::Utf8PathBuf;
+    use indoc::indoc;
+
+    use super::*;
+
+    fn read(source: &str, files: &[(&str, &str)]) -> String {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = Utf8PathBuf::try_from(tempdir.path().to_owned()).unwrap();
+        for (name, contents) in files {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+        let path = dir.join("lib.rs");
+        fs::write(&path, source).unwrap();
+        read_crate_docs(&path, &dir).unwrap()
+    }
+
+    #[test]
+    fn doc_comments() {
+        let docs = read(
+            indoc! {r#"
+                //! # Title
+                //!
+                //! Some text.
+                //!
+                //! ```
+                //!     indented();
+                //! ```
+                #![warn(missing_docs)]
+                #![doc(html_root_url = "https://docs.rs/foo/0.1.0")]
+
+                /// Item docs.
+                pub fn foo() {}
+            "#},
+            &[],
+        );
+        assert_eq!(docs, "# Title\n\nSome text.\n\n```\n    indented();\n```");
+    }
+
+    #[test]
+    fn raw_doc_attributes() {
+        let docs = read(
+            indoc! {r#"
+                #![doc = include_str!("README.md")]
+                //! Appended.
+                #![doc = "Raw."]
+            "#},
+            &[("README.md", "# Readme\n\nText.\n")],
+        );
+        assert_eq!(docs, "# Readme\n\nText.\nAppended.\nRaw.");
+    }
+
+    #[test]
+    fn include_str_in_manifest_dir() {
+        let docs = read(
+            indoc! {r#"
+                #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+                #![doc = concat!("Raw", ".")]
+            "#},
+            &[("README.md", "# Readme\n")],
+        );
+        assert_eq!(docs, "# Readme\nRaw.");
+    }
+
+    #[test]
+    fn block_doc_comments() {
+        let docs = read(
+            indoc! {"
+                /*!
+                 * Block docs.
+                 *
+                 * More.
+                 */
+            "},
+            &[],
+        );
+        assert_eq!(docs, "Block docs.\n\nMore.");
+    }
+
+    #[test]
+    fn unsupported_doc_attribute() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Utf8PathBuf::try_from(dir.path().join("lib.rs")).unwrap();
+        fs::write(&path, "#![doc = env!(\"DOCS\")]\n").unwrap();
+        let err = read_crate_docs(&path, path.parent().unwrap()).unwrap_err();
+        assert!(err.to_string().starts_with("unsupported `doc` attribute"));
+    }
+}
@@ -0,0 +1,230 @@
+use std::{fs, io, sync::Arc};
+
+use cargo_metadata::{Metadata, Package, camino::Utf8PathBuf};
+use miette::{NamedSource, SourceSpan};
+use snafu::{OptionExt as _, ResultExt as _, Snafu};
+
+use crate::{
+    parse::Spanned,
+    sync::{MarkdownFile, contents::include},
+    traits::PackageExt as _,
+};
+
+const ANCHOR_START: &str = "ANCHOR:";
+const ANCHOR_END: &str = "ANCHOR_END:";
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+pub(in super::super) enum CreateSnippetError {
+    #[snafu(display("failed to read snippet file: {path}"))]
+    ReadSnippetFile {
+        path: Utf8PathBuf,
+        #[snafu(source)]
+        source: io::Error,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("snippet file is outside of the workspace: {path}"))]
+    #[diagnostic(help("only files in the workspace root directory can be used as snippets"))]
+    SnippetFileOutsideWorkspace {
+        path: Utf8PathBuf,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("anchor `{anchor}` not found in {path}"))]
+    #[diagnostic(help(
+        "delimit the snippet with `// ANCHOR: {anchor}` and `// ANCHOR_END: {anchor}` comments"
+    ))]
+    AnchorNotFound {
+        anchor: Arc<str>,
+        path: Utf8PathBuf,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("anchor `{anchor}` is not closed in {path}"))]
+    #[diagnostic(help("add `// ANCHOR_END: {anchor}` after the end of the snippet"))]
+    UnclosedAnchor {
+        anchor: Arc<str>,
+        path: Utf8PathBuf,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+}
+
+pub(super) fn create(
+    markdown: &MarkdownFile<'_>,
+    workspace: &Metadata,
+    package: &Package,
+    path: &Spanned<Arc<str>>,
+    anchor: &Spanned<Arc<str>>,
+    lang: Option<&str>,
+) -> Result<String, CreateSnippetError> {
+    let relative_path = package
+        .workspace_relative_root_directory(workspace)
+        .join(&*path.value);
+    let read_error = || ReadSnippetFileSnafu {
+        path: relative_path.clone(),
+        source_code: markdown.to_named_source(),
+        span: path.source_span(),
+    };
+    let full_path = include::workspace_file(&workspace.workspace_root, &relative_path)
+        .with_context(|_source| read_error())?
+        .with_context(|| SnippetFileOutsideWorkspaceSnafu {
+            path: relative_path.clone(),
+            source_code: markdown.to_named_source(),
+            span: path.source_span(),
+        })?;
+    let text = fs::read_to_string(full_path).with_context(|_source| read_error())?;
+
+    let lines = match extract_anchor(&text, &anchor.value) {
+        Ok(lines) => lines,
+        Err(ExtractError::NotFound) => {
+            return AnchorNotFoundSnafu {
+                anchor: Arc::clone(&anchor.value),
+                path: relative_path,
+                source_code: markdown.to_named_source(),
+                span: anchor.source_span(),
+            }
+            .fail();
+        }
+        Err(ExtractError::Unclosed) => {
+            return UnclosedAnchorSnafu {
+                anchor: Arc::clone(&anchor.value),
+                path: relative_path,
+                source_code: markdown.to_named_source(),
+                span: anchor.source_span(),
+            }
+            .fail();
+        }
+    };
+
+    let lang = lang
+        .or_else(|| relative_path.extension().map(include::lang_from_extension))
+        .unwrap_or_default();
+    Ok(include::code_block(&dedent(&lines), lang))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExtractError {
+    NotFound,
+    Unclosed,
+}
+
+/// Extracts the lines between `ANCHOR: name` and `ANCHOR_END: name`.
+///
+/// Lines of other anchors in the region are removed.
+fn extract_anchor<'a>(text: &'a str, name: &str) -> Result<Vec<&'a str>, ExtractError> {
+    let mut lines = text.lines();
+    lines
+        .by_ref()
+        .find(|line| parse_anchor(line, ANCHOR_START) == Some(name))
+        .ok_or(ExtractError::NotFound)?;
+
+    let mut snippet = vec![];
+    for line in lines {
+        if parse_anchor(line, ANCHOR_END) == Some(name) {
+            return Ok(snippet);
+        }
+        if parse_anchor(line, ANCHOR_START).is_some() || parse_anchor(line, ANCHOR_END).is_some() {
+            continue;
+        }
+        snippet.push(line);
+    }
+    Err(ExtractError::Unclosed)
+}
+
+/// Returns the name of the anchor if `line` contains `prefix` followed by a name.
+fn parse_anchor<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    let (_, rest) = line.split_once(prefix)?;
+    let rest = rest.trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(rest.len());
+    let name = &rest[..end];
+    (!name.is_empty()).then_some(name)
+}
+
+/// Joins `lines` after removing the common indentation.
+///
+/// The indentation is the longest prefix of spaces and tabs shared by all
+/// non-blank lines.
+fn dedent(lines: &[&str]) -> String {
+    let mut indent: Option<&str> = None;
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        let leading = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        let common = indent.map_or(leading.len(), |indent| {
+            indent
+                .bytes()
+                .zip(leading.bytes())
+                .take_while(|(a, b)| a == b)
+                .count()
+        });
+        indent = Some(&leading[..common]);
+    }
+    let indent = indent.unwrap_or_default();
+    let mut output = String::new();
+    for line in lines {
+        if !line.trim().is_empty() {
+            output.push_str(line.strip_prefix(indent).unwrap_or(line));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    static SOURCE: &str = indoc::indoc! {"
+        fn main() {
+            // ANCHOR: all
+            // ANCHOR: setup
+            let x = 1;
+            // ANCHOR_END: setup
+
+            if x > 0 {
+                println!(\"{x}\");
+            }
+            // ANCHOR_END: all
+            // ANCHOR: unclosed
+        }
+    "};
+
+    #[test]
+    fn extract_anchor_extracts_region() {
+        let extract = |name| dedent(&extract_anchor(SOURCE, name).unwrap());
+        assert_eq!(extract("setup"), "let x = 1;\n");
+        assert_eq!(
+            extract("all"),
+            "let x = 1;\n\nif x > 0 {\n    println!(\"{x}\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn dedent_removes_common_spaces_and_tabs() {
+        assert_eq!(dedent(&["    a", "      b", "", "    c"]), "a\n  b\n\nc\n");
+        assert_eq!(dedent(&["\t a", "\t\tb"]), " a\n\tb\n");
+        assert_eq!(dedent(&["\ta", "    b"]), "\ta\n    b\n");
+        assert_eq!(dedent(&["\u{3000}a", " b"]), "\u{3000}a\n b\n");
+    }
+
+    #[test]
+    fn extract_anchor_rejects_missing_anchors() {
+        assert_eq!(extract_anchor(SOURCE, "foo"), Err(ExtractError::NotFound));
+        assert_eq!(extract_anchor(SOURCE, "set"), Err(ExtractError::NotFound));
+        assert_eq!(
+            extract_anchor(SOURCE, "unclosed"),
+            Err(ExtractError::Unclosed)
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use cargo_metadata::Package;
+use miette::NamedSource;
+use snafu::Snafu;
+
+use crate::sync::{
+    ManifestFile,
+    template::{self, RenderTemplateError, Variables},
+};
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+#[snafu(display("failed to render template `{name}`"))]
+pub(in super::super) struct CreateTemplateError {
+    name: Arc<str>,
+    #[source_code]
+    source_code: NamedSource<Arc<str>>,
+    #[related]
+    errors: Vec<RenderTemplateError>,
+}
+
+/// Renders the template `package.metadata.cargo-sync-rdme.templates.<name>`.
+pub(super) fn create(
+    manifest: &ManifestFile,
+    package: &Package,
+    name: &Arc<str>,
+) -> Result<String, CreateTemplateError> {
+    let template = &manifest.value().config().templates[&**name];
+    let variables = Variables::from_package(package);
+    let input = manifest.map(|_| template).spanned_str();
+    let mut output = template::render(input, &variables).map_err(|errors| {
+        CreateTemplateSnafu {
+            name: Arc::clone(name),
+            source_code: manifest.to_named_source(),
+            errors,
+        }
+        .build()
+    })?;
+    if !output.is_empty() && !output.ends_with('\n') {
+        output.push('\n');
+    }
+    Ok(output)
+}
@@ -0,0 +1,243 @@
+use std::{collections::HashMap, fmt::Write as _};
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+use crate::{config::metadata::TargetFormat, sync::marker::TocOptions};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Heading {
+    level: u8,
+    /// Heading contents written in Markdown.
+    markdown: String,
+    /// Plain text of the heading, used to match excluded headings.
+    text: String,
+    anchor: String,
+}
+
+/// Creates a table of contents from the headings in `text`.
+///
+/// `text` must be the synced Markdown text, so that headings generated by other
+/// markers are also listed.
+pub(super) fn create(text: &str, options: &TocOptions) -> String {
+    let min_depth = options.min_depth();
+    let max_depth = options.max_depth();
+
+    let mut output = String::new();
+    // Levels of the listed ancestors of the current heading.
+    let mut ancestors: Vec<u8> = vec![];
+    // Level of the excluded heading whose subsections are being skipped.
+    let mut skip_level = None;
+    for heading in headings(text) {
+        if skip_level.is_some_and(|level| heading.level > level) {
+            continue;
+        }
+        skip_level = None;
+        if heading.level < min_depth || heading.level > max_depth {
+            continue;
+        }
+        if options.is_excluded(&heading.text) || options.is_excluded(&heading.anchor) {
+            skip_level = Some(heading.level);
+            continue;
+        }
+
+        while ancestors
+            .last()
+            .is_some_and(|level| *level >= heading.level)
+        {
+            ancestors.pop();
+        }
+        writeln!(
+            &mut output,
+            "{:indent$}* [{}](#{})",
+            "",
+            heading.markdown,
+            heading.anchor,
+            indent = ancestors.len() * 2,
+        )
+        .unwrap();
+        ancestors.push(heading.level);
+    }
+    output
+}
+
+/// Creates a table of contents directive of the formats other than Markdown.
+///
+/// The directives only support limiting the depth of the listed headings.
+pub(super) fn create_directive(format: TargetFormat, options: &TocOptions) -> String {
+    match format {
+        TargetFormat::Markdown => String::new(),
+        TargetFormat::Asciidoc => "toc::[]\n".to_owned(),
+        TargetFormat::Rst => {
+            let depth = options.max_depth().saturating_sub(options.min_depth()) + 1;
+            format!(".. contents::\n   :depth: {depth}\n")
+        }
+    }
+}
+
+fn headings(text: &str) -> Vec<Heading> {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+    let mut slugger = Slugger::default();
+    let mut headings = vec![];
+    let mut current = None;
+    for event in Parser::new_ext(text, options) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some((level as u8, vec![], String::new()));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let Some((level, events, text)) = current.take() else {
+                    continue;
+                };
+                let mut markdown = String::new();
+                pulldown_cmark_to_cmark::cmark(events.into_iter(), &mut markdown).unwrap();
+                let anchor = slugger.slug(&text);
+                headings.push(Heading {
+                    level,
+                    markdown,
+                    text,
+                    anchor,
+                });
+            }
+            event => {
+                let Some((_, events, text)) = &mut current else {
+                    continue;
+                };
+                match &event {
+                    Event::Text(s) | Event::Code(s) => text.push_str(s),
+                    // Links cannot be nested in the link to the heading.
+                    Event::Start(Tag::Link { .. }) | Event::End(TagEnd::Link) => continue,
+                    _ => {}
+                }
+                events.push(event);
+            }
+        }
+    }
+    headings
+}
+
+/// Generates heading anchors in the same way as GitHub.
+///
+/// See <https://github.com/Flet/github-slugger>.
+#[derive(Debug, Default)]
+pub(super) struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub(super) fn slug(&mut self, text: &str) -> String {
+        let base = text
+            .chars()
+            .flat_map(char::to_lowercase)
+            .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+            .map(|c| if c == ' ' { '-' } else { c })
+            .collect::<String>();
+        let mut slug = base.clone();
+        while self.occurrences.contains_key(&slug) {
+            let count = self.occurrences.get_mut(&base).unwrap();
+            *count += 1;
+            slug = format!("{base}-{count}");
+        }
+        self.occurrences.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn slug_matches_github() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("Hello, World!"), "hello-world");
+        assert_eq!(slugger.slug("`foo::Bar` type"), "foobar-type");
+        assert_eq!(slugger.slug("MSRV (1.98.0)"), "msrv-1980");
+        assert_eq!(slugger.slug("日本語 テスト"), "日本語-テスト");
+        assert_eq!(
+            slugger.slug("snake_case  and-kebab"),
+            "snake_case--and-kebab"
+        );
+        assert_eq!(slugger.slug("Hello, World!"), "hello-world-1");
+        assert_eq!(slugger.slug("hello world"), "hello-world-2");
+        assert_eq!(slugger.slug("hello-world-1"), "hello-world-1-1");
+    }
+
+    static TEXT: &str = indoc::indoc! {"
+        # Title
+
+        ## Installation
+
+        ```markdown
+        ## Not a heading
+        ```
+
+        ### From source
+
+        ## Usage with [`Foo`](https://example.com)
+
+        #### Deep
+
+        ### Options
+
+        ## Changelog
+
+        ### v1.0.0
+
+        ## License
+    "};
+
+    #[test]
+    fn create_nested_list() {
+        assert_eq!(
+            create(TEXT, &TocOptions::default()),
+            indoc::indoc! {"
+                * [Installation](#installation)
+                  * [From source](#from-source)
+                * [Usage with `Foo`](#usage-with-foo)
+                  * [Deep](#deep)
+                  * [Options](#options)
+                * [Changelog](#changelog)
+                  * [v1.0.0](#v100)
+                * [License](#license)
+            "}
+        );
+    }
+
+    #[test]
+    fn create_with_options() {
+        let options = TocOptions {
+            min_depth: Some(1),
+            max_depth: Some(2),
+            exclude: Some(vec!["Changelog".into(), "usage-with-foo".into()]),
+        };
+        assert_eq!(
+            create(TEXT, &options),
+            indoc::indoc! {"
+                * [Title](#title)
+                  * [Installation](#installation)
+                  * [License](#license)
+            "}
+        );
+    }
+
+    #[test]
+    fn create_directives() {
+        let options = TocOptions {
+            max_depth: Some(3),
+            ..TocOptions::default()
+        };
+        assert_eq!(
+            create_directive(TargetFormat::Asciidoc, &options),
+            "toc::[]\n"
+        );
+        assert_eq!(
+            create_directive(TargetFormat::Rst, &options),
+            ".. contents::\n   :depth: 2\n"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt::Write as _;
+
+use cargo_metadata::{Metadata, Package, PackageName, camino::Utf8Path};
+use snafu::{ResultExt as _, Snafu};
+
+use super::{Escape, badge, relative_link};
+use crate::{
+    sync::{
+        ManifestFile, MarkdownFile,
+        marker::{MemberColumn, WorkspaceMembersOptions},
+    },
+    traits::PackageExt as _,
+    with_source::ReadFileError,
+};
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+pub(in super::super) enum CreateWorkspaceMembersError {
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    ReadManifest {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: ReadFileError,
+    },
+    #[snafu(display("failed to create badges for package {package_name}"))]
+    CreateBadges {
+        package_name: PackageName,
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: badge::CreateAllBadgesError,
+    },
+}
+
+pub(super) fn create(
+    markdown: &MarkdownFile<'_>,
+    workspace: &Metadata,
+    options: &WorkspaceMembersOptions,
+) -> Result<String, CreateWorkspaceMembersError> {
+    let columns = options.columns();
+    let markdown_dir = markdown.relative_path.parent().unwrap_or(Utf8Path::new(""));
+
+    let mut packages = workspace.workspace_packages();
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut output = String::new();
+    let header = columns.iter().map(|column| column.header());
+    let separator = columns
+        .iter()
+        .map(|column| "-".repeat(column.header().len()));
+    write_row(&mut output, header);
+    write_row(&mut output, separator);
+    for package in packages {
+        let cells = columns
+            .iter()
+            .map(|column| cell(*column, markdown_dir, workspace, package))
+            .collect::<Result<Vec<_>, _>>()?;
+        write_row(&mut output, cells);
+    }
+    Ok(output)
+}
+
+fn write_row<S>(output: &mut String, cells: impl IntoIterator<Item = S>)
+where
+    S: AsRef<str>,
+{
+    output.push('|');
+    for cell in cells {
+        write!(output, " {} |", cell.as_ref()).unwrap();
+    }
+    output.push('\n');
+}
+
+fn cell(
+    column: MemberColumn,
+    markdown_dir: &Utf8Path,
+    workspace: &Metadata,
+    package: &Package,
+) -> Result<String, CreateWorkspaceMembersError> {
+    let cell = match column {
+        MemberColumn::Name => {
+            let package_dir = package.workspace_relative_root_directory(workspace);
+            match relative_link(markdown_dir, package_dir) {
+                Some(link) => format!("[`{}`]({link})", package.name),
+                None => format!("`{}`", package.name),
+            }
+        }
+        MemberColumn::Description => {
+            let description = package.description.as_deref().unwrap_or_default();
+            let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+            Escape(&description, &['|']).to_string()
+        }
+        MemberColumn::Version => package.version.to_string(),
+        MemberColumn::Links => {
+            // `publish = false` is represented as an empty list of registries.
+            if package.publish.as_ref().is_some_and(Vec::is_empty) {
+                String::new()
+            } else {
+                format!(
+                    "[crates.io](https://crates.io/crates/{name}), [docs.rs](https://docs.rs/{name})",
+                    name = package.name,
+                )
+            }
+        }
+        MemberColumn::Badges => {
+            let manifest = ManifestFile::from_toml("package manifest", &package.manifest_path)?;
+            let Some(badges) = &manifest.value().config().badge.default else {
+                return Ok(String::new());
+            };
+            let badges = badge::create_all(badges, &manifest, workspace, package).context(
+                CreateBadgesSnafu {
+                    package_name: package.name.clone(),
+                },
+            )?;
+            badges.lines().collect::<Vec<_>>().join(" ")
+        }
+    };
+    Ok(cell)
+}
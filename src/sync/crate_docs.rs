@@ -0,0 +1,99 @@
+use crate::sync::marker::MAGIC;
+
+/// Converts Markdown into `//!` doc comment lines.
+pub(super) fn to_doc_comment(docs: &str) -> String {
+    docs.lines()
+        .map(|line| {
+            if line.is_empty() {
+                "//!\n".to_owned()
+            } else {
+                format!("//! {line}\n")
+            }
+        })
+        .collect()
+}
+
+/// Replaces the lines between `// cargo-sync-rdme rustdoc [[` and
+/// `// cargo-sync-rdme ]]` in the crate root with `doc_comment`.
+///
+/// The markers are plain comments, so they do not appear in the crate
+/// documentation. Returns `None` if the markers are not found.
+pub(super) fn replace_doc_block(source: &str, doc_comment: &str) -> Option<String> {
+    let start_marker = format!("// {MAGIC} rustdoc [[");
+    let end_marker = format!("// {MAGIC} ]]");
+
+    let mut offset = 0;
+    let mut start = None;
+    for line in source.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        match start {
+            None if line.trim() == start_marker => start = Some(offset),
+            Some(start) if line.trim() == end_marker => {
+                return Some(format!(
+                    "{}{doc_comment}{}",
+                    &source[..start],
+                    &source[line_start..]
+                ));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn doc_comment() {
+        assert_eq!(to_doc_comment(""), "");
+        assert_eq!(
+            to_doc_comment("# Foo\n\nBar\n  baz\n"),
+            "//! # Foo\n//!\n//! Bar\n//!   baz\n"
+        );
+    }
+
+    #[test]
+    fn replace_existing_block() {
+        let source = indoc! {"
+            // cargo-sync-rdme rustdoc [[
+            //! Old docs.
+            // cargo-sync-rdme ]]
+
+            #![warn(missing_docs)]
+        "};
+        assert_eq!(
+            replace_doc_block(source, "//! New docs.\n//!\n//! More.\n").unwrap(),
+            indoc! {"
+                // cargo-sync-rdme rustdoc [[
+                //! New docs.
+                //!
+                //! More.
+                // cargo-sync-rdme ]]
+
+                #![warn(missing_docs)]
+            "}
+        );
+        assert_eq!(
+            replace_doc_block(source, "").unwrap(),
+            indoc! {"
+                // cargo-sync-rdme rustdoc [[
+                // cargo-sync-rdme ]]
+
+                #![warn(missing_docs)]
+            "}
+        );
+    }
+
+    #[test]
+    fn no_block() {
+        assert!(replace_doc_block("//! Docs.\n", "").is_none());
+        assert!(replace_doc_block("// cargo-sync-rdme rustdoc [[\n//! Docs.\n", "").is_none());
+        assert!(replace_doc_block("// cargo-sync-rdme ]]\n//! Docs.\n", "").is_none());
+    }
+}
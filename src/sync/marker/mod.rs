@@ -1,14 +1,21 @@
-use std::{fmt, sync::Arc};
+use std::{
+    collections::HashMap,
+    fmt::{self, Write as _},
+    range::Range,
+    str::FromStr,
+    sync::Arc,
+};
 
+use cargo_metadata::Metadata;
 use miette::NamedSource;
+use sha1::{Digest as _, Sha1};
 use snafu::{Snafu, ensure};
 
 use crate::{
-    config::metadata::BadgeItem,
+    config::metadata::{BadgeItem, MarkerStyle},
     parse::Spanned,
     sync::{
         ManifestFile, MarkdownFile, MarkdownPath,
-        contents::Contents,
         marker::resolve::{ResolveMarkerError, Resolver},
     },
 };
@@ -17,7 +24,7 @@ mod parse;
 mod resolve;
 mod scan;
 
-const MAGIC: &str = "cargo-sync-rdme";
+pub(super) const MAGIC: &str = "cargo-sync-rdme";
 
 #[derive(Debug, Snafu, miette::Diagnostic)]
 #[snafu(display(
@@ -34,44 +41,748 @@ pub(crate) struct ParseMarkersError {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) enum ResolvedReplaceSpecifier {
-    Title,
+    Title {
+        /// Name of the workspace member whose title is used (`title@<package>`).
+        package: Option<Spanned<Arc<str>>>,
+    },
+    Features,
+    Install {
+        /// `style=<toml|add|install|binstall>`: kind of the snippet.
+        style: Option<InstallStyle>,
+    },
     Badge {
+        /// Name of the workspace member whose badges are used (`badge@<package>`).
+        package: Option<Spanned<Arc<str>>>,
         group: Option<Arc<str>>,
         badges: Arc<[BadgeItem]>,
     },
-    Rustdoc,
+    Rustdoc {
+        /// Name of the workspace member whose documentation is used (`rustdoc@<package>`).
+        ///
+        /// If `None`, the documentation of the package being synced is used.
+        package: Option<Spanned<Arc<str>>>,
+        /// Path of the item whose documentation is used (`rustdoc:<path>`).
+        ///
+        /// If `None`, the documentation of the crate root is used.
+        item: Option<Spanned<Arc<str>>>,
+        options: RustdocOptions,
+    },
+    ApiSummary {
+        options: ApiSummaryOptions,
+    },
+    Include {
+        /// Path of the file to include (`include:<path>`).
+        path: Spanned<Arc<str>>,
+        options: IncludeOptions,
+    },
+    Snippet {
+        /// Path of the file relative to the package root (`snippet:<path>`).
+        path: Spanned<Arc<str>>,
+        /// Name of the anchor delimiting the snippet (`anchor=<name>`).
+        anchor: Spanned<Arc<str>>,
+        /// Language of the code block (`lang=<lang>`).
+        lang: Option<String>,
+    },
+    Toc {
+        options: TocOptions,
+    },
+    WorkspaceMembers {
+        options: WorkspaceMembersOptions,
+    },
+    Changelog {
+        options: ChangelogOptions,
+    },
+    Template {
+        /// Name of the template in `package.metadata.cargo-sync-rdme.templates` (`template:<name>`).
+        name: Arc<str>,
+    },
+    CliHelp {
+        /// Name of the binary target (`cli-help:<bin>`).
+        ///
+        /// If `None`, the only binary target of the package is used.
+        bin: Option<Spanned<Arc<str>>>,
+        options: CliHelpOptions,
+    },
+}
+
+/// Options for the `rustdoc` marker given as marker arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct RustdocOptions {
+    /// `heading-offset=<n>`: number of levels to demote headings by.
+    pub(super) heading_offset: Option<u8>,
+    /// `links=<inline|reference>`: how links are written.
+    pub(super) links: Option<LinkStyle>,
+}
+
+impl RustdocOptions {
+    pub(super) const DEFAULT_HEADING_OFFSET: u8 = 1;
+
+    pub(super) fn heading_offset(&self) -> u8 {
+        self.heading_offset.unwrap_or(Self::DEFAULT_HEADING_OFFSET)
+    }
+
+    pub(super) fn links(&self) -> LinkStyle {
+        self.links.unwrap_or_default()
+    }
+}
+
+impl fmt::Display for RustdocOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            heading_offset,
+            links,
+        } = self;
+        if let Some(heading_offset) = heading_offset {
+            write_argument(f, "heading-offset", heading_offset)?;
+        }
+        if let Some(links) = links {
+            write_argument(f, "links", links.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for the `api-summary` marker given as marker arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct ApiSummaryOptions {
+    /// `heading-level=<n>`: level of the headings of item groups.
+    pub(super) heading_level: Option<u8>,
+}
+
+impl ApiSummaryOptions {
+    pub(super) const DEFAULT_HEADING_LEVEL: u8 = 3;
+
+    pub(super) fn heading_level(&self) -> u8 {
+        self.heading_level.unwrap_or(Self::DEFAULT_HEADING_LEVEL)
+    }
+}
+
+impl fmt::Display for ApiSummaryOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { heading_level } = self;
+        if let Some(heading_level) = heading_level {
+            write_argument(f, "heading-level", heading_level)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum LinkStyle {
+    Inline,
+    #[default]
+    Reference,
+}
+
+impl LinkStyle {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Reference => "reference",
+        }
+    }
+}
+
+impl FromStr for LinkStyle {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inline" => Ok(Self::Inline),
+            "reference" => Ok(Self::Reference),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Options for the `include` marker given as marker arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct IncludeOptions {
+    /// `relative-to=<file|package>`: base directory of the path.
+    pub(super) relative_to: Option<IncludeBase>,
+    /// `format=<markdown|code|native>`: how the file is embedded.
+    ///
+    /// `native` files are inserted without conversion to the format of the
+    /// target file.
+    pub(super) format: Option<IncludeFormat>,
+    /// `lang=<lang>`: language of the code block.
+    pub(super) lang: Option<String>,
+}
+
+impl IncludeOptions {
+    pub(super) fn relative_to(&self) -> IncludeBase {
+        self.relative_to.unwrap_or_default()
+    }
+
+    /// Returns the format of the included file.
+    ///
+    /// Specifying `lang` implies `format=code`.
+    pub(super) fn format(&self) -> IncludeFormat {
+        match (self.format, &self.lang) {
+            (Some(format), _) => format,
+            (None, Some(_)) => IncludeFormat::Code,
+            (None, None) => IncludeFormat::default(),
+        }
+    }
+}
+
+impl fmt::Display for IncludeOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            relative_to,
+            format,
+            lang,
+        } = self;
+        if let Some(relative_to) = relative_to {
+            write_argument(f, "relative-to", relative_to.as_str())?;
+        }
+        if let Some(format) = format {
+            write_argument(f, "format", format.as_str())?;
+        }
+        if let Some(lang) = lang {
+            write_argument(f, "lang", lang)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum IncludeBase {
+    /// The directory containing the target Markdown file.
+    #[default]
+    File,
+    /// The package root directory.
+    Package,
+}
+
+impl IncludeBase {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Package => "package",
+        }
+    }
+}
+
+impl FromStr for IncludeBase {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(Self::File),
+            "package" => Ok(Self::Package),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum IncludeFormat {
+    /// Embed the file as Markdown.
+    #[default]
+    Markdown,
+    /// Embed the file as a fenced code block.
+    Code,
+    /// Embed the file as is, without converting it to the format of the target file.
+    Native,
+}
+
+impl IncludeFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Code => "code",
+            Self::Native => "native",
+        }
+    }
+}
+
+impl FromStr for IncludeFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(Self::Markdown),
+            "code" => Ok(Self::Code),
+            "native" => Ok(Self::Native),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Options for the `toc` marker given as marker arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct TocOptions {
+    /// `min-depth=<n>`: minimum level of headings to list.
+    pub(super) min_depth: Option<u8>,
+    /// `max-depth=<n>`: maximum level of headings to list.
+    pub(super) max_depth: Option<u8>,
+    /// `exclude=<heading>,...`: headings to exclude, with their subsections.
+    pub(super) exclude: Option<Vec<String>>,
+}
+
+impl TocOptions {
+    pub(super) const DEFAULT_MIN_DEPTH: u8 = 2;
+    pub(super) const DEFAULT_MAX_DEPTH: u8 = 6;
+
+    pub(super) fn min_depth(&self) -> u8 {
+        self.min_depth.unwrap_or(Self::DEFAULT_MIN_DEPTH)
+    }
+
+    pub(super) fn max_depth(&self) -> u8 {
+        self.max_depth.unwrap_or(Self::DEFAULT_MAX_DEPTH)
+    }
+
+    /// Returns `true` if the heading with the given text or anchor is excluded.
+    pub(super) fn is_excluded(&self, heading: &str) -> bool {
+        self.exclude
+            .iter()
+            .flatten()
+            .any(|exclude| exclude.eq_ignore_ascii_case(heading))
+    }
+}
+
+impl fmt::Display for TocOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            min_depth,
+            max_depth,
+            exclude,
+        } = self;
+        if let Some(min_depth) = min_depth {
+            write_argument(f, "min-depth", min_depth)?;
+        }
+        if let Some(max_depth) = max_depth {
+            write_argument(f, "max-depth", max_depth)?;
+        }
+        if let Some(exclude) = exclude {
+            write_argument(f, "exclude", exclude.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for the `changelog` marker given as marker arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct ChangelogOptions {
+    /// `section=<latest|n|version>`: section of the changelog to embed.
+    pub(super) section: Option<ChangelogSection>,
+    /// `heading-offset=<n>`: number of levels to demote headings by.
+    pub(super) heading_offset: Option<u8>,
+    /// `link=<true|false>`: whether to link the section title to the changelog file.
+    pub(super) link: Option<bool>,
+}
+
+impl ChangelogOptions {
+    pub(super) const DEFAULT_HEADING_OFFSET: u8 = 1;
+
+    pub(super) fn section(&self) -> &ChangelogSection {
+        self.section.as_ref().unwrap_or(&ChangelogSection::Latest)
+    }
+
+    pub(super) fn heading_offset(&self) -> u8 {
+        self.heading_offset.unwrap_or(Self::DEFAULT_HEADING_OFFSET)
+    }
+
+    pub(super) fn link(&self) -> bool {
+        self.link.unwrap_or(false)
+    }
+}
+
+impl fmt::Display for ChangelogOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            section,
+            heading_offset,
+            link,
+        } = self;
+        if let Some(section) = section {
+            write_argument(f, "section", section)?;
+        }
+        if let Some(heading_offset) = heading_offset {
+            write_argument(f, "heading-offset", heading_offset)?;
+        }
+        if let Some(link) = link {
+            write_argument(f, "link", link)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ChangelogSection {
+    /// The first section that is not `Unreleased`.
+    Latest,
+    /// The `n`-th section, starting from 1.
+    Index(usize),
+    /// The section whose title starts with the version, such as `Unreleased` or `1.2.3`.
+    Version(String),
+}
+
+impl fmt::Display for ChangelogSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Latest => write!(f, "latest"),
+            Self::Index(n) => write!(f, "{n}"),
+            Self::Version(version) => write!(f, "{version}"),
+        }
+    }
+}
+
+impl FromStr for ChangelogSection {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "latest" {
+            return Ok(Self::Latest);
+        }
+        if let Ok(n) = s.parse() {
+            return if n > 0 { Ok(Self::Index(n)) } else { Err(()) };
+        }
+        if s.is_empty() {
+            return Err(());
+        }
+        Ok(Self::Version(s.to_owned()))
+    }
+}
+
+/// Options for the `workspace-members` marker given as marker arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct WorkspaceMembersOptions {
+    /// `columns=<column>,...`: columns of the table.
+    pub(super) columns: Option<Vec<MemberColumn>>,
+}
+
+impl WorkspaceMembersOptions {
+    pub(super) const DEFAULT_COLUMNS: &[MemberColumn] = &[
+        MemberColumn::Name,
+        MemberColumn::Description,
+        MemberColumn::Version,
+        MemberColumn::Links,
+    ];
+
+    pub(super) fn columns(&self) -> &[MemberColumn] {
+        self.columns.as_deref().unwrap_or(Self::DEFAULT_COLUMNS)
+    }
+}
+
+impl fmt::Display for WorkspaceMembersOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { columns } = self;
+        if let Some(columns) = columns {
+            let columns = columns
+                .iter()
+                .map(|column| column.as_str())
+                .collect::<Vec<_>>();
+            write_argument(f, "columns", columns.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum MemberColumn {
+    /// Package name linked to the package directory.
+    Name,
+    /// `package.description`.
+    Description,
+    /// `package.version`.
+    Version,
+    /// Links to crates.io and docs.rs.
+    Links,
+    /// Default badge group of the package.
+    Badges,
+}
+
+impl MemberColumn {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Description => "description",
+            Self::Version => "version",
+            Self::Links => "links",
+            Self::Badges => "badges",
+        }
+    }
+
+    pub(super) fn header(self) -> &'static str {
+        match self {
+            Self::Name => "Crate",
+            Self::Description => "Description",
+            Self::Version => "Version",
+            Self::Links => "Links",
+            Self::Badges => "Badges",
+        }
+    }
+}
+
+impl FromStr for MemberColumn {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(Self::Name),
+            "description" => Ok(Self::Description),
+            "version" => Ok(Self::Version),
+            "links" => Ok(Self::Links),
+            "badges" => Ok(Self::Badges),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Options for the `cli-help` marker given as marker arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct CliHelpOptions {
+    /// `args=<args>`: whitespace-separated arguments passed to the binary.
+    pub(super) args: Option<String>,
+    /// `lang=<text|console>`: language of the code block.
+    pub(super) lang: Option<CliHelpLang>,
+}
+
+impl CliHelpOptions {
+    pub(super) const DEFAULT_ARGS: &str = "--help";
+
+    pub(super) fn args(&self) -> Vec<&str> {
+        self.args
+            .as_deref()
+            .unwrap_or(Self::DEFAULT_ARGS)
+            .split_whitespace()
+            .collect()
+    }
+
+    pub(super) fn lang(&self) -> CliHelpLang {
+        self.lang.unwrap_or_default()
+    }
+}
+
+impl fmt::Display for CliHelpOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { args, lang } = self;
+        if let Some(args) = args {
+            write_argument(f, "args", args)?;
+        }
+        if let Some(lang) = lang {
+            write_argument(f, "lang", lang.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum CliHelpLang {
+    /// The output only.
+    #[default]
+    Text,
+    /// The output preceded by the command line.
+    Console,
+}
+
+impl CliHelpLang {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Console => "console",
+        }
+    }
+}
+
+impl FromStr for CliHelpLang {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "console" => Ok(Self::Console),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum InstallStyle {
+    /// A `[dependencies]` table in `Cargo.toml`.
+    Toml,
+    /// A `cargo add` command.
+    Add,
+    /// A `cargo install` command.
+    Install,
+    /// A `cargo binstall` command.
+    Binstall,
+}
+
+impl InstallStyle {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Toml => "toml",
+            Self::Add => "add",
+            Self::Install => "install",
+            Self::Binstall => "binstall",
+        }
+    }
+}
+
+impl FromStr for InstallStyle {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "toml" => Ok(Self::Toml),
+            "add" => Ok(Self::Add),
+            "install" => Ok(Self::Install),
+            "binstall" => Ok(Self::Binstall),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Writes ` key=value` to the formatter, quoting the value if needed.
+fn write_argument(f: &mut fmt::Formatter<'_>, key: &str, value: impl fmt::Display) -> fmt::Result {
+    write!(f, " {key}=")?;
+    write_value(f, value)
+}
+
+/// Writes a group name or an argument value, quoting it if needed.
+///
+/// Quoted values have no escape sequences, so the value must not contain `"`.
+/// Values read by the parser never do, as `"` ends a quoted value and is not
+/// allowed in bare values.
+fn write_value(f: &mut fmt::Formatter<'_>, value: impl fmt::Display) -> fmt::Result {
+    let value = value.to_string();
+    debug_assert!(
+        !value.contains('"'),
+        "marker value cannot contain `\"`: {value}"
+    );
+    if !value.is_empty() && value.chars().all(parse::is_bare_value_char) {
+        write!(f, "{value}")
+    } else {
+        write!(f, "\"{value}\"")
+    }
+}
+
+/// Writes the `@<package>` qualifier of a marker, if any.
+fn write_package(f: &mut fmt::Formatter<'_>, package: Option<&Spanned<Arc<str>>>) -> fmt::Result {
+    if let Some(package) = package {
+        write!(f, "@{}", package.value)?;
+    }
+    Ok(())
 }
 
 impl fmt::Display for ResolvedReplaceSpecifier {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Title => write!(f, "title"),
-            Self::Badge { group, .. } => {
+            Self::Title { package } => {
+                write!(f, "title")?;
+                write_package(f, package.as_ref())
+            }
+            Self::Features => write!(f, "features"),
+            Self::Install { style } => {
+                write!(f, "install")?;
+                if let Some(style) = style {
+                    write_argument(f, "style", style.as_str())?;
+                }
+                Ok(())
+            }
+            Self::Badge { package, group, .. } => {
+                write!(f, "badge")?;
+                write_package(f, package.as_ref())?;
                 if let Some(name) = group {
-                    write!(f, "badge:{name}")
-                } else {
-                    write!(f, "badge")
+                    write!(f, ":{name}")?;
+                }
+                Ok(())
+            }
+            Self::Rustdoc {
+                package,
+                item,
+                options,
+            } => {
+                write!(f, "rustdoc")?;
+                write_package(f, package.as_ref())?;
+                if let Some(item) = item {
+                    write!(f, ":{}", item.value)?;
+                }
+                write!(f, "{options}")
+            }
+            Self::ApiSummary { options } => write!(f, "api-summary{options}"),
+            Self::Include { path, options } => {
+                write!(f, "include:")?;
+                write_value(f, &path.value)?;
+                write!(f, "{options}")
+            }
+            Self::Snippet { path, anchor, lang } => {
+                write!(f, "snippet:")?;
+                write_value(f, &path.value)?;
+                write_argument(f, "anchor", &anchor.value)?;
+                if let Some(lang) = lang {
+                    write_argument(f, "lang", lang)?;
                 }
+                Ok(())
+            }
+            Self::Toc { options } => write!(f, "toc{options}"),
+            Self::WorkspaceMembers { options } => write!(f, "workspace-members{options}"),
+            Self::Changelog { options } => write!(f, "changelog{options}"),
+            Self::Template { name } => {
+                write!(f, "template:")?;
+                write_value(f, name)
+            }
+            Self::CliHelp { bin, options } => {
+                write!(f, "cli-help")?;
+                if let Some(bin) = bin {
+                    write!(f, ":")?;
+                    write_value(f, &bin.value)?;
+                }
+                write!(f, "{options}")
             }
-            Self::Rustdoc => write!(f, "rustdoc"),
         }
     }
 }
 
+impl ResolvedReplaceSpecifier {
+    /// Returns the name of the workspace member given by the `@<package>` qualifier.
+    pub(super) fn package(&self) -> Option<&Spanned<Arc<str>>> {
+        match self {
+            Self::Title { package }
+            | Self::Badge { package, .. }
+            | Self::Rustdoc { package, .. } => package.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Markers found in a markdown file.
+#[derive(Debug, Default)]
+pub(super) struct Markers {
+    pub(super) specifiers: Vec<Spanned<ResolvedReplaceSpecifier>>,
+    /// Spans of the marker regions whose contents do not match the hashes
+    /// recorded in their start markers.
+    pub(super) hand_edited: Vec<Range<usize>>,
+    /// Manifests of the workspace members referred to by `@<package>` qualifiers.
+    pub(super) package_manifests: HashMap<String, ManifestFile>,
+}
+
 pub(super) fn parse_markers(
     markdown: &MarkdownFile<'_>,
     manifest: &ManifestFile,
-) -> Result<Vec<Spanned<ResolvedReplaceSpecifier>>, Box<ParseMarkersError>> {
-    let mut resolver = Resolver::new(&markdown.text, manifest);
-    let mut specifiers = vec![];
+    workspace: &Metadata,
+) -> Result<Markers, Box<ParseMarkersError>> {
+    let mut resolver = Resolver::new(&markdown.text, markdown.marker_style, manifest, workspace);
+    let mut markers = Markers::default();
     let mut errors = vec![];
 
     while let Some(res) = resolver.try_next().transpose() {
         match res {
-            Ok(specifier) => specifiers.push(specifier),
+            Ok(chunk) => {
+                if chunk.value.hand_edited {
+                    markers.hand_edited.push(chunk.span);
+                }
+                markers
+                    .specifiers
+                    .push(Spanned::new(chunk.value.specifier, chunk.span));
+            }
             Err(err) => errors.push(err),
         }
     }
+    markers.package_manifests = resolver.into_package_manifests();
 
     ensure!(
         errors.is_empty(),
@@ -82,15 +793,408 @@ pub(super) fn parse_markers(
         }
     );
 
-    Ok(specifiers)
+    Ok(markers)
 }
 
-pub(super) fn make_marked_contents(contents: &Contents) -> String {
-    let specifier = contents.specifier();
-    let text = contents.text();
+/// Returns the opening and closing delimiters of markers in the style.
+fn delimiters(style: MarkerStyle) -> (&'static str, &'static str) {
+    match style {
+        MarkerStyle::Html => ("<!-- ", " -->"),
+        MarkerStyle::LinkReference => ("[//]: # (", ")"),
+        MarkerStyle::Mdx => ("{/* ", " */}"),
+        MarkerStyle::Asciidoc => ("// ", ""),
+        MarkerStyle::Rst => (".. ", ""),
+    }
+}
+
+pub(super) fn make_marked_contents(
+    specifier: &ResolvedReplaceSpecifier,
+    text: &str,
+    style: MarkerStyle,
+) -> String {
+    write_marked_contents(specifier, text, style, None)
+}
+
+/// Same as [`make_marked_contents`], but records the hash of the contents in
+/// the start marker (`[[ sha=<hash>`), so that hand edits can be detected.
+pub(super) fn make_hashed_contents(
+    specifier: &ResolvedReplaceSpecifier,
+    text: &str,
+    style: MarkerStyle,
+) -> String {
+    write_marked_contents(specifier, text, style, Some(&content_hash(text)))
+}
+
+fn write_marked_contents(
+    specifier: &ResolvedReplaceSpecifier,
+    text: &str,
+    style: MarkerStyle,
+    hash: Option<&str>,
+) -> String {
+    let (open, close) = delimiters(style);
     if text.is_empty() {
-        format!("<!-- {MAGIC} {specifier} -->")
-    } else {
-        format!("<!-- {MAGIC} {specifier} [[ -->\n{text}<!-- {MAGIC} ]] -->")
+        return format!("{open}{MAGIC} {specifier}{close}");
+    }
+    // A link reference definition cannot interrupt a paragraph, so separate
+    // the end marker from the contents with a blank line.
+    // reStructuredText comments must also be separated from the other blocks.
+    let (leading, trailing) = match style {
+        MarkerStyle::LinkReference => ("", "\n"),
+        MarkerStyle::Rst => ("\n", "\n"),
+        MarkerStyle::Html | MarkerStyle::Mdx | MarkerStyle::Asciidoc => ("", ""),
+    };
+    let hash = hash.map(|hash| format!(" sha={hash}")).unwrap_or_default();
+    format!(
+        "{open}{MAGIC} {specifier} [[{hash}{close}\n{leading}{text}{trailing}{open}{MAGIC} ]]{close}"
+    )
+}
+
+/// Returns whether the contents of the marker `region` differ from `text`.
+///
+/// Differences ignored by [`content_hash`] are not counted.
+pub(super) fn contents_changed(region: &str, text: &str, style: MarkerStyle) -> bool {
+    content_hash(marked_contents(region, style)) != content_hash(text)
+}
+
+/// Returns the short hash of the contents of a marker region.
+///
+/// Trailing whitespace and surrounding blank lines are ignored, so that the
+/// hash survives editors trimming lines and the blank lines [`make_marked_contents`]
+/// adds in some marker styles.
+fn content_hash(text: &str) -> String {
+    let normalized = text
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let digest = Sha1::digest(normalized.trim_matches('\n'));
+    digest[..4].iter().fold(String::new(), |mut hash, byte| {
+        write!(hash, "{byte:02x}").unwrap();
+        hash
+    })
+}
+
+/// Returns the contents of a marker region without the markers.
+///
+/// Used in place of [`make_marked_contents`] to write copies of target files
+/// with the markers stripped. The line break at the end of the contents is
+/// removed, because the end marker is followed by a line break.
+pub(super) fn make_unmarked_contents(
+    _specifier: &ResolvedReplaceSpecifier,
+    text: &str,
+    _style: MarkerStyle,
+) -> String {
+    text.strip_suffix('\n').unwrap_or(text).to_owned()
+}
+
+/// Returns the contents between the start and end markers of a marker region.
+///
+/// This is the inverse of [`make_marked_contents`]. The contents of a marker
+/// without an end marker are empty.
+pub(super) fn marked_contents(region: &str, style: MarkerStyle) -> &str {
+    let (open, close) = delimiters(style);
+    // Markers in the styles other than HTML comments are whole lines, so the
+    // start marker ends at the end of the line even if its quoted values
+    // contain the closing delimiter.
+    let close = match style {
+        MarkerStyle::Html => close.trim_start(),
+        _ => "\n",
+    };
+    let Some((_start, rest)) = region.split_once(close) else {
+        return "";
+    };
+    let Some(end) = rest.rfind(open.trim_end()) else {
+        return "";
+    };
+    let text = &rest[..end];
+    text.strip_prefix('\n').unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn marked_contents_of_region() {
+        use MarkerStyle::{Asciidoc, Html, LinkReference, Mdx, Rst};
+
+        assert_eq!(
+            marked_contents("<!-- cargo-sync-rdme rustdoc -->", Html),
+            ""
+        );
+        assert_eq!(
+            marked_contents(
+                "<!-- cargo-sync-rdme rustdoc [[ -->\n# Foo\n<!-- cargo-sync-rdme ]] -->",
+                Html
+            ),
+            "# Foo\n"
+        );
+        assert_eq!(
+            marked_contents(
+                "<!-- cargo-sync-rdme rustdoc [[ -->\nFoo <!-- comment -->\n<!-- cargo-sync-rdme ]] -->",
+                Html
+            ),
+            "Foo <!-- comment -->\n"
+        );
+        assert_eq!(
+            marked_contents(
+                "[//]: # (cargo-sync-rdme rustdoc [[)\n# Foo\n\n[//]: # (cargo-sync-rdme ]])",
+                LinkReference
+            ),
+            "# Foo\n\n"
+        );
+        assert_eq!(
+            marked_contents(
+                "{/* cargo-sync-rdme rustdoc [[ */}\n# Foo\n{/* cargo-sync-rdme ]] */}",
+                Mdx
+            ),
+            "# Foo\n"
+        );
+        assert_eq!(
+            marked_contents(
+                "// cargo-sync-rdme rustdoc [[\n== Foo\n// cargo-sync-rdme ]]",
+                Asciidoc
+            ),
+            "== Foo\n"
+        );
+        assert_eq!(
+            marked_contents(
+                ".. cargo-sync-rdme rustdoc [[\n\nFoo\n---\n\n.. cargo-sync-rdme ]]",
+                Rst
+            ),
+            "Foo\n---\n\n"
+        );
+        assert_eq!(marked_contents(".. cargo-sync-rdme rustdoc", Rst), "");
+        assert_eq!(
+            marked_contents(
+                "[//]: # (cargo-sync-rdme include:\"a(b).md\" [[)\nFoo\n[//]: # (cargo-sync-rdme ]])",
+                LinkReference
+            ),
+            "Foo\n"
+        );
+        assert_eq!(
+            marked_contents(
+                "{/* cargo-sync-rdme include:\"*/}.md\" [[ */}\nFoo\n{/* cargo-sync-rdme ]] */}",
+                Mdx
+            ),
+            "Foo\n"
+        );
+    }
+
+    #[test]
+    fn make_unmarked_contents_strips_markers() {
+        let title = ResolvedReplaceSpecifier::Title { package: None };
+        for style in [MarkerStyle::Html, MarkerStyle::Rst] {
+            assert_eq!(make_unmarked_contents(&title, "", style), "");
+            assert_eq!(make_unmarked_contents(&title, "# foo\n", style), "# foo");
+            assert_eq!(
+                make_unmarked_contents(&title, "# foo\n\nbar\n", style),
+                "# foo\n\nbar"
+            );
+        }
+    }
+
+    #[test]
+    fn make_marked_contents_in_style() {
+        use MarkerStyle::{Asciidoc, Html, LinkReference, Mdx, Rst};
+
+        let title = ResolvedReplaceSpecifier::Title { package: None };
+        assert_eq!(
+            make_marked_contents(&title, "", Html),
+            "<!-- cargo-sync-rdme title -->"
+        );
+        assert_eq!(
+            make_marked_contents(&title, "# foo\n", Html),
+            "<!-- cargo-sync-rdme title [[ -->\n# foo\n<!-- cargo-sync-rdme ]] -->"
+        );
+        assert_eq!(
+            make_marked_contents(&title, "", LinkReference),
+            "[//]: # (cargo-sync-rdme title)"
+        );
+        assert_eq!(
+            make_marked_contents(&title, "# foo\n", LinkReference),
+            "[//]: # (cargo-sync-rdme title [[)\n# foo\n\n[//]: # (cargo-sync-rdme ]])"
+        );
+        assert_eq!(
+            make_marked_contents(&title, "# foo\n", Mdx),
+            "{/* cargo-sync-rdme title [[ */}\n# foo\n{/* cargo-sync-rdme ]] */}"
+        );
+        assert_eq!(
+            make_marked_contents(&title, "= foo\n", Asciidoc),
+            "// cargo-sync-rdme title [[\n= foo\n// cargo-sync-rdme ]]"
+        );
+        assert_eq!(
+            make_marked_contents(&title, "foo\n===\n", Rst),
+            ".. cargo-sync-rdme title [[\n\nfoo\n===\n\n.. cargo-sync-rdme ]]"
+        );
+    }
+
+    #[test]
+    fn make_hashed_contents_in_style() {
+        use MarkerStyle::{Asciidoc, Html, LinkReference, Mdx, Rst};
+
+        let title = ResolvedReplaceSpecifier::Title { package: None };
+        assert_eq!(
+            make_hashed_contents(&title, "", Html),
+            "<!-- cargo-sync-rdme title -->"
+        );
+        assert_eq!(
+            make_hashed_contents(&title, "# foo\n", Html),
+            "<!-- cargo-sync-rdme title [[ sha=8e4932f6 -->\n# foo\n<!-- cargo-sync-rdme ]] -->"
+        );
+        for style in [Html, LinkReference, Mdx, Asciidoc, Rst] {
+            let region = make_hashed_contents(&title, "# foo\n", style);
+            assert!(!contents_changed(&region, "# foo\n", style));
+            assert!(contents_changed(&region, "# bar\n", style));
+
+            let text = format!("{region}\n");
+            let chunk = scan::Scanner::new(&text, style)
+                .try_next()
+                .unwrap()
+                .unwrap();
+            assert!(!chunk.value.hand_edited, "{style:?}");
+        }
+    }
+
+    #[test]
+    fn display_specifier_with_package() {
+        let specifier = ResolvedReplaceSpecifier::Rustdoc {
+            package: Some(Spanned::new("foo-core".into(), 0..8)),
+            item: Some(Spanned::new("Bar".into(), 0..3)),
+            options: RustdocOptions {
+                heading_offset: Some(2),
+                links: None,
+            },
+        };
+        assert_eq!(
+            specifier.to_string(),
+            "rustdoc@foo-core:Bar heading-offset=2"
+        );
+
+        let specifier = ResolvedReplaceSpecifier::Title {
+            package: Some(Spanned::new("foo-core".into(), 0..8)),
+        };
+        assert_eq!(specifier.to_string(), "title@foo-core");
+
+        let specifier = ResolvedReplaceSpecifier::Badge {
+            package: Some(Spanned::new("foo-core".into(), 0..8)),
+            group: Some("ci".into()),
+            badges: Arc::from([]),
+        };
+        assert_eq!(specifier.to_string(), "badge@foo-core:ci");
+    }
+
+    #[test]
+    fn display_specifier_with_arguments() {
+        let specifier = ResolvedReplaceSpecifier::Rustdoc {
+            package: None,
+            item: None,
+            options: RustdocOptions::default(),
+        };
+        assert_eq!(specifier.to_string(), "rustdoc");
+
+        let specifier = ResolvedReplaceSpecifier::Rustdoc {
+            package: None,
+            item: Some(Spanned::new("foo::Bar".into(), 0..8)),
+            options: RustdocOptions::default(),
+        };
+        assert_eq!(specifier.to_string(), "rustdoc:foo::Bar");
+
+        let specifier = ResolvedReplaceSpecifier::Rustdoc {
+            package: None,
+            item: None,
+            options: RustdocOptions {
+                heading_offset: Some(0),
+                links: Some(LinkStyle::Inline),
+            },
+        };
+        assert_eq!(
+            specifier.to_string(),
+            "rustdoc heading-offset=0 links=inline"
+        );
+
+        let specifier = ResolvedReplaceSpecifier::ApiSummary {
+            options: ApiSummaryOptions {
+                heading_level: Some(2),
+            },
+        };
+        assert_eq!(specifier.to_string(), "api-summary heading-level=2");
+
+        let specifier = ResolvedReplaceSpecifier::Include {
+            path: Spanned::new("../examples/foo.toml".into(), 0..20),
+            options: IncludeOptions::default(),
+        };
+        assert_eq!(specifier.to_string(), "include:../examples/foo.toml");
+
+        let specifier = ResolvedReplaceSpecifier::Include {
+            path: Spanned::new("dir name/foo.sh".into(), 0..15),
+            options: IncludeOptions {
+                relative_to: Some(IncludeBase::Package),
+                format: Some(IncludeFormat::Code),
+                lang: Some("shell session".into()),
+            },
+        };
+        assert_eq!(
+            specifier.to_string(),
+            r#"include:"dir name/foo.sh" relative-to=package format=code lang="shell session""#
+        );
+
+        let specifier = ResolvedReplaceSpecifier::Snippet {
+            path: Spanned::new("examples/foo.rs".into(), 0..15),
+            anchor: Spanned::new("setup".into(), 0..5),
+            lang: None,
+        };
+        assert_eq!(
+            specifier.to_string(),
+            "snippet:examples/foo.rs anchor=setup"
+        );
+
+        let specifier = ResolvedReplaceSpecifier::Toc {
+            options: TocOptions {
+                min_depth: None,
+                max_depth: Some(3),
+                exclude: Some(vec!["Table of Contents".into(), "license".into()]),
+            },
+        };
+        assert_eq!(
+            specifier.to_string(),
+            r#"toc max-depth=3 exclude="Table of Contents,license""#
+        );
+
+        let specifier = ResolvedReplaceSpecifier::Changelog {
+            options: ChangelogOptions {
+                section: Some(ChangelogSection::Version("Unreleased".into())),
+                heading_offset: Some(2),
+                link: Some(true),
+            },
+        };
+        assert_eq!(
+            specifier.to_string(),
+            "changelog section=Unreleased heading-offset=2 link=true"
+        );
+
+        let specifier = ResolvedReplaceSpecifier::WorkspaceMembers {
+            options: WorkspaceMembersOptions {
+                columns: Some(vec![MemberColumn::Name, MemberColumn::Badges]),
+            },
+        };
+        assert_eq!(
+            specifier.to_string(),
+            "workspace-members columns=name,badges"
+        );
+
+        let specifier = ResolvedReplaceSpecifier::CliHelp {
+            bin: Some(Spanned::new("foo".into(), 0..3)),
+            options: CliHelpOptions {
+                args: Some("sub --help".into()),
+                lang: Some(CliHelpLang::Console),
+            },
+        };
+        assert_eq!(
+            specifier.to_string(),
+            r#"cli-help:foo args="sub --help" lang=console"#
+        );
     }
 }
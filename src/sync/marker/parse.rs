@@ -1,25 +1,58 @@
-use std::fmt::{self, Display};
+use std::{
+    fmt::{self, Display},
+    iter, ops,
+};
 
 use miette::{Diagnostic, SourceSpan};
-use pulldown_cmark::{Event, OffsetIter, Options};
+use pulldown_cmark::{Event, OffsetIter, Options, Tag};
 use snafu::{OptionExt as _, Snafu, ensure};
 
 use crate::{
+    config::metadata::MarkerStyle,
     parse::{self, Spanned},
-    sync::marker::MAGIC,
+    sync::marker::{self, MAGIC},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum Marker<'a> {
     Replace(SpannedReplaceSpecifier<'a>),
-    Start(SpannedReplaceSpecifier<'a>),
+    /// Start marker with the hash of the contents recorded by the last synchronization (`sha=<hash>`).
+    Start(SpannedReplaceSpecifier<'a>, Option<Input<'a>>),
     End,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) struct ReplaceSpecifier<'a> {
     pub(super) kind: Spanned<&'a str>,
+    pub(super) package: Option<Spanned<&'a str>>,
     pub(super) group: Option<Spanned<&'a str>>,
+    pub(super) args: Arguments<'a>,
+}
+
+/// The `key=value` arguments of a marker.
+///
+/// Holds the source text of the arguments, which is already validated by the
+/// parser, so that [`ReplaceSpecifier`] can remain `Copy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Arguments<'a>(Input<'a>);
+
+impl<'a> Arguments<'a> {
+    pub(super) fn iter(&self) -> impl Iterator<Item = Argument<'a>> {
+        let mut rest = self.0;
+        iter::from_fn(move || {
+            let (arg, tail) =
+                parse_argument(rest).expect("arguments should be validated by the parser")?;
+            rest = tail;
+            Some(arg)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Argument<'a> {
+    pub(super) key: Spanned<&'a str>,
+    pub(super) value: Spanned<&'a str>,
+    pub(super) span: SourceSpan,
 }
 
 #[derive(Debug, Snafu, Diagnostic)]
@@ -42,16 +75,50 @@ pub(crate) enum ParseMarkerError {
 #[derive(Debug)]
 pub(super) struct MarkerParser<'a> {
     markdown: &'a str,
+    style: MarkerStyle,
     parser: OffsetIter<'a>,
+    /// Offset of the next line to scan, for styles other than HTML comments.
+    offset: usize,
+    /// Ranges of code blocks, in which markers are not recognized.
+    code_blocks: Vec<ops::Range<usize>>,
 }
 
 impl<'a> MarkerParser<'a> {
-    pub(super) fn new(markdown: &'a str) -> Self {
-        let parser = pulldown_cmark::Parser::new_ext(markdown, Options::all()).into_offset_iter();
-        Self { markdown, parser }
+    pub(super) fn new(markdown: &'a str, style: MarkerStyle) -> Self {
+        let mut parser =
+            pulldown_cmark::Parser::new_ext(markdown, Options::all()).into_offset_iter();
+        let code_blocks = match style {
+            MarkerStyle::Html => vec![],
+            // Markers in other styles are not HTML, so they are found by lines.
+            MarkerStyle::LinkReference | MarkerStyle::Mdx => parser
+                .by_ref()
+                .filter_map(|(event, range)| {
+                    matches!(event, Event::Start(Tag::CodeBlock(_))).then_some(range)
+                })
+                .collect(),
+            MarkerStyle::Asciidoc => asciidoc_code_blocks(markdown),
+            MarkerStyle::Rst => rst_code_blocks(markdown),
+        };
+        Self {
+            markdown,
+            style,
+            parser,
+            offset: 0,
+            code_blocks,
+        }
     }
 
     pub(super) fn try_next(&mut self) -> Result<Option<SpannedMarker<'a>>, ParseMarkerError> {
+        match self.style {
+            MarkerStyle::Html => self.try_next_html(),
+            MarkerStyle::LinkReference
+            | MarkerStyle::Mdx
+            | MarkerStyle::Asciidoc
+            | MarkerStyle::Rst => self.try_next_line(),
+        }
+    }
+
+    fn try_next_html(&mut self) -> Result<Option<SpannedMarker<'a>>, ParseMarkerError> {
         for (event, range) in self.parser.by_ref() {
             if let Event::Html(_html) = event {
                 // Use the original markdown slice for this HTML event instead of the
@@ -64,13 +131,87 @@ impl<'a> MarkerParser<'a> {
                 // <https://github.com/pulldown-cmark/pulldown-cmark/blob/07bae2459d90175b661d42b8acf207382e111ae5/pulldown-cmark/src/parse.rs#L2404-L2406>
                 let html = &self.markdown[range.clone()];
                 let html = Spanned::new(html, range);
-                if let Some(marker) = parse_marker(html)? {
+                if let Some(marker) = parse_marker(html, self.style)? {
                     return Ok(Some(marker));
                 }
             }
         }
         Ok(None)
     }
+
+    fn try_next_line(&mut self) -> Result<Option<SpannedMarker<'a>>, ParseMarkerError> {
+        while self.offset < self.markdown.len() {
+            let start = self.offset;
+            let rest = &self.markdown[start..];
+            let end = start + rest.find('\n').map_or(rest.len(), |idx| idx + 1);
+            self.offset = end;
+            if self.code_blocks.iter().any(|range| range.contains(&start)) {
+                continue;
+            }
+            let line = Spanned::new(&self.markdown[start..end], start..end);
+            if let Some(marker) = parse_marker(line, self.style)? {
+                return Ok(Some(marker));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the lines of `text` with their offsets, including the line endings.
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    })
+}
+
+/// Returns the ranges of verbatim delimited blocks (listing, literal, passthrough,
+/// comment, and fenced code blocks) in an Asciidoc document.
+fn asciidoc_code_blocks(text: &str) -> Vec<ops::Range<usize>> {
+    let is_delimiter = |line: &str| {
+        line.starts_with("```")
+            || (line.len() >= 4
+                && ['-', '.', '+', '/']
+                    .iter()
+                    .any(|c| line.chars().all(|ch| ch == *c)))
+    };
+
+    let mut blocks = vec![];
+    let mut current = None;
+    for (start, line) in lines(text) {
+        let delimiter = line.trim_end();
+        match current {
+            None if is_delimiter(delimiter) => current = Some((start, delimiter)),
+            Some((block_start, open))
+                if delimiter == open || (open.starts_with("```") && delimiter == "```") =>
+            {
+                blocks.push(block_start..start + line.len());
+                current = None;
+            }
+            _ => {}
+        }
+    }
+    blocks.extend(current.map(|(start, _open)| start..text.len()));
+    blocks
+}
+
+/// Returns the ranges of indented lines in a reStructuredText document.
+///
+/// Literal blocks and directive contents are indented, so markers must be
+/// written at the start of lines.
+fn rst_code_blocks(text: &str) -> Vec<ops::Range<usize>> {
+    let mut blocks: Vec<ops::Range<usize>> = vec![];
+    for (start, line) in lines(text) {
+        if !line.starts_with([' ', '\t']) {
+            continue;
+        }
+        match blocks.last_mut() {
+            Some(block) if block.end == start => block.end = start + line.len(),
+            _ => blocks.push(start..start + line.len()),
+        }
+    }
+    blocks
 }
 
 type Input<'a> = Spanned<&'a str>;
@@ -82,18 +223,29 @@ type SpannedToken<'a> = Spanned<Token<'a>>;
 //
 // marker ::= replace-marker | start-marker | end-marker
 // replace-marker ::= "<!-- cargo-sync-rdme " specifier " -->"
-// start-marker ::= "<!-- cargo-sync-rdme " specifier " [[ -->"
+// start-marker ::= "<!-- cargo-sync-rdme " specifier " [[" [ " sha=" bare-value ] " -->"
 // end-marker ::= "<!-- cargo-sync-rdme ]] -->"
-// specifier ::= marker-kind [ ":" group-name ]
+//
+// In the other marker styles, `<!--` and `-->` are replaced with `[//]: # (`
+// and `)`, or `{/*` and `*/}`.
+// specifier ::= marker-kind [ "@" package-name ] [ ":" group-name ] { argument }
 // marker-kind ::= ident
-// group-name ::= ident
+// package-name ::= ident
+// group-name ::= [A-Za-z./] [^\s"=\[\]]* | quoted-value
+// argument ::= ident "=" argument-value
+// argument-value ::= bare-value | quoted-value
+// bare-value ::= [^\s"=\[\]]+
+// quoted-value ::= '"' [^"]* '"'
 // ident ::= [A-Za-z][-_A-Za-z0-9]*
 
-pub(super) fn parse_marker(html: Input<'_>) -> Result<Option<SpannedMarker<'_>>, ParseMarkerError> {
+pub(super) fn parse_marker(
+    html: Input<'_>,
+    style: MarkerStyle,
+) -> Result<Option<SpannedMarker<'_>>, ParseMarkerError> {
     let html = html.trim();
     let html_span = html.span;
 
-    let Some(comment_body) = trim_comment(html) else {
+    let Some(comment_body) = trim_comment(html, style) else {
         return Ok(None);
     };
     let Some(marker_body) = trim_magic(comment_body) else {
@@ -110,8 +262,28 @@ pub(super) fn parse_marker(html: Input<'_>) -> Result<Option<SpannedMarker<'_>>,
         return Ok(Some(Spanned::new(Marker::Replace(specifier), html_span)));
     }
     let (_token, rest) = expect_token(rest, Token::StartMarkerSymbol, "end of marker or `[[`")?;
+    let (hash, rest) = parse_hash(rest)?;
     expect_end_of_marker(rest)?;
-    Ok(Some(Spanned::new(Marker::Start(specifier), html_span)))
+    Ok(Some(Spanned::new(
+        Marker::Start(specifier, hash),
+        html_span,
+    )))
+}
+
+/// Parses the optional `sha=<hash>` argument following `[[`.
+fn parse_hash(input: Input<'_>) -> Result<(Option<Input<'_>>, Input<'_>), ParseMarkerError> {
+    let Some((arg, rest)) = parse_argument(input)? else {
+        return Ok((None, input));
+    };
+    ensure!(
+        arg.key.value == "sha",
+        UnexpectedTokenSnafu {
+            token: arg.key.value,
+            expected: "end of marker or `sha=`",
+            span: arg.key.source_span(),
+        }
+    );
+    Ok((Some(arg.value), rest))
 }
 
 pub(super) fn parse_specifier(
@@ -122,22 +294,36 @@ pub(super) fn parse_specifier(
         return Ok(None);
     };
 
-    let Ok((_colon, rest)) = expect_token(rest, Token::Colon, "`:`") else {
-        return Ok(Some((
-            Spanned::new(
-                ReplaceSpecifier { kind, group: None },
-                input.prefix_of(rest).span,
-            ),
-            rest,
-        )));
+    let (package, rest) = match expect_token(rest, Token::At, "`@`") {
+        Ok((_at, rest)) => {
+            let (package, rest) = expect_ident(rest, "package name")?;
+            (Some(package), rest)
+        }
+        Err(_) => (None, rest),
+    };
+
+    let (group, rest) = match expect_token(rest, Token::Colon, "`:`") {
+        Ok((_colon, rest)) => {
+            let (group, rest) = expect_group(rest)?;
+            (Some(group), rest)
+        }
+        Err(_) => (None, rest),
     };
 
-    let (group, rest) = expect_ident(rest, "group name")?;
+    let args_start = rest;
+    let mut rest = rest;
+    while let Some((_arg, tail)) = parse_argument(rest)? {
+        rest = tail;
+    }
+    let args = Arguments(args_start.prefix_of(rest));
+
     Ok(Some((
         Spanned::new(
             ReplaceSpecifier {
                 kind,
-                group: Some(group),
+                package,
+                group,
+                args,
             },
             input.prefix_of(rest).span,
         ),
@@ -145,6 +331,93 @@ pub(super) fn parse_specifier(
     )))
 }
 
+fn expect_group(input: Input<'_>) -> Result<(Input<'_>, Input<'_>), ParseMarkerError> {
+    let input = input.trim_start();
+    if !input
+        .value
+        .starts_with(|c| c == '"' || is_group_start_char(c))
+    {
+        // Report the offending token in the same way as other identifiers.
+        return expect_ident(input, "group name");
+    }
+    expect_value(input, "group name")
+}
+
+fn is_group_start_char(c: char) -> bool {
+    parse::is_ident_start(c) || matches!(c, '.' | '/')
+}
+
+fn parse_argument(input: Input<'_>) -> Result<Option<(Argument<'_>, Input<'_>)>, ParseMarkerError> {
+    // An identifier is parsed as an argument key only when followed by `=`, so
+    // that other tokens are reported as unexpected by the caller.
+    let Some((
+        Spanned {
+            value: Token::Ident(key),
+            span,
+        },
+        rest,
+    )) = next_token(input)
+    else {
+        return Ok(None);
+    };
+    if !next_token(rest).is_some_and(|(token, _)| token.value == Token::Equals) {
+        return Ok(None);
+    }
+    let key = Spanned::new(key, span);
+    let (_equals, rest) = expect_token(rest, Token::Equals, "`=`")?;
+    let (value, rest) = expect_argument_value(rest)?;
+    let span = input.trim_start().prefix_of(rest).source_span();
+    Ok(Some((Argument { key, value, span }, rest)))
+}
+
+fn expect_argument_value(input: Input<'_>) -> Result<(Input<'_>, Input<'_>), ParseMarkerError> {
+    expect_value(input, "argument value")
+}
+
+fn expect_value<'a>(
+    input: Input<'a>,
+    expected: &str,
+) -> Result<(Input<'a>, Input<'a>), ParseMarkerError> {
+    let input = input.trim_start();
+    if let Some(quoted) = input.strip_prefix_str("\"") {
+        let (value, rest) =
+            quoted
+                .split_once_fn(|c| c == '"')
+                .with_context(|| UnexpectedEndOfMarkerSnafu {
+                    expected: "`\"`",
+                    span: input.end().source_span(),
+                })?;
+        return Ok((value, rest));
+    }
+
+    let end = input
+        .value
+        .find(|c: char| !is_bare_value_char(c))
+        .unwrap_or(input.value.len());
+    let value = input.substr(&input.value[..end]);
+    let rest = input.substr(&input.value[end..]);
+    if value.value.is_empty() {
+        let Some((token, _rest)) = next_token(input) else {
+            return UnexpectedEndOfMarkerSnafu {
+                expected,
+                span: input.end().source_span(),
+            }
+            .fail();
+        };
+        return UnexpectedTokenSnafu {
+            token: token.value.to_string(),
+            expected,
+            span: token.source_span(),
+        }
+        .fail();
+    }
+    Ok((value, rest))
+}
+
+pub(super) fn is_bare_value_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '"' | '=' | '[' | ']')
+}
+
 fn trim_magic(comment_body: Input<'_>) -> Option<Input<'_>> {
     let (head, tail) = comment_body
         .split_once_fn(char::is_whitespace)
@@ -155,12 +428,13 @@ fn trim_magic(comment_body: Input<'_>) -> Option<Input<'_>> {
     Some(tail.trim())
 }
 
-fn trim_comment(html: Input<'_>) -> Option<Input<'_>> {
+fn trim_comment(html: Input<'_>, style: MarkerStyle) -> Option<Input<'_>> {
+    let (open, close) = marker::delimiters(style);
     let comment_body = html
         .trim()
-        .strip_prefix_str("<!--")?
+        .strip_prefix_str(open.trim_end())?
         .trim_start()
-        .strip_suffix_str("-->")?
+        .strip_suffix_str(close.trim_start())?
         .trim_end();
     Some(comment_body)
 }
@@ -169,6 +443,8 @@ fn trim_comment(html: Input<'_>) -> Option<Input<'_>> {
 enum Token<'a> {
     Ident(&'a str),
     Colon,
+    At,
+    Equals,
     UnknownChar(&'a str),
     StartMarkerSymbol,
     EndMarkerSymbol,
@@ -179,6 +455,8 @@ impl Display for Token<'_> {
         match self {
             Token::Ident(s) | Token::UnknownChar(s) => s.fmt(f),
             Token::Colon => ":".fmt(f),
+            Token::At => "@".fmt(f),
+            Token::Equals => "=".fmt(f),
             Token::StartMarkerSymbol => "[[".fmt(f),
             Token::EndMarkerSymbol => "]]".fmt(f),
         }
@@ -227,6 +505,14 @@ fn next_token(input: Input<'_>) -> Option<(SpannedToken<'_>, Input<'_>)> {
         let token = Spanned::new(Token::Colon, token.span);
         return Some((token, rest));
     }
+    if let Some((token, rest)) = eat_str(input, "@") {
+        let token = Spanned::new(Token::At, token.span);
+        return Some((token, rest));
+    }
+    if let Some((token, rest)) = eat_str(input, "=") {
+        let token = Spanned::new(Token::Equals, token.span);
+        return Some((token, rest));
+    }
     if let Some((token, rest)) = eat_ident(input) {
         let token = Spanned::new(Token::Ident(token.value), token.span);
         return Some((token, rest));
@@ -307,7 +593,7 @@ mod tests {
 
         #[track_caller]
         pub(crate) fn into_start(self) -> SpannedReplaceSpecifier<'a> {
-            let Self::Start(specifier) = self else {
+            let Self::Start(specifier, _hash) = self else {
                 panic!("unexpected marker: {self:?}");
             };
             specifier
@@ -347,7 +633,7 @@ mod tests {
     #[test]
     fn parse_marker_parses_markers() {
         let source = Spanned::from_str("<!-- cargo-sync-rdme kind:group -->");
-        let marker = parse_marker(source).unwrap().unwrap();
+        let marker = parse_marker(source, MarkerStyle::Html).unwrap().unwrap();
         source.assert_spanned(marker, "<!-- cargo-sync-rdme kind:group -->");
         let specifier = marker.value.into_replace();
         source.assert_spanned(specifier, "kind:group");
@@ -355,23 +641,40 @@ mod tests {
         source.assert_spanned_str(specifier.value.group.unwrap(), "group");
 
         let source = Spanned::from_str("<!-- cargo-sync-rdme kind:group [[ -->");
-        let marker = parse_marker(source).unwrap().unwrap();
+        let marker = parse_marker(source, MarkerStyle::Html).unwrap().unwrap();
         source.assert_spanned(marker, "<!-- cargo-sync-rdme kind:group [[ -->");
         let specifier = marker.value.into_start();
         source.assert_spanned(specifier, "kind:group");
         source.assert_spanned_str(specifier.value.kind, "kind");
         source.assert_spanned_str(specifier.value.group.unwrap(), "group");
 
+        let source = Spanned::from_str("<!-- cargo-sync-rdme kind [[ sha=0123abcd -->");
+        let marker = parse_marker(source, MarkerStyle::Html).unwrap().unwrap();
+        source.assert_spanned(marker, "<!-- cargo-sync-rdme kind [[ sha=0123abcd -->");
+        let Marker::Start(specifier, Some(hash)) = marker.value else {
+            panic!("unexpected marker: {marker:?}");
+        };
+        source.assert_spanned(specifier, "kind");
+        source.assert_spanned_str(hash, "0123abcd");
+
         let source = Spanned::from_str("<!-- cargo-sync-rdme ]] -->");
-        let marker = parse_marker(source).unwrap().unwrap();
+        let marker = parse_marker(source, MarkerStyle::Html).unwrap().unwrap();
         source.assert_spanned(marker, "<!-- cargo-sync-rdme ]] -->");
         marker.value.into_end();
+
+        // The closing delimiter is only matched at the end of the line.
+        let source = Spanned::from_str(r#"[//]: # (cargo-sync-rdme kind:"a(b).md" [[)"#);
+        let marker = parse_marker(source, MarkerStyle::LinkReference)
+            .unwrap()
+            .unwrap();
+        let specifier = marker.value.into_start();
+        source.assert_spanned_str(specifier.value.group.unwrap(), "a(b).md");
     }
 
     #[test]
     fn parse_marker_rejects_invalid_markers() {
         let source = Spanned::from_str("<!-- cargo-sync-rdme -->");
-        let err = parse_marker(source).unwrap_err();
+        let err = parse_marker(source, MarkerStyle::Html).unwrap_err();
         let ParseMarkerError::UnexpectedEndOfMarker { expected, span } = err else {
             panic!("unexpected error: {err:?}");
         };
@@ -380,43 +683,61 @@ mod tests {
         assert_eq!(span.offset(), source.value.len() - " -->".len());
 
         let source = Spanned::from_str("<!-- cargo-sync-rdme [[ -->");
-        let (token, expected, span) = parse_marker(source).unwrap_err().into_unexpected_token();
+        let (token, expected, span) = parse_marker(source, MarkerStyle::Html)
+            .unwrap_err()
+            .into_unexpected_token();
         assert_eq!(token, "[[");
         assert_eq!(expected, "marker kind or `]]`");
         source.assert_source_span(span, "[[");
 
         let source = Spanned::from_str("<!-- cargo-sync-rdme badge:123 -->");
-        let (token, expected, span) = parse_marker(source).unwrap_err().into_unexpected_token();
+        let (token, expected, span) = parse_marker(source, MarkerStyle::Html)
+            .unwrap_err()
+            .into_unexpected_token();
         assert_eq!(token, "1");
         assert_eq!(expected, "group name");
         source.assert_source_span(span, "1");
 
         let source = Spanned::from_str("<!-- cargo-sync-rdme ]] xxx -->");
-        let (token, expected, span) = parse_marker(source).unwrap_err().into_unexpected_token();
+        let (token, expected, span) = parse_marker(source, MarkerStyle::Html)
+            .unwrap_err()
+            .into_unexpected_token();
         assert_eq!(token, "xxx");
         assert_eq!(expected, "end of marker");
         source.assert_source_span(span, "xxx");
 
         let source = Spanned::from_str("<!-- cargo-sync-rdme badge:bar xxx -->");
-        let (token, expected, span) = parse_marker(source).unwrap_err().into_unexpected_token();
+        let (token, expected, span) = parse_marker(source, MarkerStyle::Html)
+            .unwrap_err()
+            .into_unexpected_token();
         assert_eq!(token, "xxx");
         assert_eq!(expected, "end of marker or `[[`");
         source.assert_source_span(span, "xxx");
 
         let source = Spanned::from_str("<!-- cargo-sync-rdme badge:bar [[ xxx -->");
-        let (token, expected, span) = parse_marker(source).unwrap_err().into_unexpected_token();
+        let (token, expected, span) = parse_marker(source, MarkerStyle::Html)
+            .unwrap_err()
+            .into_unexpected_token();
         assert_eq!(token, "xxx");
         assert_eq!(expected, "end of marker");
         source.assert_source_span(span, "xxx");
+
+        let source = Spanned::from_str("<!-- cargo-sync-rdme badge:bar [[ md5=0123 -->");
+        let (token, expected, span) = parse_marker(source, MarkerStyle::Html)
+            .unwrap_err()
+            .into_unexpected_token();
+        assert_eq!(token, "md5");
+        assert_eq!(expected, "end of marker or `sha=`");
+        source.assert_source_span(span, "md5");
     }
 
     #[test]
     fn parse_marker_ignores_non_markers() {
         let source = Spanned::from_str("<p>paragraph</p>");
-        assert!(parse_marker(source).unwrap().is_none());
+        assert!(parse_marker(source, MarkerStyle::Html).unwrap().is_none());
 
         let source = Spanned::from_str("<!-- test -->");
-        assert!(parse_marker(source).unwrap().is_none());
+        assert!(parse_marker(source, MarkerStyle::Html).unwrap().is_none());
     }
 
     #[test]
@@ -453,6 +774,128 @@ mod tests {
         assert!(parse_specifier(source).unwrap().is_none());
     }
 
+    #[test]
+    fn parse_specifier_parses_arguments() {
+        let source = Spanned::from_str(r#" kind:group a=1 b-c = "x y" d=./foo.md [[ "#);
+        let (specifier, rest) = parse_specifier(source).unwrap().unwrap();
+        source.assert_spanned(specifier, r#"kind:group a=1 b-c = "x y" d=./foo.md"#);
+        source.assert_spanned_str(specifier.value.kind, "kind");
+        source.assert_spanned_str(specifier.value.group.unwrap(), "group");
+        source.assert_spanned_str(rest, " [[ ");
+
+        let args = specifier.value.args.iter().collect::<Vec<_>>();
+        assert_eq!(args.len(), 3);
+        source.assert_spanned_str(args[0].key, "a");
+        source.assert_spanned_str(args[0].value, "1");
+        source.assert_source_span(args[0].span, "a=1");
+        source.assert_spanned_str(args[1].key, "b-c");
+        source.assert_spanned_str(args[1].value, "x y");
+        source.assert_source_span(args[1].span, r#"b-c = "x y""#);
+        source.assert_spanned_str(args[2].key, "d");
+        source.assert_spanned_str(args[2].value, "./foo.md");
+        source.assert_source_span(args[2].span, "d=./foo.md");
+
+        let source = Spanned::from_str("kind a=1[[");
+        let (specifier, rest) = parse_specifier(source).unwrap().unwrap();
+        source.assert_spanned(specifier, "kind a=1");
+        source.assert_spanned_str(rest, "[[");
+
+        let source = Spanned::from_str("kind a=\"\" xxx");
+        let (specifier, rest) = parse_specifier(source).unwrap().unwrap();
+        let args = specifier.value.args.iter().collect::<Vec<_>>();
+        assert_eq!(args.len(), 1);
+        source.assert_spanned_str(args[0].value, "");
+        source.assert_spanned_str(rest, " xxx");
+    }
+
+    #[test]
+    fn parse_specifier_rejects_invalid_arguments() {
+        let source = Spanned::from_str("kind a=");
+        let (expected, span) = parse_specifier(source).unwrap_err().into_unexpected_eom();
+        assert_eq!(expected, "argument value");
+        assert_eq!(span.offset(), source.value.len());
+
+        let source = Spanned::from_str("kind a= [[");
+        let (token, expected, span) = parse_specifier(source).unwrap_err().into_unexpected_token();
+        assert_eq!(token, "[[");
+        assert_eq!(expected, "argument value");
+        source.assert_source_span(span, "[[");
+
+        let source = Spanned::from_str("kind a=\"foo");
+        let (expected, span) = parse_specifier(source).unwrap_err().into_unexpected_eom();
+        assert_eq!(expected, "`\"`");
+        assert_eq!(span.offset(), source.value.len());
+
+        let source = Spanned::from_str(r#"<!-- cargo-sync-rdme kind a="x"y" -->"#);
+        let (token, expected, span) = parse_marker(source, MarkerStyle::Html)
+            .unwrap_err()
+            .into_unexpected_token();
+        assert_eq!(token, "y");
+        assert_eq!(expected, "end of marker or `[[`");
+        source.assert_source_span(span, "y");
+
+        let source = Spanned::from_str("<!-- cargo-sync-rdme kind a=1 b -->");
+        let (token, expected, span) = parse_marker(source, MarkerStyle::Html)
+            .unwrap_err()
+            .into_unexpected_token();
+        assert_eq!(token, "b");
+        assert_eq!(expected, "end of marker or `[[`");
+        source.assert_source_span(span, "b");
+    }
+
+    #[test]
+    fn parse_specifier_parses_paths() {
+        let source = Spanned::from_str("kind: foo::bar_baz::Qux a=1");
+        let (specifier, rest) = parse_specifier(source).unwrap().unwrap();
+        source.assert_spanned(specifier, "kind: foo::bar_baz::Qux a=1");
+        source.assert_spanned_str(specifier.value.kind, "kind");
+        source.assert_spanned_str(specifier.value.group.unwrap(), "foo::bar_baz::Qux");
+        source.assert_spanned_str(rest, "");
+
+        let source = Spanned::from_str("kind:../examples/foo.toml [[");
+        let (specifier, rest) = parse_specifier(source).unwrap().unwrap();
+        source.assert_spanned_str(specifier.value.group.unwrap(), "../examples/foo.toml");
+        source.assert_spanned_str(rest, " [[");
+
+        let source = Spanned::from_str(r#"kind:"dir name/foo.md" a=1"#);
+        let (specifier, rest) = parse_specifier(source).unwrap().unwrap();
+        source.assert_spanned(specifier, r#"kind:"dir name/foo.md" a=1"#);
+        source.assert_spanned_str(specifier.value.group.unwrap(), "dir name/foo.md");
+        source.assert_spanned_str(rest, "");
+
+        let source = Spanned::from_str("kind:\"foo");
+        let (expected, span) = parse_specifier(source).unwrap_err().into_unexpected_eom();
+        assert_eq!(expected, "`\"`");
+        assert_eq!(span.offset(), source.value.len());
+
+        let source = Spanned::from_str("kind: [[");
+        let (token, expected, span) = parse_specifier(source).unwrap_err().into_unexpected_token();
+        assert_eq!(token, "[[");
+        assert_eq!(expected, "group name");
+        source.assert_source_span(span, "[[");
+    }
+
+    #[test]
+    fn parse_specifier_parses_package() {
+        let source = Spanned::from_str("kind@my-pkg:foo::Bar a=1 [[");
+        let (specifier, rest) = parse_specifier(source).unwrap().unwrap();
+        source.assert_spanned(specifier, "kind@my-pkg:foo::Bar a=1");
+        source.assert_spanned_str(specifier.value.kind, "kind");
+        source.assert_spanned_str(specifier.value.package.unwrap(), "my-pkg");
+        source.assert_spanned_str(specifier.value.group.unwrap(), "foo::Bar");
+        source.assert_spanned_str(rest, " [[");
+
+        let source = Spanned::from_str("kind@my_pkg");
+        let (specifier, rest) = parse_specifier(source).unwrap().unwrap();
+        source.assert_spanned_str(specifier.value.package.unwrap(), "my_pkg");
+        assert!(specifier.value.group.is_none());
+        source.assert_spanned_str(rest, "");
+
+        let source = Spanned::from_str("kind:group");
+        let (specifier, _rest) = parse_specifier(source).unwrap().unwrap();
+        assert!(specifier.value.package.is_none());
+    }
+
     #[test]
     fn parse_specifier_rejects_invalid_specifiers() {
         let source = Spanned::from_str(" kind: ");
@@ -461,6 +904,17 @@ mod tests {
         source.assert_source_span(span, "");
         assert_eq!(span.offset(), source.value.len());
 
+        let source = Spanned::from_str("kind@");
+        let (expected, span) = parse_specifier(source).unwrap_err().into_unexpected_eom();
+        assert_eq!(expected, "package name");
+        assert_eq!(span.offset(), source.value.len());
+
+        let source = Spanned::from_str("kind@:group");
+        let (token, expected, span) = parse_specifier(source).unwrap_err().into_unexpected_token();
+        assert_eq!(token, ":");
+        assert_eq!(expected, "package name");
+        source.assert_source_span(span, ":");
+
         let source = Spanned::from_str(" :group");
         assert!(parse_specifier(source).unwrap().is_none());
     }
@@ -482,22 +936,22 @@ mod tests {
     #[test]
     fn trim_comment_trims_comment_tags() {
         let source = Spanned::from_str("<!-- test -->");
-        let trimmed = trim_comment(source).unwrap();
+        let trimmed = trim_comment(source, MarkerStyle::Html).unwrap();
         source.assert_spanned_str(trimmed, "test");
 
         let source = Spanned::from_str("<!--test-->");
-        let trimmed = trim_comment(source).unwrap();
+        let trimmed = trim_comment(source, MarkerStyle::Html).unwrap();
         source.assert_spanned_str(trimmed, "test");
     }
 
     #[test]
     fn trim_comment_ignores_non_comment() {
         let source = Spanned::from_str("test");
-        assert!(trim_comment(source).is_none());
+        assert!(trim_comment(source, MarkerStyle::Html).is_none());
         let source = Spanned::from_str("<!--test");
-        assert!(trim_comment(source).is_none());
+        assert!(trim_comment(source, MarkerStyle::Html).is_none());
         let source = Spanned::from_str("<p>paragraph</p>");
-        assert!(trim_comment(source).is_none());
+        assert!(trim_comment(source, MarkerStyle::Html).is_none());
     }
 
     #[test]
@@ -552,25 +1006,25 @@ mod tests {
 
     #[test]
     fn next_token_returns_unknown_tokens() {
-        let source = Spanned::from_str("1x x123@#");
+        let source = Spanned::from_str("1x x123%#");
         let (token, rest) = next_token(source).unwrap();
         assert_eq!(token.value, Token::UnknownChar("1"));
         source.assert_spanned(token, "1");
-        source.assert_spanned_str(rest, "x x123@#");
+        source.assert_spanned_str(rest, "x x123%#");
 
         let (token, rest) = next_token(rest).unwrap();
         assert_eq!(token.value, Token::Ident("x"));
         source.assert_spanned(token, "x");
-        source.assert_spanned_str(rest, " x123@#");
+        source.assert_spanned_str(rest, " x123%#");
 
         let (token, rest) = next_token(rest).unwrap();
         assert_eq!(token.value, Token::Ident("x123"));
         source.assert_spanned(token, "x123");
-        source.assert_spanned_str(rest, "@#");
+        source.assert_spanned_str(rest, "%#");
 
         let (token, rest) = next_token(rest).unwrap();
-        assert_eq!(token.value, Token::UnknownChar("@"));
-        source.assert_spanned(token, "@");
+        assert_eq!(token.value, Token::UnknownChar("%"));
+        source.assert_spanned(token, "%");
         source.assert_spanned_str(rest, "#");
     }
 
@@ -585,7 +1039,7 @@ mod tests {
             <!-- cargo-sync-rdme kind:group -->
         "};
         let source = Spanned::from_str(source);
-        let mut parser = MarkerParser::new(source.value);
+        let mut parser = MarkerParser::new(source.value, MarkerStyle::Html);
 
         let marker = parser.try_next().unwrap().unwrap();
         source.assert_spanned(marker, "<!-- cargo-sync-rdme kind:group [[ -->");
@@ -619,7 +1073,7 @@ mod tests {
             <!-- cargo-sync-rdme kind:group \0 -->
         "};
         let source = Spanned::from_str(source);
-        let mut parser = MarkerParser::new(source.value);
+        let mut parser = MarkerParser::new(source.value, MarkerStyle::Html);
 
         let (token, expected, span) = parser.try_next().unwrap_err().into_unexpected_token();
         assert_eq!(token, "\0");
@@ -1,18 +1,23 @@
-use std::sync::Arc;
+use std::{collections::HashMap, str::FromStr as _, sync::Arc};
 
+use cargo_metadata::Metadata;
 use miette::{Diagnostic, SourceSpan};
-use snafu::Snafu;
+use snafu::{OptionExt as _, Snafu, ensure};
 
 use crate::{
-    parse::Spanned,
+    config::metadata::MarkerStyle,
+    parse::{self, Spanned},
     sync::{
         ManifestFile,
         marker::{
-            ResolvedReplaceSpecifier,
-            parse::ReplaceSpecifier,
+            ApiSummaryOptions, ChangelogOptions, ChangelogSection, CliHelpLang, CliHelpOptions,
+            IncludeBase, IncludeFormat, IncludeOptions, InstallStyle, LinkStyle, MemberColumn,
+            ResolvedReplaceSpecifier, RustdocOptions, TocOptions, WorkspaceMembersOptions,
+            parse::{Argument, Arguments, ReplaceSpecifier},
             scan::{ScanError, Scanner},
         },
     },
+    with_source::ReadFileError,
 };
 
 #[derive(Debug, Snafu, Diagnostic)]
@@ -24,6 +29,13 @@ pub(super) enum ResolveMarkerError {
         #[diagnostic_source]
         source: ScanError,
     },
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    ReadPackageManifest {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: ReadFileError,
+    },
     #[snafu(display("unknown marker kind: {kind}"))]
     UnknownMarkerKind {
         kind: String,
@@ -37,6 +49,80 @@ pub(super) enum ResolveMarkerError {
         #[label]
         span: SourceSpan,
     },
+    #[snafu(display("marker kind `{kind}` cannot have package qualifier `@{package}`"))]
+    #[diagnostic(help(
+        "package qualifiers are supported by `title`, `badge`, and `rustdoc` markers"
+    ))]
+    UnexpectedPackageForSpecifier {
+        kind: String,
+        package: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("package `{package}` is not a member of the workspace"))]
+    PackageNotFound {
+        package: String,
+        #[help]
+        help: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("marker kind `{kind}` requires a group suffix"))]
+    MissingGroupForSpecifier {
+        kind: String,
+        #[help]
+        help: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("marker kind `{kind}` requires argument `{key}`"))]
+    MissingArgument {
+        kind: String,
+        key: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("invalid item path: `{path}`"))]
+    #[diagnostic(help("item paths must be `::`-separated identifiers, e.g. `module::Type`"))]
+    InvalidItemPath {
+        path: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("marker kind `{kind}` does not accept argument `{key}`"))]
+    UnknownArgument {
+        kind: String,
+        key: String,
+        #[help]
+        help: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("argument `{key}` is specified more than once"))]
+    DuplicateArgument {
+        key: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("invalid value for argument `{key}`: `{value}`"))]
+    InvalidArgumentValue {
+        key: String,
+        value: String,
+        #[help]
+        help: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display(
+        "minimum heading depth {min_depth} is greater than maximum heading depth {max_depth}"
+    ))]
+    #[diagnostic(help("`min-depth` must be less than or equal to `max-depth`"))]
+    InvalidTocDepthRange {
+        min_depth: u8,
+        max_depth: u8,
+        #[label]
+        span: SourceSpan,
+    },
     #[snafu(display(
         "default badge group is not configured in the package manifest: package.metadata.cargo-sync-rdme.badge.badges"
     ))]
@@ -52,31 +138,99 @@ pub(super) enum ResolveMarkerError {
         #[label]
         span: SourceSpan,
     },
+    #[snafu(display(
+        "template not found in the package manifest: package.metadata.cargo-sync-rdme.templates.{name}"
+    ))]
+    NoSuchTemplate {
+        name: String,
+        #[help]
+        help: String,
+        #[label]
+        span: SourceSpan,
+    },
+}
+
+#[derive(Debug)]
+pub(super) struct ResolvedChunk {
+    pub(super) specifier: ResolvedReplaceSpecifier,
+    /// Whether the contents of the region do not match the hash recorded in the start marker.
+    pub(super) hand_edited: bool,
 }
 
 #[derive(Debug)]
 pub(super) struct Resolver<'markdown, 'manifest> {
     manifest: &'manifest ManifestFile,
+    workspace: &'manifest Metadata,
+    /// Manifests of the workspace members referred to by `@<package>` qualifiers.
+    package_manifests: HashMap<String, ManifestFile>,
     scanner: Scanner<'markdown>,
 }
 
 impl<'markdown, 'manifest> Resolver<'markdown, 'manifest> {
-    pub(super) fn new(markdown: &'markdown str, manifest: &'manifest ManifestFile) -> Self {
+    pub(super) fn new(
+        markdown: &'markdown str,
+        style: MarkerStyle,
+        manifest: &'manifest ManifestFile,
+        workspace: &'manifest Metadata,
+    ) -> Self {
         Self {
             manifest,
-            scanner: Scanner::new(markdown),
+            workspace,
+            package_manifests: HashMap::new(),
+            scanner: Scanner::new(markdown, style),
         }
     }
 
     pub(super) fn try_next(
         &mut self,
-    ) -> Result<Option<Spanned<ResolvedReplaceSpecifier>>, ResolveMarkerError> {
+    ) -> Result<Option<Spanned<ResolvedChunk>>, ResolveMarkerError> {
         let Some(chunk) = self.scanner.try_next()? else {
             return Ok(None);
         };
-        let resolved = resolve_specifier(chunk.value.specifier, self.manifest)?;
+        let specifier = chunk.value.specifier;
+        let manifest = match specifier.value.package {
+            Some(package) => self.package_manifest(package)?,
+            None => self.manifest,
+        };
+        let resolved = ResolvedChunk {
+            specifier: resolve_specifier(specifier, manifest)?,
+            hand_edited: chunk.value.hand_edited,
+        };
         Ok(Some(Spanned::new(resolved, chunk.span)))
     }
+
+    fn package_manifest(
+        &mut self,
+        package: Spanned<&str>,
+    ) -> Result<&ManifestFile, ResolveMarkerError> {
+        if !self.package_manifests.contains_key(package.value) {
+            let members = self.workspace.workspace_packages();
+            let member = members
+                .iter()
+                .find(|member| member.name.as_str() == package.value)
+                .with_context(|| PackageNotFoundSnafu {
+                    package: package.value,
+                    help: format!(
+                        "available packages: {}",
+                        members
+                            .iter()
+                            .map(|member| member.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    span: package.source_span(),
+                })?;
+            let manifest = ManifestFile::from_toml("package manifest", &member.manifest_path)?;
+            self.package_manifests
+                .insert(package.value.to_owned(), manifest);
+        }
+        Ok(&self.package_manifests[package.value])
+    }
+
+    /// Returns the manifests of the workspace members referred to by `@<package>` qualifiers.
+    pub(super) fn into_package_manifests(self) -> HashMap<String, ManifestFile> {
+        self.package_manifests
+    }
 }
 
 pub(super) fn resolve_specifier(
@@ -85,17 +239,94 @@ pub(super) fn resolve_specifier(
 ) -> Result<ResolvedReplaceSpecifier, ResolveMarkerError> {
     let kind = specifier.value.kind;
     let group = specifier.value.group;
+    let args = specifier.value.args;
+    let package = resolve_package(kind, specifier.value.package)?;
     match (kind.value, group) {
-        ("title", None) => return Ok(ResolvedReplaceSpecifier::Title),
-        ("rustdoc", None) => return Ok(ResolvedReplaceSpecifier::Rustdoc),
-        ("title" | "rustdoc", Some(group)) => {
+        ("title", None) => {
+            expect_no_arguments(kind, args)?;
+            return Ok(ResolvedReplaceSpecifier::Title { package });
+        }
+        ("install", None) => {
+            let style = resolve_install_style(kind, args)?;
+            return Ok(ResolvedReplaceSpecifier::Install { style });
+        }
+        ("features", None) => {
+            expect_no_arguments(kind, args)?;
+            return Ok(ResolvedReplaceSpecifier::Features);
+        }
+        ("cli-help", bin) => {
+            let options = resolve_cli_help_options(kind, args)?;
+            let bin = bin.map(|bin| Spanned::new(Arc::from(bin.value), bin.span));
+            return Ok(ResolvedReplaceSpecifier::CliHelp { bin, options });
+        }
+        ("api-summary", None) => {
+            let options = resolve_api_summary_options(kind, args)?;
+            return Ok(ResolvedReplaceSpecifier::ApiSummary { options });
+        }
+        ("changelog", None) => {
+            let options = resolve_changelog_options(kind, args)?;
+            return Ok(ResolvedReplaceSpecifier::Changelog { options });
+        }
+        ("workspace-members", None) => {
+            let options = resolve_workspace_members_options(kind, args)?;
+            return Ok(ResolvedReplaceSpecifier::WorkspaceMembers { options });
+        }
+        ("toc", None) => {
+            let options = resolve_toc_options(kind, args)?;
+            return Ok(ResolvedReplaceSpecifier::Toc { options });
+        }
+        ("rustdoc", item) => {
+            let item = item.map(resolve_item_path).transpose()?;
+            let options = resolve_rustdoc_options(kind, args)?;
+            return Ok(ResolvedReplaceSpecifier::Rustdoc {
+                package,
+                item,
+                options,
+            });
+        }
+        ("include", Some(path)) => {
+            let options = resolve_include_options(kind, args)?;
+            let path = Spanned::new(Arc::from(path.value), path.span);
+            return Ok(ResolvedReplaceSpecifier::Include { path, options });
+        }
+        ("snippet", Some(path)) => {
+            let (anchor, lang) = resolve_snippet_options(specifier, kind, args)?;
+            let path = Spanned::new(Arc::from(path.value), path.span);
+            return Ok(ResolvedReplaceSpecifier::Snippet { path, anchor, lang });
+        }
+        ("template", Some(name)) => {
+            expect_no_arguments(kind, args)?;
+            return resolve_template(name, manifest);
+        }
+        ("snippet" | "include" | "template", None) => {
+            let help = match kind.value {
+                "snippet" => {
+                    "specify the path of the file relative to the package root, e.g. `snippet:examples/basic.rs anchor=setup`"
+                }
+                "include" => {
+                    "specify the path of the file to include, e.g. `include:examples/config.toml`"
+                }
+                _ => "specify the name of the template, e.g. `template:footer`",
+            };
+            return MissingGroupForSpecifierSnafu {
+                kind: kind.value,
+                help,
+                span: specifier.source_span(),
+            }
+            .fail();
+        }
+        (
+            "title" | "features" | "install" | "toc" | "workspace-members" | "changelog"
+            | "api-summary",
+            Some(group),
+        ) => {
             return Err(ResolveMarkerError::UnexpectedGroupForSpecifier {
                 kind: kind.value.to_string(),
                 group: group.value.to_string(),
                 span: specifier.source_span(),
             });
         }
-        ("badge", _) => {}
+        ("badge", _) => expect_no_arguments(kind, args)?,
         _ => {
             return Err(ResolveMarkerError::UnknownMarkerKind {
                 kind: kind.value.to_string(),
@@ -104,6 +335,72 @@ pub(super) fn resolve_specifier(
         }
     }
 
+    resolve_badge(specifier.source_span(), package, group, manifest)
+}
+
+fn resolve_item_path(item: Spanned<&str>) -> Result<Spanned<Arc<str>>, ResolveMarkerError> {
+    ensure!(
+        item.value.split("::").all(parse::is_valid_ident),
+        InvalidItemPathSnafu {
+            path: item.value,
+            span: item.source_span(),
+        }
+    );
+    Ok(Spanned::new(Arc::from(item.value), item.span))
+}
+
+fn resolve_package(
+    kind: Spanned<&str>,
+    package: Option<Spanned<&str>>,
+) -> Result<Option<Spanned<Arc<str>>>, ResolveMarkerError> {
+    let Some(package) = package else {
+        return Ok(None);
+    };
+    ensure!(
+        matches!(kind.value, "title" | "badge" | "rustdoc"),
+        UnexpectedPackageForSpecifierSnafu {
+            kind: kind.value,
+            package: package.value,
+            span: package.source_span(),
+        }
+    );
+    Ok(Some(Spanned::new(Arc::from(package.value), package.span)))
+}
+
+fn resolve_template(
+    name: Spanned<&str>,
+    manifest: &ManifestFile,
+) -> Result<ResolvedReplaceSpecifier, ResolveMarkerError> {
+    let templates = &manifest.value().config().templates;
+    if !templates.contains_key(name.value) {
+        let mut names = templates
+            .keys()
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>();
+        names.sort();
+        let help = if names.is_empty() {
+            "define templates in `package.metadata.cargo-sync-rdme.templates`".to_owned()
+        } else {
+            format!("available templates: {}", names.join(", "))
+        };
+        return NoSuchTemplateSnafu {
+            name: name.value,
+            help,
+            span: name.source_span(),
+        }
+        .fail();
+    }
+    Ok(ResolvedReplaceSpecifier::Template {
+        name: Arc::from(name.value),
+    })
+}
+
+fn resolve_badge(
+    span: SourceSpan,
+    package: Option<Spanned<Arc<str>>>,
+    group: Option<Spanned<&str>>,
+    manifest: &ManifestFile,
+) -> Result<ResolvedReplaceSpecifier, ResolveMarkerError> {
     let badge = &manifest.value().config().badge;
     if let Some(group) = group {
         let (group, badges) = badge.groups.get_key_value(group.value).ok_or_else(|| {
@@ -114,23 +411,394 @@ pub(super) fn resolve_specifier(
             .build()
         })?;
         Ok(ResolvedReplaceSpecifier::Badge {
+            package,
             group: Some(Arc::clone(group)),
             badges: Arc::clone(badges),
         })
     } else {
-        let badges = badge.default.as_ref().ok_or_else(|| {
-            NoDefaultBadgeConfiguredSnafu {
-                span: specifier.source_span(),
-            }
-            .build()
-        })?;
+        let badges = badge
+            .default
+            .as_ref()
+            .ok_or_else(|| NoDefaultBadgeConfiguredSnafu { span }.build())?;
         Ok(ResolvedReplaceSpecifier::Badge {
+            package,
             group: None,
             badges: Arc::clone(badges),
         })
     }
 }
 
+fn resolve_rustdoc_options(
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<RustdocOptions, ResolveMarkerError> {
+    let mut options = RustdocOptions::default();
+    for arg in args.iter() {
+        match arg.key.value {
+            "heading-offset" => set_argument(
+                &mut options.heading_offset,
+                arg,
+                "an integer between 0 and 5",
+                |value| value.parse().ok().filter(|offset| *offset <= 5),
+            )?,
+            "links" => set_argument(
+                &mut options.links,
+                arg,
+                "`inline` or `reference`",
+                |value| LinkStyle::from_str(value).ok(),
+            )?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `heading-offset`, `links`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn resolve_include_options(
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<IncludeOptions, ResolveMarkerError> {
+    let mut options = IncludeOptions::default();
+    for arg in args.iter() {
+        match arg.key.value {
+            "relative-to" => set_argument(
+                &mut options.relative_to,
+                arg,
+                "`file` or `package`",
+                |value| IncludeBase::from_str(value).ok(),
+            )?,
+            "format" => set_argument(
+                &mut options.format,
+                arg,
+                "`markdown`, `code`, or `native`",
+                |value| IncludeFormat::from_str(value).ok(),
+            )?,
+            "lang" => set_argument(&mut options.lang, arg, "a language name", parse_lang)?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `relative-to`, `format`, `lang`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn resolve_install_style(
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<Option<InstallStyle>, ResolveMarkerError> {
+    let mut style = None;
+    for arg in args.iter() {
+        match arg.key.value {
+            "style" => set_argument(
+                &mut style,
+                arg,
+                "`toml`, `add`, `install`, or `binstall`",
+                |value| InstallStyle::from_str(value).ok(),
+            )?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `style`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(style)
+}
+
+fn resolve_toc_options(
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<TocOptions, ResolveMarkerError> {
+    let parse_depth = |value: &str| value.parse().ok().filter(|depth| (1..=6).contains(depth));
+    let mut options = TocOptions::default();
+    let mut depth_span = None;
+    for arg in args.iter() {
+        match arg.key.value {
+            "min-depth" => {
+                set_argument(
+                    &mut options.min_depth,
+                    arg,
+                    "an integer between 1 and 6",
+                    parse_depth,
+                )?;
+                depth_span = Some(arg.span);
+            }
+            "max-depth" => {
+                set_argument(
+                    &mut options.max_depth,
+                    arg,
+                    "an integer between 1 and 6",
+                    parse_depth,
+                )?;
+                depth_span = Some(arg.span);
+            }
+            "exclude" => set_argument(
+                &mut options.exclude,
+                arg,
+                "a comma-separated list of headings",
+                |value| {
+                    let headings = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|heading| !heading.is_empty())
+                        .map(str::to_owned)
+                        .collect::<Vec<_>>();
+                    (!headings.is_empty()).then_some(headings)
+                },
+            )?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `min-depth`, `max-depth`, `exclude`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    if let Some(span) = depth_span {
+        ensure!(
+            options.min_depth() <= options.max_depth(),
+            InvalidTocDepthRangeSnafu {
+                min_depth: options.min_depth(),
+                max_depth: options.max_depth(),
+                span,
+            }
+        );
+    }
+    Ok(options)
+}
+
+fn resolve_api_summary_options(
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<ApiSummaryOptions, ResolveMarkerError> {
+    let mut options = ApiSummaryOptions::default();
+    for arg in args.iter() {
+        match arg.key.value {
+            "heading-level" => set_argument(
+                &mut options.heading_level,
+                arg,
+                "an integer between 1 and 6",
+                |value| value.parse().ok().filter(|level| (1..=6).contains(level)),
+            )?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `heading-level`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn resolve_changelog_options(
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<ChangelogOptions, ResolveMarkerError> {
+    let mut options = ChangelogOptions::default();
+    for arg in args.iter() {
+        match arg.key.value {
+            "section" => set_argument(
+                &mut options.section,
+                arg,
+                "`latest`, a positive integer, or a version",
+                |value| ChangelogSection::from_str(value).ok(),
+            )?,
+            "heading-offset" => set_argument(
+                &mut options.heading_offset,
+                arg,
+                "an integer between 0 and 5",
+                |value| value.parse().ok().filter(|offset| *offset <= 5),
+            )?,
+            "link" => set_argument(&mut options.link, arg, "`true` or `false`", |value| {
+                value.parse().ok()
+            })?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `section`, `heading-offset`, `link`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn resolve_workspace_members_options(
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<WorkspaceMembersOptions, ResolveMarkerError> {
+    let mut options = WorkspaceMembersOptions::default();
+    for arg in args.iter() {
+        match arg.key.value {
+            "columns" => set_argument(
+                &mut options.columns,
+                arg,
+                "a comma-separated list of `name`, `description`, `version`, `links`, and `badges`",
+                |value| {
+                    let mut columns = vec![];
+                    for column in value.split(',') {
+                        let column = MemberColumn::from_str(column.trim()).ok()?;
+                        if columns.contains(&column) {
+                            return None;
+                        }
+                        columns.push(column);
+                    }
+                    Some(columns)
+                },
+            )?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `columns`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn resolve_cli_help_options(
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<CliHelpOptions, ResolveMarkerError> {
+    let mut options = CliHelpOptions::default();
+    for arg in args.iter() {
+        match arg.key.value {
+            "args" => set_argument(&mut options.args, arg, "arguments", |value| {
+                Some(value.to_owned())
+            })?,
+            "lang" => set_argument(&mut options.lang, arg, "`text` or `console`", |value| {
+                CliHelpLang::from_str(value).ok()
+            })?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `args`, `lang`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn resolve_snippet_options(
+    specifier: Spanned<ReplaceSpecifier<'_>>,
+    kind: Spanned<&str>,
+    args: Arguments<'_>,
+) -> Result<(Spanned<Arc<str>>, Option<String>), ResolveMarkerError> {
+    let mut anchor = None;
+    let mut lang = None;
+    for arg in args.iter() {
+        match arg.key.value {
+            "anchor" => set_argument(
+                &mut anchor,
+                arg,
+                "an anchor name matching `[-_A-Za-z0-9]+`",
+                |value| {
+                    is_valid_anchor_name(value)
+                        .then(|| Spanned::new(Arc::from(value), arg.value.span))
+                },
+            )?,
+            "lang" => set_argument(&mut lang, arg, "a language name", parse_lang)?,
+            _ => {
+                return UnknownArgumentSnafu {
+                    kind: kind.value,
+                    key: arg.key.value,
+                    help: "available arguments: `anchor`, `lang`",
+                    span: arg.key.source_span(),
+                }
+                .fail();
+            }
+        }
+    }
+    let anchor = anchor.with_context(|| MissingArgumentSnafu {
+        kind: kind.value,
+        key: "anchor",
+        span: specifier.source_span(),
+    })?;
+    Ok((anchor, lang))
+}
+
+fn is_valid_anchor_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn parse_lang(value: &str) -> Option<String> {
+    (!value.is_empty() && !value.contains('`')).then(|| value.to_owned())
+}
+
+fn expect_no_arguments(kind: Spanned<&str>, args: Arguments<'_>) -> Result<(), ResolveMarkerError> {
+    if let Some(arg) = args.iter().next() {
+        return UnknownArgumentSnafu {
+            kind: kind.value,
+            key: arg.key.value,
+            help: format!("marker kind `{}` does not accept any arguments", kind.value),
+            span: arg.key.source_span(),
+        }
+        .fail();
+    }
+    Ok(())
+}
+
+fn set_argument<T>(
+    slot: &mut Option<T>,
+    arg: Argument<'_>,
+    expected: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> Result<(), ResolveMarkerError> {
+    ensure!(
+        slot.is_none(),
+        DuplicateArgumentSnafu {
+            key: arg.key.value,
+            span: arg.span,
+        }
+    );
+    let value = parse(arg.value.value).with_context(|| InvalidArgumentValueSnafu {
+        key: arg.key.value,
+        value: arg.value.value,
+        help: format!("expected {expected}"),
+        span: arg.value.source_span(),
+    })?;
+    *slot = Some(value);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use similar_asserts::assert_eq;
@@ -142,26 +810,55 @@ mod tests {
     static CONFIG: &str = indoc::indoc! {"
         [package.metadata.cargo-sync-rdme.badge.badges]
         [package.metadata.cargo-sync-rdme.badge.badges-foo]
+        [package.metadata.cargo-sync-rdme.templates]
+        footer = 'Part of the foo project.'
     "};
 
     impl ResolvedReplaceSpecifier {
         #[track_caller]
         pub(crate) fn into_title(self) {
-            let Self::Title = self else {
+            let Self::Title { package: None } = self else {
+                panic!("unexpected replace specifier: {self:?}");
+            };
+        }
+
+        #[track_caller]
+        pub(crate) fn into_rustdoc(self) -> (Option<Spanned<Arc<str>>>, RustdocOptions) {
+            let Self::Rustdoc {
+                package: None,
+                item,
+                options,
+            } = self
+            else {
                 panic!("unexpected replace specifier: {self:?}");
             };
+            (item, options)
         }
 
         #[track_caller]
-        pub(crate) fn into_rustdoc(self) {
-            let Self::Rustdoc = self else {
+        pub(crate) fn into_include(self) -> (Spanned<Arc<str>>, IncludeOptions) {
+            let Self::Include { path, options } = self else {
                 panic!("unexpected replace specifier: {self:?}");
             };
+            (path, options)
+        }
+
+        #[track_caller]
+        pub(crate) fn into_snippet(self) -> (Spanned<Arc<str>>, Spanned<Arc<str>>, Option<String>) {
+            let Self::Snippet { path, anchor, lang } = self else {
+                panic!("unexpected replace specifier: {self:?}");
+            };
+            (path, anchor, lang)
         }
 
         #[track_caller]
         pub(crate) fn into_badge(self) -> (Option<Arc<str>>, Arc<[BadgeItem]>) {
-            let Self::Badge { group, badges } = self else {
+            let Self::Badge {
+                package: None,
+                group,
+                badges,
+            } = self
+            else {
                 panic!("unexpected replace specifier: {self:?}");
             };
             (group, badges)
@@ -184,6 +881,86 @@ mod tests {
             (kind, group, span)
         }
 
+        #[track_caller]
+        pub(crate) fn into_unexpected_package_for_specifier(self) -> (String, String, SourceSpan) {
+            let Self::UnexpectedPackageForSpecifier {
+                kind,
+                package,
+                span,
+            } = self
+            else {
+                panic!("unexpected error: {self:?}");
+            };
+            (kind, package, span)
+        }
+
+        #[track_caller]
+        pub(crate) fn into_missing_group_for_specifier(self) -> (String, SourceSpan) {
+            let Self::MissingGroupForSpecifier { kind, span, .. } = self else {
+                panic!("unexpected error: {self:?}");
+            };
+            (kind, span)
+        }
+
+        #[track_caller]
+        pub(crate) fn into_missing_argument(self) -> (String, String, SourceSpan) {
+            let Self::MissingArgument { kind, key, span } = self else {
+                panic!("unexpected error: {self:?}");
+            };
+            (kind, key, span)
+        }
+
+        #[track_caller]
+        pub(crate) fn into_invalid_item_path(self) -> (String, SourceSpan) {
+            let Self::InvalidItemPath { path, span } = self else {
+                panic!("unexpected error: {self:?}");
+            };
+            (path, span)
+        }
+
+        #[track_caller]
+        pub(crate) fn into_unknown_argument(self) -> (String, String, SourceSpan) {
+            let Self::UnknownArgument {
+                kind, key, span, ..
+            } = self
+            else {
+                panic!("unexpected error: {self:?}");
+            };
+            (kind, key, span)
+        }
+
+        #[track_caller]
+        pub(crate) fn into_duplicate_argument(self) -> (String, SourceSpan) {
+            let Self::DuplicateArgument { key, span } = self else {
+                panic!("unexpected error: {self:?}");
+            };
+            (key, span)
+        }
+
+        #[track_caller]
+        pub(crate) fn into_invalid_argument_value(self) -> (String, String, SourceSpan) {
+            let Self::InvalidArgumentValue {
+                key, value, span, ..
+            } = self
+            else {
+                panic!("unexpected error: {self:?}");
+            };
+            (key, value, span)
+        }
+
+        #[track_caller]
+        pub(crate) fn into_invalid_toc_depth_range(self) -> (u8, u8, SourceSpan) {
+            let Self::InvalidTocDepthRange {
+                min_depth,
+                max_depth,
+                span,
+            } = self
+            else {
+                panic!("unexpected error: {self:?}");
+            };
+            (min_depth, max_depth, span)
+        }
+
         #[track_caller]
         pub(crate) fn into_no_default_badge_configured(self) -> SourceSpan {
             let Self::NoDefaultBadgeConfigured { span } = self else {
@@ -216,9 +993,71 @@ mod tests {
         let resolved = resolve(source, CONFIG).unwrap();
         resolved.into_title();
 
+        let source = Spanned::from_str("features");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(resolved, ResolvedReplaceSpecifier::Features);
+
+        let source = Spanned::from_str("install");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(resolved, ResolvedReplaceSpecifier::Install { style: None });
+
+        let source = Spanned::from_str("install style=binstall");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedReplaceSpecifier::Install {
+                style: Some(InstallStyle::Binstall)
+            }
+        );
+
         let source = Spanned::from_str("rustdoc");
         let resolved = resolve(source, CONFIG).unwrap();
-        resolved.into_rustdoc();
+        assert_eq!(resolved.into_rustdoc(), (None, RustdocOptions::default()));
+
+        let source = Spanned::from_str("rustdoc heading-offset=2 links=inline");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved.into_rustdoc(),
+            (
+                None,
+                RustdocOptions {
+                    heading_offset: Some(2),
+                    links: Some(LinkStyle::Inline),
+                }
+            )
+        );
+
+        let source = Spanned::from_str("rustdoc:foo::Bar heading-offset=0");
+        let resolved = resolve(source, CONFIG).unwrap();
+        let (item, options) = resolved.into_rustdoc();
+        let item = item.unwrap();
+        assert_eq!(&*item.value, "foo::Bar");
+        source.assert_span(item.span, "foo::Bar");
+        assert_eq!(options.heading_offset, Some(0));
+
+        let source = Spanned::from_str("include:../examples/config.toml");
+        let resolved = resolve(source, CONFIG).unwrap();
+        let (path, options) = resolved.into_include();
+        assert_eq!(&*path.value, "../examples/config.toml");
+        source.assert_span(path.span, "../examples/config.toml");
+        assert_eq!(options, IncludeOptions::default());
+        assert_eq!(options.format(), IncludeFormat::Markdown);
+
+        let source = Spanned::from_str(r#"include:"a b.sh" relative-to=package lang=console"#);
+        let resolved = resolve(source, CONFIG).unwrap();
+        let (path, options) = resolved.into_include();
+        assert_eq!(&*path.value, "a b.sh");
+        assert_eq!(options.relative_to(), IncludeBase::Package);
+        assert_eq!(options.lang.as_deref(), Some("console"));
+        assert_eq!(options.format(), IncludeFormat::Code);
+
+        let source = Spanned::from_str("snippet:examples/basic.rs anchor=setup lang=rs");
+        let resolved = resolve(source, CONFIG).unwrap();
+        let (path, anchor, lang) = resolved.into_snippet();
+        assert_eq!(&*path.value, "examples/basic.rs");
+        assert_eq!(&*anchor.value, "setup");
+        source.assert_span(anchor.span, "setup");
+        assert_eq!(lang.as_deref(), Some("rs"));
 
         let source = Spanned::from_str("badge");
         let resolved = resolve(source, CONFIG).unwrap();
@@ -231,6 +1070,167 @@ mod tests {
         assert_eq!(group.as_deref().unwrap(), "foo");
     }
 
+    #[test]
+    fn resolve_marker_resolves_templates() {
+        let source = Spanned::from_str("template:footer");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedReplaceSpecifier::Template {
+                name: "footer".into()
+            }
+        );
+
+        let source = Spanned::from_str("template:header");
+        let Err(ResolveMarkerError::NoSuchTemplate { name, help, span }) = resolve(source, CONFIG)
+        else {
+            panic!("unexpected result");
+        };
+        assert_eq!(name, "header");
+        assert_eq!(help, "available templates: `footer`");
+        source.assert_source_span(span, "header");
+
+        let source = Spanned::from_str("template");
+        let (kind, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_missing_group_for_specifier();
+        assert_eq!(kind, "template");
+        source.assert_source_span(span, "template");
+    }
+
+    #[test]
+    fn resolve_marker_resolves_package_qualifier() {
+        let source = Spanned::from_str("rustdoc@foo-core:Bar links=inline");
+        let ResolvedReplaceSpecifier::Rustdoc {
+            package,
+            item,
+            options,
+        } = resolve(source, CONFIG).unwrap()
+        else {
+            panic!("unexpected specifier");
+        };
+        let package = package.unwrap();
+        assert_eq!(&*package.value, "foo-core");
+        source.assert_span(package.span, "foo-core");
+        assert_eq!(item.as_ref().map(|item| &*item.value), Some("Bar"));
+        assert_eq!(options.links, Some(LinkStyle::Inline));
+
+        let source = Spanned::from_str("title@foo-core");
+        let ResolvedReplaceSpecifier::Title { package } = resolve(source, CONFIG).unwrap() else {
+            panic!("unexpected specifier");
+        };
+        assert_eq!(
+            package.as_ref().map(|package| &*package.value),
+            Some("foo-core")
+        );
+
+        let source = Spanned::from_str("badge@foo-core:foo");
+        let ResolvedReplaceSpecifier::Badge { package, group, .. } =
+            resolve(source, CONFIG).unwrap()
+        else {
+            panic!("unexpected specifier");
+        };
+        assert_eq!(
+            package.as_ref().map(|package| &*package.value),
+            Some("foo-core")
+        );
+        assert_eq!(group.as_deref(), Some("foo"));
+    }
+
+    #[test]
+    fn resolve_marker_resolves_aggregate_markers() {
+        let source = Spanned::from_str("api-summary heading-level=2");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedReplaceSpecifier::ApiSummary {
+                options: ApiSummaryOptions {
+                    heading_level: Some(2),
+                }
+            }
+        );
+
+        let source = Spanned::from_str("changelog");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedReplaceSpecifier::Changelog {
+                options: ChangelogOptions::default()
+            }
+        );
+
+        let source = Spanned::from_str("changelog section=2 heading-offset=0 link=true");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedReplaceSpecifier::Changelog {
+                options: ChangelogOptions {
+                    section: Some(ChangelogSection::Index(2)),
+                    heading_offset: Some(0),
+                    link: Some(true),
+                }
+            }
+        );
+
+        let source = Spanned::from_str("changelog section=1.2.3");
+        let ResolvedReplaceSpecifier::Changelog { options } = resolve(source, CONFIG).unwrap()
+        else {
+            panic!("unexpected specifier");
+        };
+        assert_eq!(
+            options.section(),
+            &ChangelogSection::Version("1.2.3".into())
+        );
+
+        let source = Spanned::from_str(r#"toc max-depth=3 exclude="Table of Contents, License""#);
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedReplaceSpecifier::Toc {
+                options: TocOptions {
+                    min_depth: None,
+                    max_depth: Some(3),
+                    exclude: Some(vec!["Table of Contents".into(), "License".into()]),
+                }
+            }
+        );
+
+        let source = Spanned::from_str("workspace-members columns=name,badges,version");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedReplaceSpecifier::WorkspaceMembers {
+                options: WorkspaceMembersOptions {
+                    columns: Some(vec![
+                        MemberColumn::Name,
+                        MemberColumn::Badges,
+                        MemberColumn::Version
+                    ]),
+                }
+            }
+        );
+
+        let source = Spanned::from_str("cli-help");
+        let resolved = resolve(source, CONFIG).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedReplaceSpecifier::CliHelp {
+                bin: None,
+                options: CliHelpOptions::default(),
+            }
+        );
+
+        let source = Spanned::from_str(r#"cli-help:foo args="sub --help" lang=console"#);
+        let resolved = resolve(source, CONFIG).unwrap();
+        let ResolvedReplaceSpecifier::CliHelp { bin, options } = resolved else {
+            panic!("unexpected specifier: {resolved:?}");
+        };
+        let bin = bin.unwrap();
+        source.assert_span(bin.span, "foo");
+        assert_eq!(options.args(), ["sub", "--help"]);
+        assert_eq!(options.lang(), CliHelpLang::Console);
+    }
+
     #[test]
     fn resolve_marker_rejects_invalid_markers() {
         let source = Spanned::from_str("unknown");
@@ -248,13 +1248,19 @@ mod tests {
         assert_eq!(group, "foo");
         source.assert_source_span(span, "title:foo");
 
-        let source = Spanned::from_str("rustdoc:foo");
-        let (kind, group, span) = resolve(source, CONFIG)
+        let source = Spanned::from_str("rustdoc:foo::");
+        let (path, span) = resolve(source, CONFIG)
             .unwrap_err()
-            .into_unexpected_group_for_specifier();
-        assert_eq!(kind, "rustdoc");
-        assert_eq!(group, "foo");
-        source.assert_source_span(span, "rustdoc:foo");
+            .into_invalid_item_path();
+        assert_eq!(path, "foo::");
+        source.assert_source_span(span, "foo::");
+
+        let source = Spanned::from_str("include");
+        let (kind, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_missing_group_for_specifier();
+        assert_eq!(kind, "include");
+        source.assert_source_span(span, "include");
 
         let source = Spanned::from_str("badge:bar");
         let (group, span) = resolve(source, CONFIG)
@@ -268,5 +1274,125 @@ mod tests {
             .unwrap_err()
             .into_no_default_badge_configured();
         source.assert_source_span(span, "badge");
+
+        let source = Spanned::from_str("features@foo-core");
+        let (kind, package, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_unexpected_package_for_specifier();
+        assert_eq!(kind, "features");
+        assert_eq!(package, "foo-core");
+        source.assert_source_span(span, "foo-core");
+    }
+
+    #[test]
+    fn resolve_marker_rejects_invalid_arguments() {
+        let source = Spanned::from_str("rustdoc heading-offset=1 foo=bar");
+        let (kind, key, span) = resolve(source, CONFIG).unwrap_err().into_unknown_argument();
+        assert_eq!(kind, "rustdoc");
+        assert_eq!(key, "foo");
+        source.assert_source_span(span, "foo");
+
+        let source = Spanned::from_str("badge:foo links=inline");
+        let (kind, key, span) = resolve(source, CONFIG).unwrap_err().into_unknown_argument();
+        assert_eq!(kind, "badge");
+        assert_eq!(key, "links");
+        source.assert_source_span(span, "links");
+
+        let source = Spanned::from_str("title links=inline");
+        let (kind, key, span) = resolve(source, CONFIG).unwrap_err().into_unknown_argument();
+        assert_eq!(kind, "title");
+        assert_eq!(key, "links");
+        source.assert_source_span(span, "links");
+
+        let source = Spanned::from_str("rustdoc links=inline links=\"reference\"");
+        let (key, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_duplicate_argument();
+        assert_eq!(key, "links");
+        source.assert_source_span(span, "links=\"reference\"");
+
+        let source = Spanned::from_str("rustdoc heading-offset=x");
+        let (key, value, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_argument_value();
+        assert_eq!(key, "heading-offset");
+        assert_eq!(value, "x");
+        source.assert_source_span(span, "x");
+
+        let source = Spanned::from_str("rustdoc heading-offset=6");
+        let (key, value, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_argument_value();
+        assert_eq!(key, "heading-offset");
+        assert_eq!(value, "6");
+        source.assert_source_span(span, "6");
+
+        let source = Spanned::from_str("rustdoc links=\"in line\"");
+        let (key, value, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_argument_value();
+        assert_eq!(key, "links");
+        assert_eq!(value, "in line");
+        source.assert_source_span(span, "in line");
+
+        let source = Spanned::from_str("snippet:examples/basic.rs lang=rust");
+        let (kind, key, span) = resolve(source, CONFIG).unwrap_err().into_missing_argument();
+        assert_eq!(kind, "snippet");
+        assert_eq!(key, "anchor");
+        source.assert_source_span(span, "snippet:examples/basic.rs lang=rust");
+
+        let source = Spanned::from_str("snippet:examples/basic.rs anchor=a.b");
+        let (key, value, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_argument_value();
+        assert_eq!(key, "anchor");
+        assert_eq!(value, "a.b");
+        source.assert_source_span(span, "a.b");
+
+        let source = Spanned::from_str("include:foo.sh format=raw");
+        let (key, value, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_argument_value();
+        assert_eq!(key, "format");
+        assert_eq!(value, "raw");
+        source.assert_source_span(span, "raw");
+
+        let source = Spanned::from_str("workspace-members columns=name,name");
+        let (key, value, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_argument_value();
+        assert_eq!(key, "columns");
+        assert_eq!(value, "name,name");
+        source.assert_source_span(span, "name,name");
+
+        let source = Spanned::from_str("changelog section=0");
+        let (key, value, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_argument_value();
+        assert_eq!(key, "section");
+        assert_eq!(value, "0");
+        source.assert_source_span(span, "0");
+
+        let source = Spanned::from_str("toc max-depth=7");
+        let (key, value, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_argument_value();
+        assert_eq!(key, "max-depth");
+        assert_eq!(value, "7");
+        source.assert_source_span(span, "7");
+
+        let source = Spanned::from_str("toc min-depth=4 max-depth=3");
+        let (min_depth, max_depth, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_toc_depth_range();
+        assert_eq!((min_depth, max_depth), (4, 3));
+        source.assert_source_span(span, "max-depth=3");
+
+        let source = Spanned::from_str("toc max-depth=1");
+        let (min_depth, max_depth, span) = resolve(source, CONFIG)
+            .unwrap_err()
+            .into_invalid_toc_depth_range();
+        assert_eq!((min_depth, max_depth), (2, 1));
+        source.assert_source_span(span, "max-depth=1");
     }
 }
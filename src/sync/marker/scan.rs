@@ -2,8 +2,12 @@ use miette::SourceSpan;
 use snafu::{OptionExt as _, Snafu};
 
 use crate::{
+    config::metadata::MarkerStyle,
     parse::Spanned,
-    sync::marker::parse::{self, Marker, MarkerParser, ReplaceSpecifier},
+    sync::marker::{
+        self,
+        parse::{self, Marker, MarkerParser, ReplaceSpecifier},
+    },
     traits::RangeExt as _,
 };
 
@@ -39,17 +43,25 @@ pub(super) enum ScanError {
 #[derive(Debug)]
 pub(super) struct Chunk<'a> {
     pub(super) specifier: Spanned<ReplaceSpecifier<'a>>,
+    /// Whether the contents of the region do not match the hash recorded in the start marker.
+    pub(super) hand_edited: bool,
 }
 
 #[derive(Debug)]
 pub(super) struct Scanner<'a> {
+    markdown: &'a str,
+    style: MarkerStyle,
     parser: MarkerParser<'a>,
 }
 
 impl<'a> Scanner<'a> {
-    pub(super) fn new(markdown: &'a str) -> Self {
-        let parser = MarkerParser::new(markdown);
-        Self { parser }
+    pub(super) fn new(markdown: &'a str, style: MarkerStyle) -> Self {
+        let parser = MarkerParser::new(markdown, style);
+        Self {
+            markdown,
+            style,
+            parser,
+        }
     }
 
     pub(super) fn try_next(&mut self) -> Result<Option<Spanned<Chunk<'a>>>, ScanError> {
@@ -57,11 +69,15 @@ impl<'a> Scanner<'a> {
             return Ok(None);
         };
         let start_span = start_marker.span;
-        let specifier = match start_marker.value {
+        let (specifier, hash) = match start_marker.value {
             Marker::Replace(specifier) => {
-                return Ok(Some(Spanned::new(Chunk { specifier }, start_span)));
+                let chunk = Chunk {
+                    specifier,
+                    hand_edited: false,
+                };
+                return Ok(Some(Spanned::new(chunk, start_span)));
             }
-            Marker::Start(specifier) => specifier,
+            Marker::Start(specifier, hash) => (specifier, hash),
             Marker::End => {
                 return Err(UnexpectedEndMarkerSnafu {
                     span: start_span.to_span(),
@@ -76,10 +92,19 @@ impl<'a> Scanner<'a> {
             })?;
         let end_span = end_marker.span;
         match end_marker.value {
-            Marker::End => Ok(Some(Spanned::new(
-                Chunk { specifier },
-                start_span.start..end_span.end,
-            ))),
+            Marker::End => {
+                let span = start_span.start..end_span.end;
+                let hand_edited = hash.is_some_and(|hash| {
+                    let contents =
+                        marker::marked_contents(&self.markdown[span.clone()], self.style);
+                    hash.value != marker::content_hash(contents)
+                });
+                let chunk = Chunk {
+                    specifier,
+                    hand_edited,
+                };
+                Ok(Some(Spanned::new(chunk, span)))
+            }
             _ => Err(NestedMarkerSnafu {
                 nested_span: end_span.to_span(),
                 previous_span: start_span.to_span(),
@@ -137,7 +162,7 @@ mod tests {
     #[test]
     fn no_markers() {
         let input = "Hello, world!";
-        let mut markers = Scanner::new(input);
+        let mut markers = Scanner::new(input, MarkerStyle::Html);
         assert!(markers.try_next().unwrap().is_none());
     }
 
@@ -153,7 +178,7 @@ mod tests {
             Good night, world!
         "};
         let source = Spanned::from_str(source);
-        let mut scanner = Scanner::new(source.value);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::Html);
 
         let chunk = scanner.try_next().unwrap().unwrap();
         source.assert_span(chunk.span, "<!-- cargo-sync-rdme title -->");
@@ -179,6 +204,32 @@ mod tests {
         assert!(scanner.try_next().unwrap().is_none());
     }
 
+    #[test]
+    fn hashed_start_marker() {
+        let source = indoc! {"
+            <!-- cargo-sync-rdme rustdoc [[ sha=536211c9 -->
+            Generated.
+            <!-- cargo-sync-rdme ]] -->
+            <!-- cargo-sync-rdme rustdoc [[ sha=536211c9 -->
+            Edited.
+            <!-- cargo-sync-rdme ]] -->
+            <!-- cargo-sync-rdme rustdoc [[ -->
+            Edited.
+            <!-- cargo-sync-rdme ]] -->
+        "};
+        assert_eq!(marker::content_hash("Generated.\n"), "536211c9");
+        let mut scanner = Scanner::new(source, MarkerStyle::Html);
+
+        let chunk = scanner.try_next().unwrap().unwrap();
+        assert!(!chunk.value.hand_edited);
+        let chunk = scanner.try_next().unwrap().unwrap();
+        assert!(chunk.value.hand_edited);
+        let chunk = scanner.try_next().unwrap().unwrap();
+        assert!(!chunk.value.hand_edited);
+
+        assert!(scanner.try_next().unwrap().is_none());
+    }
+
     #[test]
     fn start_and_end_marker() {
         let source = indoc! {"
@@ -190,7 +241,7 @@ mod tests {
             Good evening, world!
         "};
         let source = Spanned::from_str(source);
-        let mut scanner = Scanner::new(source.value);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::Html);
 
         let chunk = scanner.try_next().unwrap().unwrap();
         let span_source = &source.value[chunk.span];
@@ -211,7 +262,7 @@ mod tests {
         "};
 
         let source = Spanned::from_str(source);
-        let mut scanner = Scanner::new(source.value);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::Html);
 
         let span = scanner.try_next().unwrap_err().into_unexpected_end_marker();
         source.assert_source_span(span, "<!-- cargo-sync-rdme ]] -->");
@@ -226,7 +277,7 @@ mod tests {
         "};
 
         let source = Spanned::from_str(source);
-        let mut scanner = Scanner::new(source.value);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::Html);
 
         let span = scanner
             .try_next()
@@ -249,10 +300,117 @@ mod tests {
             Good night, world!
         "};
         let source = Spanned::from_str(source);
-        let mut scanner = Scanner::new(source.value);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::Html);
 
         let (nested_span, previous_span) = scanner.try_next().unwrap_err().into_nested_marker();
         source.assert_source_span(nested_span, "<!-- cargo-sync-rdme title   [[ -->");
         source.assert_source_span(previous_span, "<!-- cargo-sync-rdme rustdoc [[ -->");
     }
+
+    #[test]
+    fn link_reference_markers() {
+        let source = indoc! {"
+            [//]: # (cargo-sync-rdme title)
+
+            [//]: # (cargo-sync-rdme rustdoc [[)
+            Good afternoon, world!
+
+            [//]: # (cargo-sync-rdme ]])
+            <!-- cargo-sync-rdme badge -->
+            ```markdown
+            [//]: # (cargo-sync-rdme badge)
+            ```
+        "};
+        let source = Spanned::from_str(source);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::LinkReference);
+
+        let chunk = scanner.try_next().unwrap().unwrap();
+        source.assert_span(chunk.span, "[//]: # (cargo-sync-rdme title)");
+        source.assert_spanned_str(chunk.value.specifier.value.kind, "title");
+
+        let chunk = scanner.try_next().unwrap().unwrap();
+        let span_source = &source.value[chunk.span];
+        assert!(span_source.starts_with("[//]: # (cargo-sync-rdme rustdoc [[)"));
+        assert!(span_source.ends_with("[//]: # (cargo-sync-rdme ]])"));
+        source.assert_spanned_str(chunk.value.specifier.value.kind, "rustdoc");
+
+        assert!(scanner.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn mdx_markers() {
+        let source = indoc! {"
+            {/* cargo-sync-rdme rustdoc [[ */}
+            Good afternoon, world!
+            {/*  cargo-sync-rdme ]]  */}
+            <!-- cargo-sync-rdme badge -->
+        "};
+        let source = Spanned::from_str(source);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::Mdx);
+
+        let chunk = scanner.try_next().unwrap().unwrap();
+        let span_source = &source.value[chunk.span];
+        assert!(span_source.starts_with("{/* cargo-sync-rdme rustdoc [[ */}"));
+        assert!(span_source.ends_with("{/*  cargo-sync-rdme ]]  */}"));
+        source.assert_spanned_str(chunk.value.specifier.value.kind, "rustdoc");
+
+        assert!(scanner.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn asciidoc_markers() {
+        let source = indoc! {"
+            // cargo-sync-rdme rustdoc [[
+            Good afternoon, world!
+            // cargo-sync-rdme ]]
+            ----
+            // cargo-sync-rdme badge
+            ----
+            ////
+            // cargo-sync-rdme badge
+            ////
+            <!-- cargo-sync-rdme title -->
+        "};
+        let source = Spanned::from_str(source);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::Asciidoc);
+
+        let chunk = scanner.try_next().unwrap().unwrap();
+        let span_source = &source.value[chunk.span];
+        assert!(span_source.starts_with("// cargo-sync-rdme rustdoc [["));
+        assert!(span_source.ends_with("// cargo-sync-rdme ]]"));
+        source.assert_spanned_str(chunk.value.specifier.value.kind, "rustdoc");
+
+        assert!(scanner.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn rst_markers() {
+        let source = indoc! {"
+            .. cargo-sync-rdme title
+
+            .. cargo-sync-rdme rustdoc [[
+
+            Good afternoon, world!
+
+            .. cargo-sync-rdme ]]
+
+            ::
+
+               .. cargo-sync-rdme badge
+        "};
+        let source = Spanned::from_str(source);
+        let mut scanner = Scanner::new(source.value, MarkerStyle::Rst);
+
+        let chunk = scanner.try_next().unwrap().unwrap();
+        source.assert_span(chunk.span, ".. cargo-sync-rdme title");
+        source.assert_spanned_str(chunk.value.specifier.value.kind, "title");
+
+        let chunk = scanner.try_next().unwrap().unwrap();
+        let span_source = &source.value[chunk.span];
+        assert!(span_source.starts_with(".. cargo-sync-rdme rustdoc [["));
+        assert!(span_source.ends_with(".. cargo-sync-rdme ]]"));
+        source.assert_spanned_str(chunk.value.specifier.value.kind, "rustdoc");
+
+        assert!(scanner.try_next().unwrap().is_none());
+    }
 }
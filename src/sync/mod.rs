@@ -1,32 +1,43 @@
 use std::{
-    fs,
+    fmt, fs,
     io::{self, Write as _},
+    mem,
+    num::NonZeroUsize,
+    range::Range,
     sync::Arc,
 };
 
 use cargo_metadata::{
     Metadata, Package, PackageName,
-    camino::{Utf8Path, Utf8PathBuf},
+    camino::{Utf8Component, Utf8Path, Utf8PathBuf},
 };
 
-use miette::NamedSource;
-use snafu::{ResultExt as _, Snafu, ensure};
+use miette::{NamedSource, SourceSpan};
+use snafu::{IntoError as _, OptionExt as _, ResultExt as _, Snafu, ensure};
 use supports_color::Stream;
 use tempfile::NamedTempFile;
 use tracing::Level;
 use vcs_modify_guard::{AllowOptions, ModificationSafety, UnsafeModificationReason};
 
 use crate::{
-    args::{FeatureSelection, FixArgs, Mode, RustdocToolchainArgs},
-    config::Manifest,
-    diff,
+    args::{Direction, FeatureSelection, FixArgs, Mode, RustdocToolchainArgs},
+    config::{
+        Manifest,
+        metadata::{MarkerStyle, TargetFormat},
+    },
+    diff, output,
+    sync::marker::ResolvedReplaceSpecifier,
     traits::PackageExt as _,
     with_source::{self, WithSource},
 };
 
+pub(crate) use self::contents::RustdocCache;
+
 mod contents;
+mod crate_docs;
 mod marker;
 mod replace;
+mod template;
 
 #[derive(Debug, Snafu, miette::Diagnostic)]
 pub(crate) enum SyncError {
@@ -37,13 +48,13 @@ pub(crate) enum SyncError {
         #[diagnostic_source]
         source: with_source::ReadFileError,
     },
-    #[snafu(display("failed to read markdown file for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path))]
+    #[snafu(display("failed to read {kind} for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path, kind = markdown.kind))]
     ReadMarkdownFile {
         markdown: MarkdownPath,
         #[snafu(source)]
         source: io::Error,
     },
-    #[snafu(display("failed to write markdown file for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path))]
+    #[snafu(display("failed to write {kind} for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path, kind = markdown.kind))]
     WriteMarkdownFile {
         markdown: MarkdownPath,
         #[snafu(source)]
@@ -53,6 +64,27 @@ pub(crate) enum SyncError {
         "no target files found for package `{package}`. Specify `package.readme` or `package.metadata.cargo-sync-rdme.extra-targets`"
     ))]
     NoTargetFilesFound { package: PackageName },
+    #[snafu(display(
+        "no README found for package `{package}`. Specify `package.readme` to synchronize crate documentation from it"
+    ))]
+    NoReadmeFound { package: PackageName },
+    #[snafu(display(
+        "no `<!-- cargo-sync-rdme rustdoc [[ -->` region found in markdown file for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path
+    ))]
+    NoRustdocRegion { markdown: MarkdownPath },
+    #[snafu(display(
+        "crate documentation can only be synchronized from a Markdown README for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path
+    ))]
+    NonMarkdownReadme { markdown: MarkdownPath },
+    #[snafu(display("package `{package}` does not have a library target"))]
+    NoLibraryTarget { package: PackageName },
+    #[snafu(display(
+        "no `// cargo-sync-rdme rustdoc [[` and `// cargo-sync-rdme ]]` lines found in crate root for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path
+    ))]
+    #[diagnostic(help(
+        "add the lines around the `//!` crate documentation to mark the block to be replaced"
+    ))]
+    NoCrateDocsBlock { markdown: MarkdownPath },
     #[snafu(transparent)]
     #[diagnostic(transparent)]
     ParseMarkers {
@@ -67,15 +99,67 @@ pub(crate) enum SyncError {
         #[diagnostic_source]
         source: contents::CreateAllContentsError,
     },
+    #[snafu(transparent)]
+    #[diagnostic(transparent)]
+    BuildReplacements {
+        #[snafu(source)]
+        #[diagnostic_source]
+        source: replace::BuildReplacementsError,
+    },
+    #[snafu(display(
+        "no output path configured for markdown file with markers stripped for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path
+    ))]
+    #[diagnostic(help(
+        "`--check` cannot check a copy written to stdout. Add the target file to `package.metadata.cargo-sync-rdme.strip-markers-output`"
+    ))]
+    NoStripMarkersOutput { markdown: MarkdownPath },
+    #[snafu(display(
+        "output path of markdown file with markers stripped for package `{package}` is a target file: {markdown} -> {output}", package = markdown.package, markdown = markdown.path
+    ))]
+    #[diagnostic(help("target files cannot be overwritten with their copies"))]
+    StripMarkersOutputIsTarget {
+        markdown: MarkdownPath,
+        output: Utf8PathBuf,
+    },
+    #[snafu(display(
+        "output path of markdown file with markers stripped for package `{package}` is outside of the workspace: {markdown} -> {output}", package = markdown.package, markdown = markdown.path
+    ))]
+    #[diagnostic(help("the copies must be written inside the workspace root directory"))]
+    StripMarkersOutputOutsideWorkspace {
+        markdown: MarkdownPath,
+        output: Utf8PathBuf,
+    },
+    #[snafu(display("failed to resolve output path of markdown file with markers stripped for package `{package}`: {output}", package = markdown.package))]
+    ResolveStripMarkersOutput {
+        markdown: MarkdownPath,
+        output: Utf8PathBuf,
+        #[snafu(source)]
+        source: io::Error,
+    },
+    #[snafu(display("failed to write markdown file with markers stripped to stdout"))]
+    WriteStdout {
+        #[snafu(source)]
+        source: io::Error,
+    },
     #[snafu(display("failed to write diff output"))]
     WriteDiff {
         #[snafu(source)]
         source: io::Error,
     },
-    #[snafu(display("markdown file for package `{package}` is not up to date: {markdown}", package = markdown.package, markdown = markdown.path))]
+    #[snafu(display("{kind} for package `{package}` is not up to date: {markdown}", package = markdown.package, markdown = markdown.path, kind = markdown.kind))]
     CheckFailed { markdown: MarkdownPath },
     #[snafu(display(
-        "failed to check whether the markdown file can be modified for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path
+        "markdown file for package `{package}` has marker regions edited by hand: {markdown}\nUse --force to overwrite them.", package = markdown.package, markdown = markdown.path
+    ))]
+    HandEditedRegions {
+        markdown: MarkdownPath,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label(collection, "contents do not match the hash in the start marker")]
+        spans: Vec<SourceSpan>,
+    },
+    #[snafu(display(
+        "failed to check whether the {kind} can be modified for package `{package}`: {markdown}", package = markdown.package, markdown = markdown.path, kind = markdown.kind
     ))]
     CheckFileModificationSafety {
         markdown: MarkdownPath,
@@ -83,19 +167,19 @@ pub(crate) enum SyncError {
         source: vcs_modify_guard::ModifyGuardError,
     },
     #[snafu(display(
-        "markdown file for package `{package}` is not under version control: {markdown}\nUse --allow-no-vcs to override this check.", package = markdown.package, markdown = markdown.path
+        "{kind} for package `{package}` is not under version control: {markdown}\nUse --allow-no-vcs to override this check.", package = markdown.package, markdown = markdown.path, kind = markdown.kind
     ))]
     NoVcs { markdown: MarkdownPath },
     #[snafu(display(
-        "markdown file for package `{package}` has uncommitted changes: {markdown}\nUse --allow-dirty to override this check.", package = markdown.package, markdown = markdown.path
+        "{kind} for package `{package}` has uncommitted changes: {markdown}\nUse --allow-dirty to override this check.", package = markdown.package, markdown = markdown.path, kind = markdown.kind
     ))]
     DirtyFile { markdown: MarkdownPath },
     #[snafu(display(
-        "markdown file for package `{package}` has staged changes: {markdown}\nUse --allow-staged to override this check.", package = markdown.package, markdown = markdown.path
+        "{kind} for package `{package}` has staged changes: {markdown}\nUse --allow-staged to override this check.", package = markdown.package, markdown = markdown.path, kind = markdown.kind
     ))]
     StagedFile { markdown: MarkdownPath },
     #[snafu(display(
-        "markdown file for package `{package}` is not safe to modify for some reason: {markdown}\nreason: {reason:?}", package = markdown.package, markdown = markdown.path
+        "{kind} for package `{package}` is not safe to modify for some reason: {markdown}\nreason: {reason:?}", package = markdown.package, markdown = markdown.path, kind = markdown.kind
     ))]
     UnsafeToModifyForSomeReason {
         markdown: MarkdownPath,
@@ -103,6 +187,13 @@ pub(crate) enum SyncError {
     },
 }
 
+#[derive(Debug, Snafu, miette::Diagnostic)]
+#[snafu(display("failed to synchronize {} packages", errors.len()))]
+pub(crate) struct SyncPackagesError {
+    #[related]
+    errors: Vec<miette::Report>,
+}
+
 impl From<with_source::ReadFileError> for Box<SyncError> {
     fn from(value: with_source::ReadFileError) -> Self {
         Box::new(value.into())
@@ -115,6 +206,12 @@ impl From<Box<marker::ParseMarkersError>> for Box<SyncError> {
     }
 }
 
+impl From<replace::BuildReplacementsError> for Box<SyncError> {
+    fn from(value: replace::BuildReplacementsError) -> Self {
+        Box::new(value.into())
+    }
+}
+
 impl From<contents::CreateAllContentsError> for Box<SyncError> {
     fn from(value: contents::CreateAllContentsError) -> Self {
         Box::new(value.into())
@@ -124,11 +221,41 @@ impl From<contents::CreateAllContentsError> for Box<SyncError> {
 #[derive(Debug, Clone)]
 pub(crate) struct SyncOptions<'a> {
     pub(crate) mode: Mode,
+    pub(crate) strip_markers: bool,
     pub(crate) verbosity: Option<Level>,
     pub(crate) diff_stream: Stream,
     pub(crate) fix: &'a FixArgs,
     pub(crate) toolchain: &'a RustdocToolchainArgs,
     pub(crate) feature: &'a FeatureSelection,
+    pub(crate) rustdoc_cache: &'a RustdocCache,
+}
+
+/// Synchronizes the packages, running up to `jobs` packages in parallel.
+///
+/// All packages are synchronized even if some of them fail, and the errors are
+/// reported together. The output of each package is printed in the order of
+/// `packages`.
+pub(crate) fn sync_packages(
+    workspace: &Metadata,
+    packages: &[&Package],
+    direction: Direction,
+    options: &SyncOptions<'_>,
+    jobs: NonZeroUsize,
+) -> miette::Result<()> {
+    let results = output::run_ordered(packages, jobs, |package| match direction {
+        Direction::Forward => sync_all(workspace, package, options),
+        Direction::Reverse => sync_crate_docs(workspace, package, options),
+    });
+    let mut errors = results
+        .into_iter()
+        .filter_map(Result::err)
+        .map(|source| miette::Report::new_boxed(source))
+        .collect::<Vec<_>>();
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.pop().unwrap()),
+        _ => Err(SyncPackagesError { errors }.into()),
+    }
 }
 
 pub(crate) fn sync_all(
@@ -148,60 +275,399 @@ pub(crate) fn sync_all(
         }
     );
 
-    for path in paths {
+    let replacements = replace::Replacements::new(&manifest, package)?;
+
+    for &path in &paths {
         tracing::info!("syncing markdown file: {path}");
 
-        let mut markdown = MarkdownFile::new(workspace, package, path)?;
+        let config = manifest.value().config();
+        let (format, marker_style) = (config.target_format(path), config.marker_style(path));
+        let mut markdown = MarkdownFile::new(workspace, package, path, format, marker_style)?;
 
-        let all_markers = marker::parse_markers(&markdown, &manifest)?;
+        let mut all_markers = marker::parse_markers(&markdown, &manifest, workspace)?;
+        let hand_edited = mem::take(&mut all_markers.hand_edited);
 
         tracing::info!("creating replacement contents for markdown file: {path}");
-        let all_contents =
-            contents::create_all(all_markers, &manifest, workspace, package, options)?;
+        let mut all_contents = contents::create_all(
+            all_markers,
+            &markdown,
+            &manifest,
+            workspace,
+            package,
+            options,
+        )?;
+
+        let replace_all = |all_contents: &[contents::Contents], make_contents| {
+            replace::replace_all(
+                &markdown.text,
+                all_contents,
+                &replacements,
+                marker_style,
+                make_contents,
+            )
+        };
+        let make_marked_contents: replace::MakeContents = if config.content_hash {
+            marker::make_hashed_contents
+        } else {
+            marker::make_marked_contents
+        };
+        let new_text = replace_all(&all_contents, make_marked_contents);
+        let new_text = if contents::create_toc(&mut all_contents, &new_text, format) {
+            replace_all(&all_contents, make_marked_contents)
+        } else {
+            new_text
+        };
+
+        // The copy is derived from the synced target file, so it is written
+        // after the target file is checked or updated.
+        let stripped = options
+            .strip_markers
+            .then(|| replace_all(&all_contents, marker::make_unmarked_contents));
+
+        let output = config.strip_markers_output(path);
+        if stripped.is_some() {
+            check_stripped_output(workspace, package, &paths, path, output, options)?;
+        }
+
+        if !options.fix.force {
+            ensure_no_hand_edits(&markdown, &all_contents, &hand_edited)?;
+        }
+        update(&mut markdown, path, new_text, options)?;
 
-        let new_text = replace::replace_all(&markdown.text, &all_contents);
+        if let Some(stripped) = stripped {
+            write_stripped(workspace, package, path, output, &stripped, options)?;
+        }
+    }
 
-        let changed = new_text.as_str() != &*markdown.text;
-        if !changed {
-            tracing::info!("markdown file is already up to date: {path}");
-            continue;
+    Ok(())
+}
+
+/// Fails if synchronization would overwrite the hand-edited marker regions.
+///
+/// The regions whose contents are stale because their sources changed are
+/// overwritten as usual.
+fn ensure_no_hand_edits(
+    markdown: &MarkdownFile<'_>,
+    all_contents: &[contents::Contents],
+    hand_edited: &[Range<usize>],
+) -> Result<(), Box<SyncError>> {
+    let spans = all_contents
+        .iter()
+        .filter(|contents| {
+            let span = contents.specifier().span;
+            hand_edited.contains(&span)
+                && marker::contents_changed(
+                    &markdown.text[span],
+                    contents.text(),
+                    markdown.marker_style,
+                )
+        })
+        .map(|contents| contents.specifier().source_span())
+        .collect::<Vec<_>>();
+    ensure!(
+        spans.is_empty(),
+        HandEditedRegionsSnafu {
+            markdown,
+            source_code: markdown.to_named_source(),
+            spans,
         }
+    );
+    Ok(())
+}
 
-        match options.mode {
-            Mode::Check => {
-                tracing::warn!("markdown file is not up to date: {path}");
-                diff::write_pretty_diff(options.diff_stream, &markdown.text, &new_text)
-                    .context(WriteDiffSnafu)?;
-                return Err(CheckFailedSnafu {
-                    markdown: &markdown,
-                }
-                .build()
-                .into());
+/// Synchronizes the `//!` crate documentation in the crate root with the
+/// `rustdoc` marker region of the package README.
+pub(crate) fn sync_crate_docs(
+    workspace: &Metadata,
+    package: &Package,
+    options: &SyncOptions<'_>,
+) -> Result<(), Box<SyncError>> {
+    let manifest = ManifestFile::from_toml("package manifest", &package.manifest_path)?;
+    let _span = tracing::info_span!("sync", "{}", package.name).entered();
+
+    let readme_path = package
+        .readme
+        .as_deref()
+        .with_context(|| NoReadmeFoundSnafu {
+            package: package.name.clone(),
+        })?;
+    let crate_root_path = package
+        .targets
+        .iter()
+        .find(|target| target.is_lib() || target.is_rlib() || target.is_proc_macro())
+        .map(|target| &target.src_path)
+        .with_context(|| NoLibraryTargetSnafu {
+            package: package.name.clone(),
+        })?;
+    let crate_root_path = crate_root_path
+        .strip_prefix(package.root_directory())
+        .unwrap_or(crate_root_path);
+
+    tracing::info!("syncing crate documentation from markdown file: {readme_path}");
+
+    let config = manifest.value().config();
+    let (format, marker_style) = (
+        config.target_format(readme_path),
+        config.marker_style(readme_path),
+    );
+    let markdown = MarkdownFile::new(workspace, package, readme_path, format, marker_style)?;
+    ensure!(
+        format == TargetFormat::Markdown,
+        NonMarkdownReadmeSnafu {
+            markdown: &markdown,
+        }
+    );
+    let all_markers = marker::parse_markers(&markdown, &manifest, workspace)?;
+
+    // Only the crate documentation of the package itself can be synchronized back.
+    let (text, rustdoc_options) = all_markers
+        .specifiers
+        .iter()
+        .find_map(|specifier| match &specifier.value {
+            ResolvedReplaceSpecifier::Rustdoc {
+                package: None,
+                item: None,
+                options,
+            } => Some((
+                marker::marked_contents(&markdown.text[specifier.span], marker_style),
+                options,
+            )),
+            _ => None,
+        })
+        .with_context(|| NoRustdocRegionSnafu {
+            markdown: &markdown,
+        })?;
+
+    let docs = contents::create_crate_docs(
+        text,
+        &manifest,
+        workspace,
+        package,
+        options,
+        rustdoc_options,
+    )?;
+
+    let mut crate_root = MarkdownFile::crate_root(workspace, package, crate_root_path)?;
+    let new_text =
+        crate_docs::replace_doc_block(&crate_root.text, &crate_docs::to_doc_comment(&docs))
+            .with_context(|| NoCrateDocsBlockSnafu {
+                markdown: &crate_root,
+            })?;
+
+    update(&mut crate_root, crate_root_path, new_text, options)
+}
+
+fn update(
+    markdown: &mut MarkdownFile<'_>,
+    path: &Utf8Path,
+    new_text: String,
+    options: &SyncOptions<'_>,
+) -> Result<(), Box<SyncError>> {
+    let kind = markdown.kind;
+    let changed = new_text.as_str() != &*markdown.text;
+    if !changed {
+        tracing::info!("{kind} is already up to date: {path}");
+        return Ok(());
+    }
+
+    match options.mode {
+        Mode::Check => {
+            tracing::warn!("{kind} is not up to date: {path}");
+            diff::write_pretty_diff(options.diff_stream, &markdown.text, &new_text)
+                .context(WriteDiffSnafu)?;
+            return Err(CheckFailedSnafu {
+                markdown: &*markdown,
             }
-            Mode::Fix => {}
+            .build()
+            .into());
         }
+        Mode::Fix => {}
+    }
 
-        // Update README if allowed
-        check_update_allowed(&markdown, options.fix)?;
-        markdown.replace(new_text.into())?;
+    // Update README if allowed
+    check_update_allowed(markdown, options.fix)?;
+    markdown.replace(new_text.into())?;
 
-        tracing::info!("updated markdown file: {path}");
+    tracing::info!("updated {kind}: {path}");
+    Ok(())
+}
+
+/// Writes `text`, the synced target file at `path` with the markers stripped,
+/// to `output` or to the standard output.
+///
+/// `output` must be checked by [`check_stripped_output`] beforehand.
+fn write_stripped(
+    workspace: &Metadata,
+    package: &Package,
+    path: &Utf8Path,
+    output: Option<&Utf8Path>,
+    text: &str,
+    options: &SyncOptions<'_>,
+) -> Result<(), Box<SyncError>> {
+    let package_dir = package.workspace_relative_root_directory(workspace);
+    let Some(output) = output else {
+        tracing::info!("writing markdown file with markers stripped to stdout: {path}");
+        output::writer(Stream::Stdout)
+            .write_all(text.as_bytes())
+            .context(WriteStdoutSnafu)?;
+        return Ok(());
+    };
+
+    let relative_path = package_dir.join(output);
+    let markdown = MarkdownPath::new(package, relative_path.clone(), FileKind::Markdown);
+    let output_path = workspace.workspace_root.join(&relative_path);
+    let old_text = match fs::read_to_string(&output_path) {
+        Ok(old_text) => old_text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(source) => return Err(ReadMarkdownFileSnafu { markdown }.into_error(source).into()),
+    };
+    if old_text == text {
+        tracing::info!("markdown file with markers stripped is already up to date: {output}");
+        return Ok(());
+    }
+
+    match options.mode {
+        Mode::Check => {
+            tracing::warn!("markdown file with markers stripped is not up to date: {output}");
+            diff::write_pretty_diff(options.diff_stream, &old_text, text)
+                .context(WriteDiffSnafu)?;
+            return Err(CheckFailedSnafu { markdown }.build().into());
+        }
+        Mode::Fix => {}
     }
 
+    // The stripped copy is derived from the target file, so it is written
+    // without checking the VCS status.
+    (|| {
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&output_path, text)
+    })()
+    .context(WriteMarkdownFileSnafu {
+        markdown: markdown.clone(),
+    })?;
+
+    tracing::info!("wrote markdown file with markers stripped: {output}");
     Ok(())
 }
 
+/// Fails if `output`, the output path of the target file at `path` with the
+/// markers stripped, is one of the `targets` or is outside of the workspace,
+/// or if it is not configured in check mode.
+///
+/// The paths are compared after resolving `..` and symbolic links, so that a
+/// target file cannot be overwritten through another spelling of its path.
+fn check_stripped_output(
+    workspace: &Metadata,
+    package: &Package,
+    targets: &[&Utf8Path],
+    path: &Utf8Path,
+    output: Option<&Utf8Path>,
+    options: &SyncOptions<'_>,
+) -> Result<(), Box<SyncError>> {
+    let package_dir = package.workspace_relative_root_directory(workspace);
+    let markdown = MarkdownPath::new(package, package_dir.join(path), FileKind::Markdown);
+    let Some(output) = output else {
+        ensure!(
+            options.mode != Mode::Check,
+            NoStripMarkersOutputSnafu { markdown }
+        );
+        return Ok(());
+    };
+    let package_root = workspace.workspace_root.join(package_dir);
+    let resolve = |path: &Utf8Path| -> Result<Utf8PathBuf, Box<SyncError>> {
+        let resolved = resolve_path(&package_root.join(path)).with_context(|_| {
+            ResolveStripMarkersOutputSnafu {
+                markdown: markdown.clone(),
+                output: path,
+            }
+        })?;
+        Ok(resolved)
+    };
+
+    let output_path = resolve(output)?;
+    let workspace_root = resolve(&workspace.workspace_root)?;
+    ensure!(
+        output_path.starts_with(workspace_root),
+        StripMarkersOutputOutsideWorkspaceSnafu {
+            markdown: markdown.clone(),
+            output,
+        }
+    );
+    for &target in targets {
+        ensure!(
+            resolve(target)? != output_path,
+            StripMarkersOutputIsTargetSnafu {
+                markdown: markdown.clone(),
+                output,
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Returns `path` with `.` and `..` resolved and the symbolic links in the
+/// longest existing ancestor followed.
+///
+/// Unlike [`Utf8Path::canonicalize_utf8`], the file does not need to exist.
+fn resolve_path(path: &Utf8Path) -> io::Result<Utf8PathBuf> {
+    let mut normalized = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::CurDir => {}
+            Utf8Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    let mut missing = vec![];
+    let mut existing = normalized.as_path();
+    while !existing.exists() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            break;
+        };
+        missing.push(name);
+        existing = parent;
+    }
+    let mut resolved = existing.canonicalize_utf8()?;
+    resolved.extend(missing.iter().rev());
+    Ok(resolved)
+}
+
+/// Kind of a file written by the tool, used in messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    /// A target file with markers.
+    Markdown,
+    /// The crate root, written by `--reverse`.
+    CrateRoot,
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Markdown => write!(f, "markdown file"),
+            Self::CrateRoot => write!(f, "crate root"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct MarkdownPath {
     package: PackageName,
     path: Utf8PathBuf,
+    kind: FileKind,
 }
 
 impl MarkdownPath {
-    fn new(package: &Package, path: Utf8PathBuf) -> Self {
+    fn new(package: &Package, path: Utf8PathBuf, kind: FileKind) -> Self {
         Self {
             package: package.name.clone(),
             path,
+            kind,
         }
     }
 }
@@ -211,6 +677,7 @@ impl From<&MarkdownFile<'_>> for MarkdownPath {
         Self {
             package: markdown.package.name.clone(),
             path: markdown.relative_path.clone(),
+            kind: markdown.kind,
         }
     }
 }
@@ -221,6 +688,9 @@ struct MarkdownFile<'a> {
     relative_path: Utf8PathBuf,
     path: Utf8PathBuf,
     text: Arc<str>,
+    kind: FileKind,
+    format: TargetFormat,
+    marker_style: MarkerStyle,
 }
 
 impl<'a> MarkdownFile<'a> {
@@ -228,6 +698,43 @@ impl<'a> MarkdownFile<'a> {
         workspace: &'a Metadata,
         package: &'a Package,
         package_relative_path: &'a Utf8Path,
+        format: TargetFormat,
+        marker_style: MarkerStyle,
+    ) -> Result<Self, Box<SyncError>> {
+        Self::read(
+            workspace,
+            package,
+            package_relative_path,
+            FileKind::Markdown,
+            format,
+            marker_style,
+        )
+    }
+
+    /// Reads the crate root of the package, whose `//!` crate documentation is
+    /// written by `--reverse`.
+    fn crate_root(
+        workspace: &'a Metadata,
+        package: &'a Package,
+        package_relative_path: &'a Utf8Path,
+    ) -> Result<Self, Box<SyncError>> {
+        Self::read(
+            workspace,
+            package,
+            package_relative_path,
+            FileKind::CrateRoot,
+            TargetFormat::default(),
+            MarkerStyle::default(),
+        )
+    }
+
+    fn read(
+        workspace: &'a Metadata,
+        package: &'a Package,
+        package_relative_path: &'a Utf8Path,
+        kind: FileKind,
+        format: TargetFormat,
+        marker_style: MarkerStyle,
     ) -> Result<Self, Box<SyncError>> {
         let relative_path = package
             .workspace_relative_root_directory(workspace)
@@ -235,7 +742,7 @@ impl<'a> MarkdownFile<'a> {
         let path = workspace.workspace_root.join(&relative_path);
         let text = fs::read_to_string(&path)
             .with_context(|_source| ReadMarkdownFileSnafu {
-                markdown: MarkdownPath::new(package, relative_path.clone()),
+                markdown: MarkdownPath::new(package, relative_path.clone(), kind),
             })?
             .into();
         Ok(Self {
@@ -243,6 +750,9 @@ impl<'a> MarkdownFile<'a> {
             relative_path,
             path,
             text,
+            kind,
+            format,
+            marker_style,
         })
     }
 
@@ -287,6 +797,7 @@ fn check_update_allowed(
         allow_no_vcs,
         allow_dirty,
         allow_staged,
+        force: _,
     } = options;
 
     let safety = AllowOptions::new()
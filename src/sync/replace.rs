@@ -1,16 +1,125 @@
-use std::{borrow::Cow, iter, range::Range};
+use std::{borrow::Cow, iter, range::Range, sync::Arc};
 
-use crate::sync::{contents::Contents, marker};
+use cargo_metadata::Package;
+use miette::{NamedSource, SourceSpan};
+use regex::Regex;
+use snafu::{ResultExt as _, Snafu, ensure};
 
-pub(in super::super) fn replace_all(text: &str, contents: &[Contents]) -> String {
+use crate::{
+    config::metadata::MarkerStyle,
+    sync::{
+        ManifestFile,
+        contents::Contents,
+        marker::ResolvedReplaceSpecifier,
+        template::{self, RenderTemplateError, Variables},
+    },
+};
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+#[snafu(display("invalid replacements in the package manifest"))]
+pub(crate) struct BuildReplacementsError {
+    #[related]
+    errors: Vec<BuildReplacementError>,
+}
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+pub(crate) enum BuildReplacementError {
+    #[snafu(display("invalid regular expression in `replacements.search`"))]
+    InvalidSearchRegex {
+        #[snafu(source(from(regex::Error, Box::new)))]
+        source: Box<regex::Error>,
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("failed to render `replacements.replace`"))]
+    RenderReplace {
+        #[source_code]
+        source_code: NamedSource<Arc<str>>,
+        #[related]
+        errors: Vec<RenderTemplateError>,
+    },
+}
+
+/// Replacements applied to the text outside marker regions.
+#[derive(Debug, Default)]
+pub(super) struct Replacements {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Replacements {
+    /// Builds replacements from `package.metadata.cargo-sync-rdme.replacements`.
+    ///
+    /// In addition to the variables available in templates, `{{major_minor}}`
+    /// is replaced with the major and minor version of the package.
+    pub(super) fn new(
+        manifest: &ManifestFile,
+        package: &Package,
+    ) -> Result<Self, BuildReplacementsError> {
+        let mut variables = Variables::from_package(package);
+        let major_minor = format!("{}.{}", package.version.major, package.version.minor);
+        variables.set("major_minor", Some(major_minor.into()));
+
+        let mut rules = vec![];
+        let mut errors = vec![];
+        for replacement in &manifest.value().config().replacements {
+            let search = manifest.map(|_| &replacement.search);
+            let regex = Regex::new(search.value().get_ref()).context(InvalidSearchRegexSnafu {
+                source_code: manifest.to_named_source(),
+                span: search.span(),
+            });
+            let replace = manifest.map(|_| &replacement.replace);
+            let replace = template::render(replace.spanned_str(), &variables).map_err(|errors| {
+                RenderReplaceSnafu {
+                    source_code: manifest.to_named_source(),
+                    errors,
+                }
+                .build()
+            });
+            match (regex, replace) {
+                (Ok(regex), Ok(replace)) => rules.push((regex, replace)),
+                (regex, replace) => errors.extend(regex.err().into_iter().chain(replace.err())),
+            }
+        }
+
+        ensure!(errors.is_empty(), BuildReplacementsSnafu { errors });
+
+        Ok(Self { rules })
+    }
+
+    fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for (regex, replace) in &self.rules {
+            if let Cow::Owned(replaced) = regex.replace_all(&text, replace.as_str()) {
+                tracing::debug!(search = regex.as_str(), "replaced text outside markers");
+                text = Cow::Owned(replaced);
+            }
+        }
+        text
+    }
+}
+
+/// Writes the contents of a marker region, such as `marker::make_marked_contents`.
+pub(super) type MakeContents = fn(&ResolvedReplaceSpecifier, &str, MarkerStyle) -> String;
+
+pub(in super::super) fn replace_all(
+    text: &str,
+    contents: &[Contents],
+    replacements: &Replacements,
+    marker_style: MarkerStyle,
+    make_contents: MakeContents,
+) -> String {
     let pairs = contents
         .iter()
         .map(|contents| (contents, contents.specifier().span));
 
     interpolate_ranges((0..text.len()).into(), pairs)
         .map(|(contents, range)| match contents {
-            Some(contents) => marker::make_marked_contents(contents).into(),
-            None => Cow::Borrowed(&text[range]),
+            Some(contents) => {
+                make_contents(&contents.specifier().value, contents.text(), marker_style).into()
+            }
+            None => replacements.apply(&text[range]),
         })
         .collect()
 }
@@ -45,6 +154,43 @@ fn interpolate_ranges<T>(
 mod tests {
     use similar_asserts::assert_eq;
 
+    use super::*;
+    use crate::sync::marker;
+
+    #[test]
+    fn replace_all_applies_replacements() {
+        let replacements = Replacements {
+            rules: vec![
+                (
+                    Regex::new(r#"foo = "[0-9.]+""#).unwrap(),
+                    r#"foo = "1.2""#.to_owned(),
+                ),
+                (Regex::new(r"foo@(\w+)").unwrap(), "foo@$1-new".to_owned()),
+            ],
+        };
+        let text = "foo = \"0.1\"\nfoo@bar\nfoo = \"1.2\"\n";
+        assert_eq!(
+            replace_all(
+                text,
+                &[],
+                &replacements,
+                MarkerStyle::Html,
+                marker::make_marked_contents
+            ),
+            "foo = \"1.2\"\nfoo@bar-new\nfoo = \"1.2\"\n"
+        );
+        assert_eq!(
+            replace_all(
+                text,
+                &[],
+                &Replacements::default(),
+                MarkerStyle::Html,
+                marker::make_marked_contents
+            ),
+            text
+        );
+    }
+
     #[test]
     fn interpolate_ranges() {
         let items = [(1, (0..1).into()), (2, (1..2).into()), (3, (2..3).into())];
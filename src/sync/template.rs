@@ -0,0 +1,499 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use cargo_metadata::Package;
+use miette::SourceSpan;
+use snafu::Snafu;
+
+use crate::parse::{self, Spanned};
+
+#[derive(Debug, Snafu, miette::Diagnostic)]
+pub(super) enum RenderTemplateError {
+    #[snafu(display("unclosed template variable"))]
+    UnclosedVariable {
+        #[label("expected `}}}}` after this")]
+        span: SourceSpan,
+    },
+    #[snafu(display("unclosed `{{{{#each}}}}` block"))]
+    UnclosedLoop {
+        #[label("expected `{{{{/each}}}}` after this")]
+        span: SourceSpan,
+    },
+    #[snafu(display("unexpected `{{{{/each}}}}` without `{{{{#each}}}}`"))]
+    UnexpectedLoopEnd {
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("invalid template variable name: `{name}`"))]
+    #[diagnostic(help("variable names must match `[A-Za-z][-_A-Za-z0-9]*`"))]
+    InvalidVariableName {
+        name: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("unknown template variable: `{name}`"))]
+    UnknownVariable {
+        name: String,
+        #[help]
+        help: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("template variable `{name}` is not available for this package"))]
+    UnavailableVariable {
+        name: String,
+        #[label]
+        span: SourceSpan,
+    },
+    #[snafu(display("template variable `{name}` is not a list"))]
+    NotAList {
+        name: String,
+        #[help]
+        help: String,
+        #[label]
+        span: SourceSpan,
+    },
+}
+
+impl RenderTemplateError {
+    fn span(&self) -> SourceSpan {
+        match self {
+            Self::UnclosedVariable { span }
+            | Self::UnclosedLoop { span }
+            | Self::UnexpectedLoopEnd { span }
+            | Self::InvalidVariableName { span, .. }
+            | Self::UnknownVariable { span, .. }
+            | Self::UnavailableVariable { span, .. }
+            | Self::NotAList { span, .. } => *span,
+        }
+    }
+}
+
+/// Name of the variable holding the current item in `{{#each}}` blocks.
+const LOOP_ITEM: &str = "this";
+
+/// Values of the variables that can be referenced from templates as `{{name}}`.
+///
+/// A variable whose value is `None` is known but not available, e.g. the
+/// corresponding field is not set in `Cargo.toml`.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Variables<'a> {
+    values: BTreeMap<&'a str, Value<'a>>,
+}
+
+#[derive(Debug, Clone)]
+enum Value<'a> {
+    Scalar(Option<Cow<'a, str>>),
+    List(Vec<Cow<'a, str>>),
+}
+
+impl<'a> Variables<'a> {
+    pub(super) fn from_package(package: &'a Package) -> Self {
+        let mut vars = Self::default();
+        vars.set("name", Some(package.name.as_str().into()));
+        vars.set("package_name", Some(package.name.as_str().into()));
+        vars.set("crate_name", Some(package.name.replace('-', "_").into()));
+        vars.set("version", Some(package.version.to_string().into()));
+        vars.set(
+            "description",
+            package.description.as_deref().map(Into::into),
+        );
+        vars.set("repository", package.repository.as_deref().map(Into::into));
+        vars.set("homepage", package.homepage.as_deref().map(Into::into));
+        vars.set("license", package.license.as_deref().map(Into::into));
+        vars.set(
+            "rust-version",
+            package.rust_version.as_ref().map(|v| v.to_string().into()),
+        );
+        vars.set("edition", Some(package.edition.as_str().into()));
+        vars.set_list(
+            "authors",
+            package.authors.iter().map(|s| s.as_str().into()).collect(),
+        );
+        vars.set_list(
+            "keywords",
+            package.keywords.iter().map(|s| s.as_str().into()).collect(),
+        );
+        vars
+    }
+
+    pub(super) fn set(&mut self, name: &'a str, value: Option<Cow<'a, str>>) {
+        self.values.insert(name, Value::Scalar(value));
+    }
+
+    /// Sets a list variable, which can be iterated with `{{#each name}}`.
+    ///
+    /// If referenced as `{{name}}`, the items are joined with `, `.
+    pub(super) fn set_list(&mut self, name: &'a str, items: Vec<Cow<'a, str>>) {
+        self.values.insert(name, Value::List(items));
+    }
+
+    fn help(&self) -> String {
+        let names = self
+            .values
+            .keys()
+            .map(|name| format!("`{{{{{name}}}}}`"))
+            .collect::<Vec<_>>();
+        format!("available variables: {}", names.join(", "))
+    }
+
+    fn list_help(&self) -> String {
+        let names = self
+            .values
+            .iter()
+            .filter(|(_, value)| matches!(value, Value::List(_)))
+            .map(|(name, _)| format!("`{name}`"))
+            .collect::<Vec<_>>();
+        format!("available lists: {}", names.join(", "))
+    }
+}
+
+#[derive(Debug)]
+enum Node<'a> {
+    Text(&'a str),
+    Variable(Spanned<&'a str>),
+    Each {
+        list: Spanned<&'a str>,
+        body: Vec<Node<'a>>,
+    },
+}
+
+/// Renders `template` by replacing each `{{name}}` with the value of the variable.
+///
+/// `{{#each name}}...{{/each}}` renders the block for each item of the list
+/// variable `name`, with the item available as `{{this}}`. A newline directly
+/// after `{{#each name}}` or `{{/each}}` is removed, so that blocks can be
+/// written on their own lines.
+///
+/// The span of `template` is used to point to the location of errors. If the
+/// length of the span differs from the length of the template (e.g. the
+/// template is a TOML string with escape sequences), errors point to the whole
+/// span.
+pub(super) fn render(
+    template: Spanned<&str>,
+    variables: &Variables<'_>,
+) -> Result<String, Vec<RenderTemplateError>> {
+    let label = |target: Spanned<&str>| {
+        if template.span.end - template.span.start == template.value.len() {
+            target.source_span()
+        } else {
+            template.source_span()
+        }
+    };
+
+    let mut errors = vec![];
+    let mut rest = template;
+    let nodes = parse_nodes(&mut rest, None, &label, &mut errors);
+
+    let mut output = String::new();
+    evaluate(&nodes, variables, None, &label, &mut output, &mut errors);
+
+    if errors.is_empty() {
+        Ok(output)
+    } else {
+        // Errors are found while parsing and while evaluating, so report them in source order.
+        errors.sort_by_key(|error| error.span().offset());
+        Err(errors)
+    }
+}
+
+/// Parses nodes until the end of the template or the `{{/each}}` closing `open`.
+fn parse_nodes<'a>(
+    rest: &mut Spanned<&'a str>,
+    open: Option<Spanned<&'a str>>,
+    label: &impl Fn(Spanned<&str>) -> SourceSpan,
+    errors: &mut Vec<RenderTemplateError>,
+) -> Vec<Node<'a>> {
+    let mut nodes = vec![];
+    while let Some(open_idx) = rest.value.find("{{") {
+        nodes.push(Node::Text(&rest.value[..open_idx]));
+        let tag_open = rest.substr(&rest.value[open_idx..][..2]);
+        let Some((inner, tail)) = rest.value[open_idx + 2..].split_once("}}") else {
+            errors.push(
+                UnclosedVariableSnafu {
+                    span: label(tag_open),
+                }
+                .build(),
+            );
+            *rest = rest.end();
+            return nodes;
+        };
+        let tag = rest.substr(&rest.value[open_idx..][..inner.len() + 4]);
+        let inner = rest.substr(inner).trim();
+        *rest = rest.substr(tail);
+
+        if let Some(list) = inner.strip_prefix_str("#each")
+            && (list.value.is_empty() || list.value.starts_with(char::is_whitespace))
+        {
+            *rest = strip_newline(*rest);
+            let list = list.trim();
+            let body = parse_nodes(rest, Some(tag), label, errors);
+            if validate_name(list, label, errors) {
+                nodes.push(Node::Each { list, body });
+            }
+            continue;
+        }
+        if inner.value == "/each" {
+            *rest = strip_newline(*rest);
+            if open.is_some() {
+                return nodes;
+            }
+            errors.push(UnexpectedLoopEndSnafu { span: label(tag) }.build());
+            continue;
+        }
+        if validate_name(inner, label, errors) {
+            nodes.push(Node::Variable(inner));
+        }
+    }
+    nodes.push(Node::Text(rest.value));
+    *rest = rest.end();
+
+    if let Some(open) = open {
+        errors.push(UnclosedLoopSnafu { span: label(open) }.build());
+    }
+    nodes
+}
+
+fn validate_name(
+    name: Spanned<&str>,
+    label: &impl Fn(Spanned<&str>) -> SourceSpan,
+    errors: &mut Vec<RenderTemplateError>,
+) -> bool {
+    let valid = parse::is_valid_ident(name.value);
+    if !valid {
+        errors.push(
+            InvalidVariableNameSnafu {
+                name: name.value,
+                span: label(name),
+            }
+            .build(),
+        );
+    }
+    valid
+}
+
+fn strip_newline(input: Spanned<&str>) -> Spanned<&str> {
+    input
+        .strip_prefix_str("\r\n")
+        .or_else(|| input.strip_prefix_str("\n"))
+        .unwrap_or(input)
+}
+
+fn evaluate(
+    nodes: &[Node<'_>],
+    variables: &Variables<'_>,
+    item: Option<&str>,
+    label: &impl Fn(Spanned<&str>) -> SourceSpan,
+    output: &mut String,
+    errors: &mut Vec<RenderTemplateError>,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable(name) if name.value == LOOP_ITEM && item.is_some() => {
+                output.push_str(item.unwrap());
+            }
+            Node::Variable(name) => match variables.values.get(name.value) {
+                Some(Value::Scalar(Some(value))) => output.push_str(value),
+                Some(Value::List(items)) => output.push_str(&items.join(", ")),
+                Some(Value::Scalar(None)) => errors.push(
+                    UnavailableVariableSnafu {
+                        name: name.value,
+                        span: label(*name),
+                    }
+                    .build(),
+                ),
+                None => errors.push(
+                    UnknownVariableSnafu {
+                        name: name.value,
+                        help: variables.help(),
+                        span: label(*name),
+                    }
+                    .build(),
+                ),
+            },
+            Node::Each { list, body } => {
+                let items = match variables.values.get(list.value) {
+                    Some(Value::List(items)) => items,
+                    Some(Value::Scalar(_)) => {
+                        errors.push(
+                            NotAListSnafu {
+                                name: list.value,
+                                help: variables.list_help(),
+                                span: label(*list),
+                            }
+                            .build(),
+                        );
+                        continue;
+                    }
+                    None => {
+                        errors.push(
+                            UnknownVariableSnafu {
+                                name: list.value,
+                                help: variables.help(),
+                                span: label(*list),
+                            }
+                            .build(),
+                        );
+                        continue;
+                    }
+                };
+                if items.is_empty() {
+                    // Evaluate the block anyway to report errors in it.
+                    evaluate(body, variables, Some(""), label, &mut String::new(), errors);
+                }
+                for (idx, item) in items.iter().enumerate() {
+                    // Errors in the block are the same for all items, so report them once.
+                    let mut item_errors = vec![];
+                    let item_errors = if idx == 0 {
+                        &mut *errors
+                    } else {
+                        &mut item_errors
+                    };
+                    evaluate(body, variables, Some(item), label, output, item_errors);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches;
+
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    fn variables() -> Variables<'static> {
+        let mut vars = Variables::default();
+        vars.set("package_name", Some("foo-bar".into()));
+        vars.set("version", Some("1.2.3".into()));
+        vars.set("rust-version", None);
+        vars.set_list("keywords", vec!["cli".into(), "markdown".into()]);
+        vars.set_list("authors", vec![]);
+        vars
+    }
+
+    #[test]
+    fn render_variables() {
+        let vars = variables();
+        let render = |s| render(Spanned::from_str(s), &vars).unwrap();
+        assert_eq!(render(""), "");
+        assert_eq!(render("foo"), "foo");
+        assert_eq!(render("{{package_name}}"), "foo-bar");
+        assert_eq!(render("{{ version }}"), "1.2.3");
+        assert_eq!(
+            render("https://example.com/{{package_name}}/{{version}}.svg"),
+            "https://example.com/foo-bar/1.2.3.svg"
+        );
+        assert_eq!(render("{ version }}"), "{ version }}");
+    }
+
+    #[test]
+    fn render_loops() {
+        let vars = variables();
+        let render = |s| render(Spanned::from_str(s), &vars).unwrap();
+        assert_eq!(render("{{keywords}}"), "cli, markdown");
+        assert_eq!(
+            render("{{#each keywords}}[{{this}}]{{/each}}"),
+            "[cli][markdown]"
+        );
+        assert_eq!(
+            render(
+                "Keywords:\n{{#each keywords}}\n* `{{this}}` ({{package_name}})\n{{/each}}\nEnd"
+            ),
+            "Keywords:\n* `cli` (foo-bar)\n* `markdown` (foo-bar)\nEnd"
+        );
+        assert_eq!(render("{{#each authors}}{{this}}{{/each}}"), "");
+    }
+
+    #[test]
+    fn render_loop_errors() {
+        let vars = variables();
+
+        let input = Spanned::from_str("{{#each version}}{{this}}{{/each}}{{/each}}{{this}}");
+        let errors = render(input, &vars).unwrap_err();
+        let [
+            RenderTemplateError::NotAList {
+                name: n1, span: s1, ..
+            },
+            RenderTemplateError::UnexpectedLoopEnd { span: s2 },
+            RenderTemplateError::UnknownVariable {
+                name: n3, span: s3, ..
+            },
+        ] = &*errors
+        else {
+            panic!("unexpected errors: {errors:?}");
+        };
+        assert_eq!(n1, "version");
+        input.assert_source_span(*s1, "version");
+        input.assert_source_span(*s2, "{{/each}}");
+        assert_eq!(n3, "this");
+        input.assert_source_span(*s3, "this");
+
+        // Errors in the block are reported once, even for empty lists.
+        let input =
+            Spanned::from_str("{{#each keywords}}{{x}}{{/each}}{{#each authors}}{{y}}{{/each}}");
+        let errors = render(input, &vars).unwrap_err();
+        let [
+            RenderTemplateError::UnknownVariable { name: n1, .. },
+            RenderTemplateError::UnknownVariable { name: n2, .. },
+        ] = &*errors
+        else {
+            panic!("unexpected errors: {errors:?}");
+        };
+        assert_eq!(n1, "x");
+        assert_eq!(n2, "y");
+
+        let input = Spanned::from_str("foo {{#each keywords}}{{this}}");
+        let errors = render(input, &vars).unwrap_err();
+        let [RenderTemplateError::UnclosedLoop { span }] = &*errors else {
+            panic!("unexpected errors: {errors:?}");
+        };
+        input.assert_source_span(*span, "{{#each keywords}}");
+    }
+
+    #[test]
+    fn render_errors() {
+        let vars = variables();
+
+        let input = Spanned::from_str("https://{{unknown}}/{{version}}/{{rust-version}}/{{!}}");
+        let errors = render(input, &vars).unwrap_err();
+        let [
+            RenderTemplateError::UnknownVariable {
+                name: n1, span: s1, ..
+            },
+            RenderTemplateError::UnavailableVariable { name: n2, span: s2 },
+            RenderTemplateError::InvalidVariableName { name: n3, span: s3 },
+        ] = &*errors
+        else {
+            panic!("unexpected errors: {errors:?}");
+        };
+        assert_eq!(n1, "unknown");
+        input.assert_source_span(*s1, "unknown");
+        assert_eq!(n2, "rust-version");
+        input.assert_source_span(*s2, "rust-version");
+        assert_eq!(n3, "!");
+        input.assert_source_span(*s3, "!");
+
+        let input = Spanned::from_str("foo {{version");
+        let errors = render(input, &vars).unwrap_err();
+        let [RenderTemplateError::UnclosedVariable { span }] = &*errors else {
+            panic!("unexpected errors: {errors:?}");
+        };
+        input.assert_source_span(*span, "{{");
+    }
+
+    #[test]
+    fn render_errors_with_unaligned_span() {
+        let vars = variables();
+
+        // e.g. `"0\u0031{{unknown}}"` in TOML
+        let input = Spanned::new("01{{unknown}}", 10..31);
+        let errors = render(input, &vars).unwrap_err();
+        assert_matches!(
+            &*errors,
+            [RenderTemplateError::UnknownVariable { span, .. }] if *span == input.source_span()
+        );
+    }
+}
@@ -8,6 +8,8 @@ use serde::Deserialize;
 use snafu::{ResultExt as _, Snafu};
 use toml::Spanned;
 
+use crate::parse;
+
 #[derive(Debug, Snafu, miette::Diagnostic)]
 pub(crate) enum ReadFileError {
     #[snafu(display("failed to read {name}: {path}"))]
@@ -70,6 +72,20 @@ pub(crate) struct WithSource<T> {
     value: T,
 }
 
+impl WithSource<()> {
+    /// Reads the source text without parsing it.
+    pub(crate) fn open(
+        name: impl Into<String>,
+        path: impl Into<Utf8PathBuf>,
+    ) -> Result<Self, ReadFileError> {
+        let source_info = Rc::new(SourceInfo::open(name, path)?);
+        Ok(Self {
+            source_info,
+            value: (),
+        })
+    }
+}
+
 impl<T> WithSource<T> {
     pub(crate) fn from_toml(
         name: impl Into<String>,
@@ -94,35 +110,26 @@ impl<T> WithSource<T> {
         Ok(Self { source_info, value })
     }
 
-    pub(crate) fn from_json(
-        name: impl Into<String>,
-        path: impl Into<Utf8PathBuf>,
-    ) -> Result<Self, ReadFileError>
+    /// Parses the source text as JSON, as another type.
+    pub(crate) fn parse_json<U>(&self) -> Result<WithSource<U>, ReadFileError>
     where
-        T: for<'de> Deserialize<'de>,
+        U: for<'de> Deserialize<'de>,
     {
-        let source_info = SourceInfo::open(name, path)?;
-
-        let value: T = serde_json::from_str(&source_info.text).with_context(|source| {
-            let offset =
-                SourceOffset::from_location(&source_info.text, source.line(), source.column());
-            let label = SourceSpan::new(offset, 1);
-            let source_code = source_info.to_named_source();
-            ParseJsonSnafu {
-                name: source_info.name.clone(),
-                source_code,
-                label,
-            }
-        })?;
-
-        let source_info = Rc::new(source_info);
-        Ok(Self { source_info, value })
+        let value = parse_json(&self.source_info)?;
+        Ok(WithSource {
+            source_info: Rc::clone(&self.source_info),
+            value,
+        })
     }
 
     pub(crate) fn name(&self) -> &str {
         &self.source_info.name
     }
 
+    pub(crate) fn text(&self) -> &str {
+        &self.source_info.text
+    }
+
     pub(crate) fn value(&self) -> &T {
         &self.value
     }
@@ -155,8 +162,46 @@ impl<T> WithSource<T> {
     }
 }
 
+fn parse_json<T>(source_info: &SourceInfo) -> Result<T, ReadFileError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    serde_json::from_str(&source_info.text).with_context(|source| {
+        let offset = SourceOffset::from_location(&source_info.text, source.line(), source.column());
+        let label = SourceSpan::new(offset, 1);
+        let source_code = source_info.to_named_source();
+        ParseJsonSnafu {
+            name: source_info.name.clone(),
+            source_code,
+            label,
+        }
+    })
+}
+
 impl<T> WithSource<&'_ Spanned<T>> {
     pub(crate) fn span(&self) -> SourceSpan {
         SourceSpan::from(self.value().span())
     }
 }
+
+impl<'a> WithSource<&'a Spanned<String>> {
+    /// Returns the string value with the span of its contents in the source text.
+    ///
+    /// If the string literal contains escape sequences, the span covers the
+    /// whole literal instead.
+    pub(crate) fn spanned_str(&self) -> parse::Spanned<&'a str> {
+        let value = self.value.get_ref().as_str();
+        let span = self.value.span();
+        let literal = &self.source_info.text[span.clone()];
+        let contents = ["\"\"\"", "'''", "\"", "'"]
+            .into_iter()
+            .find_map(|quote| literal.strip_prefix(quote)?.strip_suffix(quote));
+        match contents {
+            Some(contents) if contents == value => {
+                let start = span.start + (literal.len() - contents.len()) / 2;
+                parse::Spanned::new(value, start..start + contents.len())
+            }
+            _ => parse::Spanned::new(value, span),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Integration test to ensure that hand edits of marker regions are detected by content hashes.
+
+use std::fs;
+
+use indoc::indoc;
+use similar_asserts::assert_eq;
+use test_helper::Workspace;
+
+fn setup() -> Workspace {
+    let workspace = Workspace::from_fixture("link_showcase");
+    workspace.insert_crate_doc_comment("src/lib.rs", "//! Crate docs.\n\n");
+    let manifest_path = workspace.root_path().join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path).unwrap();
+    fs::write(
+        &manifest_path,
+        manifest
+            + indoc! {"
+
+                [package.metadata.cargo-sync-rdme]
+                content-hash = true
+            "},
+    )
+    .unwrap();
+    workspace
+}
+
+#[test]
+fn records_content_hash_in_start_marker() {
+    let workspace = setup();
+    workspace.cargo_sync_rdme_default().assert().success();
+
+    let readme = fs::read_to_string(workspace.root_path().join("README.md")).unwrap();
+    assert_eq!(
+        readme,
+        indoc! {"
+            # link showcase
+
+            <!-- cargo-sync-rdme rustdoc [[ sha=252295cc -->
+            Crate docs.
+            <!-- cargo-sync-rdme ]] -->
+        "}
+    );
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--check"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn updates_stale_region() {
+    let workspace = setup();
+    workspace.cargo_sync_rdme_default().assert().success();
+
+    let lib_path = workspace.root_path().join("src/lib.rs");
+    let lib = fs::read_to_string(&lib_path).unwrap();
+    fs::write(&lib_path, lib.replace("Crate docs.", "Updated crate docs.")).unwrap();
+    workspace.cargo_sync_rdme_default().assert().success();
+
+    let readme = fs::read_to_string(workspace.root_path().join("README.md")).unwrap();
+    assert!(readme.contains("\nUpdated crate docs.\n"), "{readme}");
+}
+
+#[test]
+fn refuses_to_overwrite_hand_edits() {
+    let workspace = setup();
+    workspace.cargo_sync_rdme_default().assert().success();
+
+    let readme_path = workspace.root_path().join("README.md");
+    let readme = fs::read_to_string(&readme_path).unwrap();
+    let edited = readme.replace("Crate docs.", "Edited crate docs.");
+    fs::write(&readme_path, &edited).unwrap();
+
+    for args in [&[][..], &["--check"]] {
+        let output = workspace
+            .cargo_sync_rdme_default()
+            .args(args)
+            .assert()
+            .failure()
+            .get_output()
+            .clone();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("has marker regions edited"), "{stderr}");
+        assert_eq!(fs::read_to_string(&readme_path).unwrap(), edited);
+    }
+
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--force"])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&readme_path).unwrap(), readme);
+}
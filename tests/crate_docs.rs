@@ -0,0 +1,80 @@
+//! Integration test to ensure that `--reverse` synchronizes crate documentation from the README.
+
+use std::fs;
+
+use indoc::indoc;
+use similar_asserts::assert_eq;
+use test_helper::Workspace;
+
+#[test]
+fn syncs_crate_docs_from_readme() {
+    let fixture_name = "link_showcase";
+    let workspace = Workspace::from_fixture(fixture_name);
+    workspace.insert_crate_doc_comment(
+        "src/lib.rs",
+        indoc! {"
+            // cargo-sync-rdme rustdoc [[
+            //! Crate docs with [`Struct`] and [the trait](Trait).
+            //!
+            //! # Usage
+            //!
+            //! See [`module`].
+            // cargo-sync-rdme ]]
+
+        "},
+    );
+
+    workspace.cargo_sync_rdme_default().assert().success();
+
+    let package = workspace.package("link-showcase").unwrap();
+    let readme_path = package.readme().unwrap();
+    let readme = fs::read_to_string(&readme_path).unwrap();
+    fs::write(&readme_path, readme.replace("See", "Also see")).unwrap();
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["--reverse", "--check"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("crate root for package `link-showcase` is not up to date: src/lib.rs"),
+        "{stderr}"
+    );
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--reverse"])
+        .assert()
+        .success();
+
+    let librs = fs::read_to_string(workspace.root_path().join("src/lib.rs")).unwrap();
+    let (docs, _rest) = librs.split_once("\n\n").unwrap();
+    assert_eq!(
+        docs,
+        indoc! {"
+            // cargo-sync-rdme rustdoc [[
+            //! Crate docs with [`Struct`] and [the trait][Trait].
+            //!
+            //! # Usage
+            //!
+            //! Also see [`module`].
+            //!
+            //! [`Struct`]: Struct
+            //! [Trait]: Trait
+            //! [`module`]: module
+            // cargo-sync-rdme ]]"}
+    );
+
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--reverse", "--check"])
+        .assert()
+        .success();
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--check"])
+        .assert()
+        .success();
+}
@@ -0,0 +1,86 @@
+//! Integration test to ensure that crate documentation can be read from source without rustdoc.
+
+use std::fs;
+
+use test_helper::{self as helper, Workspace};
+
+const RUSTDOC_COMMAND_LOG: &str = "executing rustdoc command";
+
+#[test]
+fn syncs_crate_docs_from_source() {
+    let workspace = Workspace::from_fixture("workspace");
+
+    let output = workspace
+        .cargo_sync_rdme()
+        .allow_no_vcs()
+        .args(["-v", "--workspace", "--docs-from-source"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains(RUSTDOC_COMMAND_LOG), "{stderr}");
+    for package in ["", "pkg-a", "pkg-b"] {
+        let readme = workspace.root_path().join(package).join("README.md");
+        let items = helper::collect_list_item_from_markdown_file(readme);
+        assert_eq!(items.len(), 1, "{package}: {items:?}");
+        assert_eq!(items[0].trim(), "UPDATED", "{package}");
+    }
+}
+
+#[test]
+fn resolves_intra_doc_links_by_mappings() {
+    let workspace = Workspace::from_fixture("workspace");
+    workspace.insert_crate_doc_comment(
+        "pkg-a/src/lib.rs",
+        "//! See [`Mapped`] and [`Unmapped`].\n//!\n",
+    );
+    let manifest_path = workspace.root_path().join("pkg-a/Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path).unwrap();
+    let manifest = manifest.replace(
+        "[package.metadata.cargo-sync-rdme.rustdoc]\n",
+        "[package.metadata.cargo-sync-rdme.rustdoc]\nmappings = { \"`Mapped`\" = \"https://example.com/mapped\" }\n",
+    );
+    fs::write(&manifest_path, manifest).unwrap();
+
+    let output = workspace
+        .cargo_sync_rdme()
+        .allow_no_vcs()
+        .args(["-p", "pkg-a", "--docs-from-source"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("`rustdoc.mappings`: `Unmapped`"),
+        "{stderr}"
+    );
+
+    let readme = fs::read_to_string(workspace.root_path().join("pkg-a/README.md")).unwrap();
+    assert!(readme.contains("https://example.com/mapped"), "{readme}");
+}
+
+#[test]
+fn rejects_markers_requiring_rustdoc_json() {
+    let workspace = Workspace::from_fixture("workspace");
+    fs::write(
+        workspace.root_path().join("pkg-a/README.md"),
+        "<!-- cargo-sync-rdme api-summary -->\n",
+    )
+    .unwrap();
+
+    let output = workspace
+        .cargo_sync_rdme()
+        .allow_no_vcs()
+        .args(["-p", "pkg-a", "--docs-from-source"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("`api-summary` marker requires rustdoc JSON"),
+        "{stderr}"
+    );
+}
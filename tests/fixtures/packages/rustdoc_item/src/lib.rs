@@ -0,0 +1,20 @@
+//! <!-- SYNC_RDME_INTEGRATION_TEST::SPAN_START -->
+//! * crate root
+//! <!-- SYNC_RDME_INTEGRATION_TEST::SPAN_END -->
+
+pub use inner::Reexported;
+
+/// <!-- SYNC_RDME_INTEGRATION_TEST::SPAN_START -->
+/// * struct Foo
+/// <!-- SYNC_RDME_INTEGRATION_TEST::SPAN_END -->
+pub struct Foo;
+
+/// <!-- SYNC_RDME_INTEGRATION_TEST::SPAN_START -->
+/// * module inner
+/// <!-- SYNC_RDME_INTEGRATION_TEST::SPAN_END -->
+pub mod inner {
+    /// <!-- SYNC_RDME_INTEGRATION_TEST::SPAN_START -->
+    /// * struct inner::Reexported
+    /// <!-- SYNC_RDME_INTEGRATION_TEST::SPAN_END -->
+    pub struct Reexported;
+}
@@ -0,0 +1,84 @@
+//! Integration test to ensure that packages synchronized in parallel behave like a serial run.
+
+use similar_asserts::assert_eq;
+use test_helper::Workspace;
+
+fn run(workspace: &Workspace, args: &[&str]) -> (bool, String, String) {
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["--workspace"])
+        .args(args)
+        .assert()
+        .get_output()
+        .clone();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn parallel_output_matches_serial_output() {
+    let workspace = Workspace::from_fixture("workspace");
+
+    let serial = run(&workspace, &["--check", "-j", "1"]);
+    let parallel = run(&workspace, &["--check", "-j", "3"]);
+    assert!(!serial.0);
+    assert_eq!(serial, parallel);
+}
+
+#[test]
+fn reports_errors_of_all_packages() {
+    let workspace = Workspace::from_fixture("workspace");
+
+    let (success, _stdout, stderr) = run(&workspace, &["--check", "-j", "2"]);
+    assert!(!success);
+    assert!(stderr.contains("failed to synchronize 3 packages"));
+    let positions = ["pkg-a", "pkg-b", "root"].map(|name| {
+        stderr
+            .find(&format!(
+                "markdown file for package `{name}` is not up to date"
+            ))
+            .unwrap_or_else(|| panic!("missing error for `{name}`:\n{stderr}"))
+    });
+    assert!(positions.is_sorted(), "errors are not ordered:\n{stderr}");
+}
+
+#[test]
+fn synchronizes_all_packages_in_parallel() {
+    let workspace = Workspace::from_fixture("workspace");
+
+    let (success, _stdout, stderr) = run(&workspace, &["-j", "3"]);
+    assert!(success, "{stderr}");
+    let (success, _stdout, stderr) = run(&workspace, &["--check", "-j", "3"]);
+    assert!(success, "{stderr}");
+}
+
+#[test]
+fn prints_rustdoc_warnings_in_package_order() {
+    let workspace = Workspace::from_fixture("workspace");
+    for (path, name) in [
+        ("pkg-a/src/lib.rs", "MissingA"),
+        ("pkg-b/src/lib.rs", "MissingB"),
+        ("src/lib.rs", "MissingRoot"),
+    ] {
+        workspace.insert_crate_doc_comment(path, &format!("//! [{name}]\n"));
+    }
+
+    let serial = run(&workspace, &["-j", "1"]);
+    let parallel = run(&workspace, &["--check", "-j", "3"]);
+    assert!(serial.0, "{}", serial.2);
+    assert!(parallel.0, "{}", parallel.2);
+    let positions = ["MissingA", "MissingB", "MissingRoot"].map(|name| {
+        parallel
+            .2
+            .find(&format!("unresolved link to `{name}`"))
+            .unwrap_or_else(|| panic!("missing warning for `{name}`:\n{}", parallel.2))
+    });
+    assert!(
+        positions.is_sorted(),
+        "warnings are not ordered:\n{}",
+        parallel.2
+    );
+}
@@ -0,0 +1,99 @@
+//! Integration test to ensure that rustdoc output is reused across markers and invocations.
+
+use std::fs;
+
+use indoc::indoc;
+use test_helper::Workspace;
+
+const RUSTDOC_COMMAND_LOG: &str = "executing rustdoc command";
+
+fn setup() -> Workspace {
+    let workspace = Workspace::from_fixture("link_showcase");
+    workspace.insert_crate_doc_comment("src/lib.rs", "//! Crate docs.\n\n");
+    fs::write(
+        workspace.root_path().join("README.md"),
+        indoc! {"
+            # link showcase
+
+            <!-- cargo-sync-rdme rustdoc -->
+
+            <!-- cargo-sync-rdme rustdoc:Struct -->
+
+            <!-- cargo-sync-rdme api-summary -->
+        "},
+    )
+    .unwrap();
+    workspace
+}
+
+fn run_and_count_rustdoc(workspace: &Workspace, args: &[&str]) -> usize {
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["-v"])
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    stderr.matches(RUSTDOC_COMMAND_LOG).count()
+}
+
+#[test]
+fn runs_rustdoc_once_per_invocation() {
+    let workspace = setup();
+    assert_eq!(run_and_count_rustdoc(&workspace, &[]), 1);
+}
+
+#[test]
+fn reuses_cached_rustdoc_output() {
+    let workspace = setup();
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--cache"]), 1);
+    assert_eq!(
+        run_and_count_rustdoc(&workspace, &["--cache", "--check"]),
+        0
+    );
+
+    let lib_path = workspace.root_path().join("src/lib.rs");
+    let lib = fs::read_to_string(&lib_path).unwrap();
+    fs::write(&lib_path, lib.replace("Crate docs.", "Updated crate docs.")).unwrap();
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--cache"]), 1);
+    assert_eq!(
+        run_and_count_rustdoc(&workspace, &["--cache", "--check"]),
+        0
+    );
+
+    // Without `--cache`, rustdoc is run every time.
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--check"]), 1);
+}
+
+#[test]
+fn invalidates_cached_rustdoc_output() {
+    let workspace = setup();
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--cache"]), 1);
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--cache"]), 0);
+
+    // Files outside of the target source directories may be read by `include_str!`.
+    fs::write(workspace.root_path().join("NOTES.md"), "notes\n").unwrap();
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--cache"]), 1);
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--cache"]), 0);
+
+    // Path dependencies are inputs, but other packages are not.
+    fs::write(workspace.root_path().join("pkg-a/NOTES.md"), "notes\n").unwrap();
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--cache"]), 1);
+    let other = workspace.root_path().join("other");
+    fs::create_dir(&other).unwrap();
+    fs::write(other.join("Cargo.toml"), "[package]\nname = \"other\"\n").unwrap();
+    assert_eq!(run_and_count_rustdoc(&workspace, &["--cache"]), 0);
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["-v", "--cache"])
+        .envs([("RUSTDOCFLAGS", "--cfg docsrs")])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.matches(RUSTDOC_COMMAND_LOG).count(), 1);
+}
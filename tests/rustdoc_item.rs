@@ -0,0 +1,18 @@
+//! Integration test to ensure that `rustdoc:<path>` markers are replaced with the documentation of the specified items.
+
+use test_helper::{self as helper, Workspace};
+
+#[test]
+fn syncs_item_docs() {
+    let fixture_name = "rustdoc_item";
+    let workspace = Workspace::from_fixture(fixture_name);
+
+    workspace.cargo_sync_rdme_default().assert().success();
+
+    let package = workspace.package("rustdoc-item").unwrap();
+    let list_item = helper::collect_list_item_from_markdown_file(package.readme().unwrap());
+    assert_eq!(
+        list_item,
+        ["struct Foo", "module inner", "struct inner::Reexported"]
+    );
+}
@@ -0,0 +1,148 @@
+//! Integration test to ensure that pre-built rustdoc JSON is used instead of running rustdoc.
+
+use std::{fs, path::PathBuf, process::Command};
+
+use rstest::rstest;
+use test_helper::{self as helper, Workspace};
+
+const RUSTDOC_COMMAND_LOG: &str = "executing rustdoc command";
+
+fn setup() -> (Workspace, PathBuf) {
+    let workspace = Workspace::from_fixture("workspace");
+    // Build rustdoc JSON in the same way as tools like cargo-semver-checks.
+    for package in ["root", "pkg-a", "pkg-b"] {
+        let status = Command::new("rustup")
+            .args(["run", "nightly", "cargo", "rustdoc", "-q", "-p", package])
+            .args(["--", "-Zunstable-options", "--output-format=json"])
+            .current_dir(workspace.root_path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    let doc_dir = workspace
+        .metadata()
+        .target_directory
+        .join("doc")
+        .into_std_path_buf();
+    (workspace, doc_dir)
+}
+
+fn readme_items(workspace: &Workspace, package: &str) -> Vec<String> {
+    let readme = workspace.root_path().join(package).join("README.md");
+    helper::collect_list_item_from_markdown_file(readme)
+}
+
+#[test]
+fn reads_rustdoc_json_from_directory() {
+    let (workspace, doc_dir) = setup();
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["-v", "--workspace", "--rustdoc-json"])
+        .args([&doc_dir])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains(RUSTDOC_COMMAND_LOG), "{stderr}");
+    for package in ["", "pkg-a", "pkg-b"] {
+        let items = readme_items(&workspace, package);
+        assert_eq!(items.len(), 1, "{package}: {items:?}");
+        assert_eq!(items[0].trim(), "UPDATED", "{package}");
+    }
+}
+
+#[test]
+fn reads_rustdoc_json_from_config() {
+    let (workspace, _doc_dir) = setup();
+    let manifest_path = workspace.root_path().join("pkg-a/Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path).unwrap();
+    let manifest = manifest.replace(
+        "[package.metadata.cargo-sync-rdme.rustdoc]\n",
+        "[package.metadata.cargo-sync-rdme.rustdoc]\njson = \"../target/doc/pkg_a.json\"\n",
+    );
+    fs::write(&manifest_path, manifest).unwrap();
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["-v", "-p", "pkg-a"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains(RUSTDOC_COMMAND_LOG), "{stderr}");
+    assert_eq!(readme_items(&workspace, "pkg-a")[0].trim(), "UPDATED");
+}
+
+#[test]
+fn rejects_rustdoc_json_of_another_crate() {
+    let (workspace, doc_dir) = setup();
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["-p", "pkg-b", "--rustdoc-json"])
+        .args([doc_dir.join("pkg_a.json")])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("rustdoc JSON of crate `pkg_a` does not match package pkg-b"),
+        "{stderr}"
+    );
+}
+
+#[rstest]
+#[case(1, "rustdoc JSON format version 1 is older than supported")]
+#[case(9999, "rustdoc JSON format version 9999 is newer than supported")]
+fn rejects_unsupported_format_version(#[case] version: u32, #[case] message: &str) {
+    let (workspace, doc_dir) = setup();
+    let json_path = doc_dir.join("pkg_a.json");
+    let mut json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    json["format_version"] = version.into();
+    fs::write(&json_path, json.to_string()).unwrap();
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["-p", "pkg-a", "--rustdoc-json"])
+        .args([&json_path])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(message), "{stderr}");
+}
+
+#[test]
+fn reads_older_format_version() {
+    let (workspace, doc_dir) = setup();
+    let json_path = doc_dir.join("pkg_a.json");
+    let mut json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    // Format version 53 has string attributes and no external crate paths.
+    json["format_version"] = 53.into();
+    for item in json["index"].as_object_mut().unwrap().values_mut() {
+        item["attrs"] = serde_json::json!(["#[doc(hidden)]"]);
+    }
+    for krate in json["external_crates"]
+        .as_object_mut()
+        .unwrap()
+        .values_mut()
+    {
+        krate.as_object_mut().unwrap().remove("path");
+    }
+    fs::write(&json_path, json.to_string()).unwrap();
+
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["-p", "pkg-a", "--rustdoc-json"])
+        .args([&json_path])
+        .assert()
+        .success();
+    assert_eq!(readme_items(&workspace, "pkg-a")[0].trim(), "UPDATED");
+}
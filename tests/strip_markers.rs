@@ -0,0 +1,169 @@
+//! Integration test to ensure that `--strip-markers` writes copies of target files without markers.
+
+use std::fs;
+
+use indoc::indoc;
+use similar_asserts::assert_eq;
+use test_helper::Workspace;
+
+fn setup() -> Workspace {
+    let workspace = Workspace::from_fixture("link_showcase");
+    workspace.insert_crate_doc_comment("src/lib.rs", "//! Crate docs.\n\n");
+    workspace
+}
+
+fn set_output(workspace: &Workspace, output: &str) {
+    let manifest_path = workspace.root_path().join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path).unwrap();
+    fs::write(
+        &manifest_path,
+        manifest
+            + &format!(
+                "\n[package.metadata.cargo-sync-rdme.strip-markers-output]\n\"README.md\" = {output:?}\n"
+            ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn writes_stripped_copy_to_stdout() {
+    let workspace = setup();
+    let readme_path = workspace.root_path().join("README.md");
+
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers"])
+        .assert()
+        .success()
+        .stdout_eq(indoc! {"
+            # link showcase
+
+            Crate docs.
+        "});
+    // The target file is synchronized before the copy is written.
+    assert_eq!(
+        fs::read_to_string(&readme_path).unwrap(),
+        indoc! {"
+            # link showcase
+
+            <!-- cargo-sync-rdme rustdoc [[ -->
+            Crate docs.
+            <!-- cargo-sync-rdme ]] -->
+        "}
+    );
+}
+
+#[test]
+fn writes_stripped_copy_to_output_path() {
+    let workspace = setup();
+    set_output(&workspace, "target/publish/README.md");
+
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers", "--check"])
+        .assert()
+        .failure();
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers"])
+        .assert()
+        .success()
+        .stdout_eq("");
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers", "--check"])
+        .assert()
+        .success();
+
+    let output = workspace.root_path().join("target/publish/README.md");
+    assert_eq!(
+        fs::read_to_string(output).unwrap(),
+        indoc! {"
+            # link showcase
+
+            Crate docs.
+        "}
+    );
+}
+
+#[test]
+fn rejects_check_without_output_path() {
+    let workspace = setup();
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers", "--check"])
+        .assert()
+        .failure()
+        .stdout_eq("")
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("no output path configured for markdown file with markers stripped"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn rejects_output_path_of_target_file() {
+    let workspace = setup();
+    set_output(&workspace, "src/../README.md");
+    let readme_path = workspace.root_path().join("README.md");
+    let readme = fs::read_to_string(&readme_path).unwrap();
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("is a target file"), "{stderr}");
+    assert_eq!(fs::read_to_string(&readme_path).unwrap(), readme);
+}
+
+#[test]
+fn rejects_output_path_outside_of_workspace() {
+    let workspace = setup();
+    set_output(&workspace, "../README.md");
+
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("is outside of the workspace"), "{stderr}");
+    assert!(!workspace.root_path().join("../README.md").exists());
+}
+
+#[test]
+fn checks_target_file_with_copy() {
+    let workspace = setup();
+    set_output(&workspace, "target/publish/README.md");
+    workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers"])
+        .assert()
+        .success();
+
+    let readme_path = workspace.root_path().join("README.md");
+    let readme = fs::read_to_string(&readme_path).unwrap();
+    fs::write(&readme_path, readme.replace("Crate docs.", "Edited.")).unwrap();
+    let output = workspace
+        .cargo_sync_rdme_default()
+        .args(["--strip-markers", "--check"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("markdown file for package `link-showcase` is not up to date: README.md"),
+        "{stderr}"
+    );
+}